@@ -0,0 +1,1100 @@
+use log::{error, warn};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::config_manager::TargetSettings;
+use crate::delivery_retry::{DEFAULT_INITIAL_DELAY_MS, DEFAULT_MAX_ATTEMPTS};
+use crate::message_template::{validate_message_template, Language};
+use crate::notification_routing::{RoutingRule, ROUTING_RULES_KEY};
+use crate::notifier_instance::{NotifierInstance, NOTIFIER_INSTANCES_KEY, NOTIFIER_KINDS};
+
+/// 画像差分のしきい値の既定値。
+pub const DEFAULT_THRESHOLD: f32 = 0.05;
+/// 監視間隔の既定値（ミリ秒）。
+pub const DEFAULT_INTERVAL: u64 = 1000;
+/// 監視間隔の最小値（ミリ秒）。
+const MIN_INTERVAL: u64 = 100;
+const TARGET_SETTINGS_KEY: &str = "TARGET_SETTINGS";
+
+/// アプリケーションの設定（`appsettings.json`）。
+///
+/// # 概要
+/// 設定ファイルのキーはこれまでと同じ大文字のキー（例: `THRESHOLD`）で、値の多くは文字列（`"0.050"`、`"1000"`、`"true"`）で保存されています。
+/// 読み込み時は文字列と JSON の数値・真偽値のどちらも受け付けて型付きの値へ変換し、キーが存在しない場合は既定値を使用します。
+/// 1 つの不正な値で設定の取得や監視の開始が失敗しないよう、読み込み時は不正な値を警告して読み飛ばし、検証は保存時に行います。
+/// 書き込み時は従来のバージョンや通知先の構築処理が読めるよう、これまでどおり文字列として保存します。
+/// このアプリが扱わないキーは `other` に保持し、保存時にそのまま書き戻します。
+///
+/// # フィールド
+/// - `threshold`: 画像差分のしきい値（`0.0〜1.0`）。
+/// - `interval`: 監視間隔（ミリ秒）。
+/// - `minimize_on_monitor_start`: 監視開始時に対象ウィンドウを最小化するかどうか。
+/// - `resume_monitoring_on_startup`: 起動時に前回の監視対象の監視を再開するかどうか。
+/// - `target_settings`: 監視対象ごとの設定。`AppInfo::identity` をキーとします。
+/// - `notify_retry_max_attempts`: 通知の送信を試みる最大回数（初回を含む）。
+/// - `notify_retry_initial_delay_ms`: 初回の再送までの待ち時間（ミリ秒）。
+/// - `notify_language`: 通知の言語。
+/// - `message_templates`: 通知先ごとの本文テンプレート。
+/// - `routing_rules`: 通知先のルーティングルール（評価順）。
+/// - `notifier_instances`: 名前付きの通知先のインスタンス。
+/// - `discord`〜`command`: 通知先ごとの設定。各項目は従来のキー（例: `DISCORD_WEBHOOK_URL`）へそのまま展開して保存されます。
+/// - `other`: このアプリが扱わないキー。
+/// - `discarded`: 読み込み時に読み飛ばした値。設定ファイルには保存せず、保存時に書き戻すために保持します。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    #[serde(rename = "THRESHOLD", with = "string_value")]
+    pub threshold: f32,
+    #[serde(rename = "INTERVAL", with = "string_value")]
+    pub interval: u64,
+    #[serde(rename = "MINIMIZE_ON_MONITOR_START", with = "string_value")]
+    pub minimize_on_monitor_start: bool,
+    #[serde(rename = "RESUME_MONITORING_ON_STARTUP", with = "string_value")]
+    pub resume_monitoring_on_startup: bool,
+    #[serde(rename = "TARGET_SETTINGS")]
+    pub target_settings: HashMap<String, TargetSettings>,
+    #[serde(rename = "NOTIFY_RETRY_MAX_ATTEMPTS", with = "string_value")]
+    pub notify_retry_max_attempts: u32,
+    #[serde(rename = "NOTIFY_RETRY_INITIAL_DELAY_MS", with = "string_value")]
+    pub notify_retry_initial_delay_ms: u64,
+    #[serde(rename = "NOTIFY_LANGUAGE", with = "language")]
+    pub notify_language: Language,
+    #[serde(rename = "MESSAGE_TEMPLATES")]
+    pub message_templates: HashMap<String, String>,
+    #[serde(rename = "ROUTING_RULES")]
+    pub routing_rules: Vec<RoutingRule>,
+    #[serde(rename = "NOTIFIER_INSTANCES")]
+    pub notifier_instances: Vec<NotifierInstance>,
+    #[serde(flatten)]
+    pub discord: DiscordConfig,
+    #[serde(flatten)]
+    pub line: LineConfig,
+    #[serde(flatten)]
+    pub slack: SlackConfig,
+    #[serde(flatten)]
+    pub teams: TeamsConfig,
+    #[serde(flatten)]
+    pub telegram: TelegramConfig,
+    #[serde(flatten)]
+    pub ntfy: NtfyConfig,
+    #[serde(flatten)]
+    pub gotify: GotifyConfig,
+    #[serde(flatten)]
+    pub pushover: PushoverConfig,
+    #[serde(flatten)]
+    pub smtp: SmtpConfig,
+    #[serde(flatten)]
+    pub http_webhook: HttpWebhookConfig,
+    #[serde(flatten)]
+    pub command: CommandConfig,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+    #[serde(skip)]
+    discarded: DiscardedValues,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_THRESHOLD,
+            interval: DEFAULT_INTERVAL,
+            minimize_on_monitor_start: true,
+            resume_monitoring_on_startup: false,
+            target_settings: HashMap::new(),
+            notify_retry_max_attempts: DEFAULT_MAX_ATTEMPTS,
+            notify_retry_initial_delay_ms: DEFAULT_INITIAL_DELAY_MS,
+            notify_language: Language::default(),
+            message_templates: HashMap::new(),
+            routing_rules: Vec::new(),
+            notifier_instances: Vec::new(),
+            discord: DiscordConfig::default(),
+            line: LineConfig::default(),
+            slack: SlackConfig::default(),
+            teams: TeamsConfig::default(),
+            telegram: TelegramConfig::default(),
+            ntfy: NtfyConfig::default(),
+            gotify: GotifyConfig::default(),
+            pushover: PushoverConfig::default(),
+            smtp: SmtpConfig::default(),
+            http_webhook: HttpWebhookConfig::default(),
+            command: CommandConfig::default(),
+            other: Map::new(),
+            discarded: DiscardedValues::default(),
+        }
+    }
+}
+
+/// Discord 通知の設定。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiscordConfig {
+    #[serde(rename = "DISCORD_WEBHOOK_URL")]
+    pub webhook_url: String,
+    #[serde(rename = "DISCORD_USERNAME")]
+    pub username: String,
+    #[serde(rename = "DISCORD_AVATAR_URL")]
+    pub avatar_url: String,
+    #[serde(rename = "DISCORD_MENTION_ROLE_IDS")]
+    pub mention_role_ids: String,
+    #[serde(rename = "DISCORD_MENTION_USER_IDS")]
+    pub mention_user_ids: String,
+}
+
+impl DiscordConfig {
+    /// メンションするロール ID とユーザー ID がカンマ区切りの数字であることを検証します。
+    pub fn validate(&self) -> Result<(), String> {
+        for ids in [&self.mention_role_ids, &self.mention_user_ids] {
+            let valid = ids
+                .split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .all(|id| id.chars().all(|c| c.is_ascii_digit()));
+            if !valid {
+                return Err(
+                    "メンションするロール ID とユーザー ID はカンマ区切りの数字で指定してください。"
+                        .to_string(),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// LINE 通知の設定。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LineConfig {
+    #[serde(rename = "LINE_ENABLED", with = "string_value")]
+    pub enabled: bool,
+    #[serde(rename = "LINE_CHANNEL_ACCESS_TOKEN")]
+    pub channel_access_token: String,
+    #[serde(rename = "LINE_TARGET")]
+    pub target: String,
+    #[serde(rename = "LINE_IMAGE_DIR")]
+    pub image_dir: String,
+    #[serde(rename = "LINE_IMAGE_BASE_URL")]
+    pub image_base_url: String,
+}
+
+impl LineConfig {
+    /// 画像の公開 URL が HTTPS であることを検証します。LINE の画像メッセージは HTTPS の URL のみ指定できます。
+    pub fn validate(&self) -> Result<(), String> {
+        let base_url = self.image_base_url.trim();
+        if !base_url.is_empty() && !base_url.starts_with("https://") {
+            return Err("画像の公開 URL は https:// で始まる URL を指定してください。".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Slack 通知の設定。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlackConfig {
+    #[serde(rename = "SLACK_WEBHOOK_URL")]
+    pub webhook_url: String,
+}
+
+/// Microsoft Teams 通知の設定。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TeamsConfig {
+    #[serde(rename = "TEAMS_WEBHOOK_URL")]
+    pub webhook_url: String,
+}
+
+/// Telegram 通知の設定。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelegramConfig {
+    #[serde(rename = "TELEGRAM_BOT_TOKEN")]
+    pub bot_token: String,
+    #[serde(rename = "TELEGRAM_CHAT_ID")]
+    pub chat_id: String,
+    #[serde(rename = "TELEGRAM_SEND_PHOTO", with = "string_value")]
+    pub send_photo: bool,
+    #[serde(rename = "TELEGRAM_API_BASE_URL")]
+    pub api_base_url: String,
+}
+
+/// ntfy 通知の設定。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NtfyConfig {
+    #[serde(rename = "NTFY_TOPIC_URL")]
+    pub topic_url: String,
+    #[serde(rename = "NTFY_PRIORITY", with = "optional_string_value")]
+    pub priority: Option<u8>,
+    #[serde(rename = "NTFY_TAGS")]
+    pub tags: String,
+    #[serde(rename = "NTFY_ACCESS_TOKEN")]
+    pub access_token: String,
+}
+
+impl NtfyConfig {
+    /// 優先度が 1 から 5 の範囲であることを検証します。
+    pub fn validate(&self) -> Result<(), String> {
+        if self
+            .priority
+            .is_some_and(|priority| !(1..=5).contains(&priority))
+        {
+            return Err("ntfy の優先度は 1 から 5 の範囲で指定してください。".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Gotify 通知の設定。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GotifyConfig {
+    #[serde(rename = "GOTIFY_SERVER_URL")]
+    pub server_url: String,
+    #[serde(rename = "GOTIFY_APP_TOKEN")]
+    pub app_token: String,
+    #[serde(rename = "GOTIFY_PRIORITY", with = "optional_string_value")]
+    pub priority: Option<i64>,
+}
+
+impl GotifyConfig {
+    /// 優先度が 0 以上であることを検証します。
+    pub fn validate(&self) -> Result<(), String> {
+        if self.priority.is_some_and(|priority| priority < 0) {
+            return Err("Gotify の優先度は 0 以上で指定してください。".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Pushover 通知の設定。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PushoverConfig {
+    #[serde(rename = "PUSHOVER_USER_KEY")]
+    pub user_key: String,
+    #[serde(rename = "PUSHOVER_APP_TOKEN")]
+    pub app_token: String,
+    #[serde(rename = "PUSHOVER_DEVICE")]
+    pub device: String,
+    #[serde(rename = "PUSHOVER_SOUND")]
+    pub sound: String,
+    #[serde(rename = "PUSHOVER_PRIORITY", with = "optional_string_value")]
+    pub priority: Option<i8>,
+    #[serde(rename = "PUSHOVER_RETRY", with = "optional_string_value")]
+    pub retry: Option<u32>,
+    #[serde(rename = "PUSHOVER_EXPIRE", with = "optional_string_value")]
+    pub expire: Option<u32>,
+}
+
+impl PushoverConfig {
+    /// 優先度・再通知間隔・再通知時間の範囲を検証します。未設定の項目は既定値を使用するため検証しません。
+    pub fn validate(&self) -> Result<(), String> {
+        if self
+            .priority
+            .is_some_and(|priority| !(-2..=2).contains(&priority))
+        {
+            return Err("Pushover の優先度は -2 から 2 の範囲で指定してください。".to_string());
+        }
+        if self.retry.is_some_and(|retry| retry < 30) {
+            return Err("Pushover の再通知間隔は 30 秒以上で指定してください。".to_string());
+        }
+        if self
+            .expire
+            .is_some_and(|expire| !(1..=10800).contains(&expire))
+        {
+            return Err(
+                "Pushover の再通知時間は 1 から 10800 秒の範囲で指定してください。".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// メール（SMTP）通知の設定。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmtpConfig {
+    #[serde(rename = "SMTP_HOST")]
+    pub host: String,
+    #[serde(rename = "SMTP_PORT", with = "optional_string_value")]
+    pub port: Option<u16>,
+    #[serde(rename = "SMTP_SECURITY")]
+    pub security: String,
+    #[serde(rename = "SMTP_USERNAME")]
+    pub username: String,
+    #[serde(rename = "SMTP_PASSWORD")]
+    pub password: String,
+    #[serde(rename = "SMTP_FROM")]
+    pub from: String,
+    #[serde(rename = "SMTP_TO")]
+    pub to: String,
+}
+
+impl SmtpConfig {
+    /// 接続方式が `starttls`、`tls`、`none` のいずれかであることを検証します。
+    pub fn validate(&self) -> Result<(), String> {
+        if !matches!(self.security.trim(), "" | "starttls" | "tls" | "none") {
+            return Err(
+                "SMTP の接続方式は starttls、tls、none のいずれかを指定してください。".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: None,
+            security: "starttls".to_string(),
+            username: String::new(),
+            password: String::new(),
+            from: String::new(),
+            to: String::new(),
+        }
+    }
+}
+
+/// 汎用 HTTP Webhook 通知の設定。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpWebhookConfig {
+    #[serde(rename = "HTTP_WEBHOOK_URL")]
+    pub url: String,
+    #[serde(rename = "HTTP_WEBHOOK_METHOD")]
+    pub method: String,
+    #[serde(rename = "HTTP_WEBHOOK_HEADERS")]
+    pub headers: Map<String, Value>,
+    #[serde(rename = "HTTP_WEBHOOK_BODY_TEMPLATE")]
+    pub body_template: String,
+}
+
+impl HttpWebhookConfig {
+    /// HTTP メソッドと、ヘッダーの名前と値の形式を検証します。
+    pub fn validate(&self) -> Result<(), String> {
+        let method = self.method.trim();
+        if !method.is_empty() && reqwest::Method::from_bytes(method.as_bytes()).is_err() {
+            return Err(format!("HTTP メソッドが不正です: {}", method));
+        }
+        for (name, value) in &self.headers {
+            let valid = reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_ok()
+                && value
+                    .as_str()
+                    .is_some_and(|value| reqwest::header::HeaderValue::from_str(value).is_ok());
+            if !valid {
+                return Err(format!("ヘッダーが不正です: {}", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for HttpWebhookConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            method: "POST".to_string(),
+            headers: Map::new(),
+            body_template: String::new(),
+        }
+    }
+}
+
+/// 外部コマンドの実行の設定。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommandConfig {
+    #[serde(rename = "COMMAND_PROGRAM")]
+    pub program: String,
+    #[serde(rename = "COMMAND_ARGS")]
+    pub args: Vec<String>,
+    #[serde(rename = "COMMAND_TIMEOUT_MS", with = "optional_string_value")]
+    pub timeout_ms: Option<u64>,
+}
+
+impl CommandConfig {
+    /// タイムアウトが 1 ミリ秒以上であることを検証します。
+    pub fn validate(&self) -> Result<(), String> {
+        if self.timeout_ms == Some(0) {
+            return Err(
+                "コマンドのタイムアウトは 1 以上の整数（ミリ秒）で指定してください。".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl AppConfig {
+    /// 設定ファイルを読み込み、型付きの設定へ変換します。
+    ///
+    /// # 戻り値
+    /// * `Ok(AppConfig)` - 設定。不正な値は既定値に置き換えられます（`from_value` を参照）。
+    /// * `Err(String)` - ファイルの読み込み、または JSON 解析に失敗した場合。
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let config_data = fs::read_to_string(path).map_err(|e| {
+            error!("Failed to read config file: {:?}", e);
+            format!("read error: {:?}", e)
+        })?;
+        let value = serde_json::from_str(&config_data).map_err(|e| {
+            error!("Failed to parse config file: {:?}", e);
+            format!("parse error: {:?}", e)
+        })?;
+        Self::from_value(value)
+    }
+
+    /// JSON 値を型付きの設定へ変換します。
+    ///
+    /// # 概要
+    /// 各通知先の構築処理と同じく、不正な値があっても設定全体をエラーにはしません。
+    /// 変換できない値や範囲外の値は警告を出力して既定値を使用し、形式が不正な、または検証に失敗した
+    /// 監視対象ごとの設定・ルーティングルール・通知先のインスタンスは無視します。
+    /// 手で編集した設定が失われないよう、読み飛ばした値は保持し、保存時に設定ファイルの値をそのまま書き戻します（`save` を参照）。
+    ///
+    /// # 戻り値
+    /// * `Ok(AppConfig)` - 設定。
+    /// * `Err(String)` - JSON 値がオブジェクトでない場合。
+    pub fn from_value(value: Value) -> Result<Self, String> {
+        let mut entries = match value {
+            Value::Object(entries) => entries,
+            value => {
+                error!("Config file is not a JSON object: {}", value);
+                return Err(
+                    "parse error: 設定ファイルはJSONオブジェクトで記述してください。".to_string(),
+                );
+            }
+        };
+        let original = entries.clone();
+        let mut discarded = DiscardedValues::default();
+        retain_parsable_items::<TargetSettings>(&mut entries, TARGET_SETTINGS_KEY, &mut discarded);
+        retain_parsable_items::<RoutingRule>(&mut entries, ROUTING_RULES_KEY, &mut discarded);
+        retain_parsable_items::<NotifierInstance>(
+            &mut entries,
+            NOTIFIER_INSTANCES_KEY,
+            &mut discarded,
+        );
+        entries.retain(|key, value| {
+            let mut entry = Map::new();
+            entry.insert(key.clone(), value.clone());
+            match serde_json::from_value::<Self>(Value::Object(entry)) {
+                Ok(_) => true,
+                Err(e) => {
+                    warn!("{}の値が不正なため既定値を使用します: {}", key, e);
+                    discarded.keep_value(&original, key);
+                    false
+                }
+            }
+        });
+        let mut config: Self = serde_json::from_value(Value::Object(entries)).map_err(|e| {
+            error!("Failed to parse config file: {:?}", e);
+            format!("parse error: {}", e)
+        })?;
+        config.discarded = discarded;
+        config.discard_invalid_values(&original);
+        let loaded = config.to_value()?;
+        config.discarded.set_loaded_values(&loaded);
+        Ok(config)
+    }
+
+    /// 範囲外の値を既定値に戻し、検証に失敗した項目を取り除きます。
+    ///
+    /// 既定値に戻した値と取り除いた項目は、保存時に書き戻せるよう `discarded` に記録します。
+    fn discard_invalid_values(&mut self, original: &Map<String, Value>) {
+        if let Err(e) = validate_interval(self.interval) {
+            warn!("{} 既定値（{}ms）を使用します。", e, DEFAULT_INTERVAL);
+            self.interval = DEFAULT_INTERVAL;
+            self.discarded.keep_value(original, "INTERVAL");
+        }
+        if let Err(e) = validate_threshold(self.threshold) {
+            warn!("{} 既定値（{}）を使用します。", e, DEFAULT_THRESHOLD);
+            self.threshold = DEFAULT_THRESHOLD;
+            self.discarded.keep_value(original, "THRESHOLD");
+        }
+        if self.notify_retry_max_attempts == 0 {
+            warn!(
+                "NOTIFY_RETRY_MAX_ATTEMPTSは1以上を指定してください。既定値（{}）を使用します。",
+                DEFAULT_MAX_ATTEMPTS
+            );
+            self.notify_retry_max_attempts = DEFAULT_MAX_ATTEMPTS;
+            self.discarded
+                .keep_value(original, "NOTIFY_RETRY_MAX_ATTEMPTS");
+        }
+        let discarded = &mut self.discarded;
+        self.target_settings
+            .retain(|identity, settings| match settings.validate() {
+                Ok(_) => true,
+                Err(e) => {
+                    warn!("監視対象「{}」の設定を無視します: {}", identity, e);
+                    discarded.keep_item(TARGET_SETTINGS_KEY, Some(identity), settings);
+                    false
+                }
+            });
+        self.routing_rules.retain(|rule| match rule.validate() {
+            Ok(_) => true,
+            Err(e) => {
+                warn!("ルーティングルールを無視します: {}", e);
+                discarded.keep_item(ROUTING_RULES_KEY, None, rule);
+                false
+            }
+        });
+        let mut ids: Vec<String> = Vec::new();
+        self.notifier_instances.retain(|instance| {
+            if let Err(e) = instance.validate() {
+                warn!("通知先「{}」を無視します: {}", instance.id, e);
+                discarded.keep_item(NOTIFIER_INSTANCES_KEY, None, instance);
+                return false;
+            }
+            if ids.contains(&instance.id) {
+                warn!(
+                    "通知先の名前「{}」が重複しているため無視します。",
+                    instance.id
+                );
+                discarded.keep_item(NOTIFIER_INSTANCES_KEY, None, instance);
+                return false;
+            }
+            ids.push(instance.id.clone());
+            true
+        });
+    }
+
+    /// 設定を JSON 値へ変換します。値は従来の設定ファイルと同じく文字列として出力します。
+    pub fn to_value(&self) -> Result<Value, String> {
+        serde_json::to_value(self).map_err(|e| {
+            error!("Failed to serialize config file: {:?}", e);
+            format!("serialize error: {:?}", e)
+        })
+    }
+
+    /// 設定を検証し、設定ファイルへ整形して書き込みます。
+    ///
+    /// # 概要
+    /// 読み込み時に既定値へ置き換えた値はアプリで変更していない場合に限り、取り除いた項目は常に、
+    /// 設定ファイルの値のまま書き戻します。
+    ///
+    /// # 戻り値
+    /// * `Ok(())` - 書き込みに成功した場合。
+    /// * `Err(String)` - 検証、JSON の整形、またはファイル書き込みに失敗した場合。
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        self.validate()?;
+        let mut value = self.to_value()?;
+        if let Value::Object(entries) = &mut value {
+            self.discarded.restore(entries);
+        }
+        let content = serde_json::to_string_pretty(&value).map_err(|e| {
+            error!("Failed to serialize config file: {:?}", e);
+            format!("serialize error: {:?}", e)
+        })?;
+        fs::write(path, content).map_err(|e| {
+            error!("Failed to write config file: {:?}", e);
+            format!("write error: {:?}", e)
+        })
+    }
+
+    /// 設定値を検証します。保存時に呼び出され、不正な値があれば保存しません。
+    ///
+    /// 監視間隔・しきい値の範囲、再送回数、本文テンプレート、通知先ごとの設定、監視対象ごとの設定、
+    /// ルーティングルール、通知先のインスタンス（インスタンスの設定を含む）を検証します。
+    pub fn validate(&self) -> Result<(), String> {
+        validate_interval(self.interval)?;
+        validate_threshold(self.threshold)?;
+        if self.notify_retry_max_attempts == 0 {
+            return Err("NOTIFY_RETRY_MAX_ATTEMPTSは1以上を指定してください。".to_string());
+        }
+        for (notifier, template) in &self.message_templates {
+            validate_message_template(notifier, template)?;
+        }
+        for (kind, _) in NOTIFIER_KINDS {
+            self.validate_notifier(kind)?;
+        }
+        for (identity, settings) in &self.target_settings {
+            settings
+                .validate()
+                .map_err(|e| format!("{}: {}", identity, e))?;
+        }
+        for rule in &self.routing_rules {
+            rule.validate()?;
+        }
+        for (index, instance) in self.notifier_instances.iter().enumerate() {
+            instance.validate()?;
+            instance.validate_settings()?;
+            if self.notifier_instances[..index]
+                .iter()
+                .any(|existing| existing.id == instance.id)
+            {
+                return Err(format!("通知先の名前「{}」が重複しています。", instance.id));
+            }
+        }
+        Ok(())
+    }
+
+    /// 設定を `config` で置き換えます。
+    ///
+    /// 読み込み時に読み飛ばした値は、保存時に書き戻せるよう置き換え前の設定から引き継ぎます。
+    pub fn replace(&mut self, config: AppConfig) {
+        let discarded = std::mem::take(&mut self.discarded);
+        *self = AppConfig {
+            discarded,
+            ..config
+        };
+    }
+
+    /// 種類が `kind`（`NOTIFIER_KINDS` の種類の名前）の通知先の設定を検証します。
+    pub fn validate_notifier(&self, kind: &str) -> Result<(), String> {
+        match kind {
+            "discord" => self.discord.validate(),
+            "line" => self.line.validate(),
+            "ntfy" => self.ntfy.validate(),
+            "gotify" => self.gotify.validate(),
+            "pushover" => self.pushover.validate(),
+            "email" => self.smtp.validate(),
+            "webhook" => self.http_webhook.validate(),
+            "command" => self.command.validate(),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 読み込み時に読み飛ばした設定値。
+///
+/// 手で編集した設定が保存時に失われないよう、設定ファイルの値を保持します。
+#[derive(Clone, Debug, Default, PartialEq)]
+struct DiscardedValues {
+    /// 既定値に置き換えた設定キーごとの、設定ファイルの値と置き換えた後の値。
+    values: HashMap<String, (Value, Value)>,
+    /// 取り除いた項目。設定キー、オブジェクトの項目名（配列の場合は `None`）、項目の値。
+    items: Vec<(String, Option<String>, Value)>,
+}
+
+impl DiscardedValues {
+    /// 既定値に置き換えた `key` の、設定ファイルの値を記録します。
+    fn keep_value(&mut self, original: &Map<String, Value>, key: &str) {
+        if let Some(value) = original.get(key) {
+            self.values
+                .insert(key.to_string(), (value.clone(), Value::Null));
+        }
+    }
+
+    /// `key` の配列またはオブジェクトから取り除いた項目を記録します。
+    fn keep_item<T: Serialize>(&mut self, key: &str, name: Option<&str>, item: &T) {
+        if let Ok(item) = serde_json::to_value(item) {
+            self.items
+                .push((key.to_string(), name.map(str::to_string), item));
+        }
+    }
+
+    /// 既定値に置き換えた後の値を、読み込んだ設定 `loaded` から記録します。
+    fn set_loaded_values(&mut self, loaded: &Value) {
+        for (key, (_, value)) in self.values.iter_mut() {
+            *value = loaded.get(key).cloned().unwrap_or(Value::Null);
+        }
+    }
+
+    /// 保存する設定 `entries` へ、読み飛ばした値を書き戻します。
+    ///
+    /// 既定値に置き換えた値は、保存する値が読み込んだときから変わっていない場合のみ書き戻します。
+    /// 取り除いた項目は、オブジェクトでは同じ名前の項目がない場合に、配列では末尾に追加します。
+    fn restore(&self, entries: &mut Map<String, Value>) {
+        for (key, (original, loaded)) in &self.values {
+            if entries.get(key) == Some(loaded) {
+                entries.insert(key.clone(), original.clone());
+            }
+        }
+        for (key, name, item) in &self.items {
+            match (entries.get_mut(key), name) {
+                (Some(Value::Object(items)), Some(name)) => {
+                    items.entry(name.clone()).or_insert_with(|| item.clone());
+                }
+                (Some(Value::Array(items)), None) if !items.contains(item) => {
+                    items.push(item.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// `key` の配列またはオブジェクトから、`T` として読み込めない項目を警告して取り除き、`discarded` に記録します。
+fn retain_parsable_items<T: DeserializeOwned>(
+    entries: &mut Map<String, Value>,
+    key: &str,
+    discarded: &mut DiscardedValues,
+) {
+    let is_parsable = |item: &Value| match serde_json::from_value::<T>(item.clone()) {
+        Ok(_) => true,
+        Err(e) => {
+            warn!("{}の項目の形式が不正なため無視します: {}", key, e);
+            false
+        }
+    };
+    match entries.get_mut(key) {
+        Some(Value::Array(items)) => items.retain(|item| {
+            let parsable = is_parsable(item);
+            if !parsable {
+                discarded.keep_item(key, None, item);
+            }
+            parsable
+        }),
+        Some(Value::Object(items)) => items.retain(|name, item| {
+            let parsable = is_parsable(item);
+            if !parsable {
+                discarded.keep_item(key, Some(name), item);
+            }
+            parsable
+        }),
+        _ => {}
+    }
+}
+
+/// 監視間隔が 100ms 以上であることを検証します。
+pub fn validate_interval(interval: u64) -> Result<(), String> {
+    if interval >= MIN_INTERVAL {
+        Ok(())
+    } else {
+        let message = "監視間隔は100ms以上を指定してください。".to_string();
+        error!("{}", message);
+        Err(message)
+    }
+}
+
+/// 画像しきい値が 0.0 から 1.0 の有限数であることを検証します。
+pub fn validate_threshold(threshold: f32) -> Result<(), String> {
+    if threshold.is_finite() && (0.0..=1.0).contains(&threshold) {
+        Ok(())
+    } else {
+        let message = "画像しきい値は0.0から1.0の有限数を指定してください。".to_string();
+        error!("{}", message);
+        Err(message)
+    }
+}
+
+/// 設定値の文字列を `T` として解析します。
+fn parse_config_text<T, E>(text: &str) -> Result<T, E>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+    E: serde::de::Error,
+{
+    text.trim().parse().map_err(|e| {
+        E::custom(format!(
+            "{:?} を {} として解析できません: {}",
+            text,
+            std::any::type_name::<T>(),
+            e
+        ))
+    })
+}
+
+/// 文字列、または JSON の数値・真偽値として保存された値を読み込み、文字列として書き込みます。
+mod string_value {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use serde_json::Value;
+    use std::{fmt::Display, str::FromStr};
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        match Value::deserialize(deserializer)? {
+            Value::String(text) => super::parse_config_text(&text),
+            value @ (Value::Number(_) | Value::Bool(_)) => {
+                super::parse_config_text(&value.to_string())
+            }
+            value => Err(D::Error::custom(format!(
+                "文字列、数値、または真偽値を指定してください: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// 未設定を空文字列で表す値を読み込み、書き込みます。空文字列、`null`、キーの欠落は `None` として扱います。
+mod optional_string_value {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use serde_json::Value;
+    use std::{fmt::Display, str::FromStr};
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.collect_str(value),
+            None => serializer.serialize_str(""),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        match Value::deserialize(deserializer)? {
+            Value::Null => Ok(None),
+            Value::String(text) if text.trim().is_empty() => Ok(None),
+            Value::String(text) => super::parse_config_text(&text).map(Some),
+            value @ Value::Number(_) => super::parse_config_text(&value.to_string()).map(Some),
+            value => Err(D::Error::custom(format!(
+                "文字列または数値を指定してください: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// 通知の言語を読み込み、書き込みます。空の場合は日本語として扱います。
+mod language {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use crate::message_template::Language;
+
+    pub fn serialize<S: Serializer>(value: &Language, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(value.as_str())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Language, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        if text.trim().is_empty() {
+            return Ok(Language::default());
+        }
+        Language::parse(&text).ok_or_else(|| {
+            D::Error::custom(format!(
+                "NOTIFY_LANGUAGEはjaまたはenを指定してください: {}",
+                text
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AppConfig;
+    use crate::config_manager::TargetSettings;
+    use crate::message_template::Language;
+    use serde_json::{json, Value};
+    use std::fs;
+
+    #[test]
+    fn legacy_string_values_are_parsed_into_typed_fields() {
+        let config = AppConfig::from_value(json!({
+            "THRESHOLD": "0.080",
+            "INTERVAL": "2500",
+            "MINIMIZE_ON_MONITOR_START": "false",
+            "RESUME_MONITORING_ON_STARTUP": "true",
+            "NOTIFY_RETRY_MAX_ATTEMPTS": "5",
+            "NOTIFY_LANGUAGE": "en",
+            "LINE_ENABLED": "true",
+            "NTFY_PRIORITY": "4",
+            "PUSHOVER_PRIORITY": "",
+            "SMTP_PORT": "587",
+            "COMMAND_ARGS": ["--flag"],
+            "MESSAGE_TEMPLATES": { "slack": "{app_name}" }
+        }))
+        .unwrap();
+
+        assert_eq!(config.threshold, 0.08);
+        assert_eq!(config.interval, 2500);
+        assert!(!config.minimize_on_monitor_start);
+        assert!(config.resume_monitoring_on_startup);
+        assert_eq!(config.notify_retry_max_attempts, 5);
+        assert_eq!(config.notify_language, Language::En);
+        assert!(config.line.enabled);
+        assert_eq!(config.ntfy.priority, Some(4));
+        assert_eq!(config.pushover.priority, None);
+        assert_eq!(config.smtp.port, Some(587));
+        assert_eq!(config.command.args, vec!["--flag".to_string()]);
+        assert_eq!(config.message_templates["slack"], "{app_name}");
+    }
+
+    #[test]
+    fn missing_keys_use_defaults_and_native_json_values_are_accepted() {
+        let config =
+            AppConfig::from_value(json!({ "INTERVAL": 500, "LINE_ENABLED": true })).unwrap();
+
+        assert_eq!(config.interval, 500);
+        assert_eq!(config.threshold, 0.05);
+        assert!(config.minimize_on_monitor_start);
+        assert!(!config.resume_monitoring_on_startup);
+        assert!(config.line.enabled);
+        assert_eq!(config.smtp.security, "starttls");
+        assert_eq!(config.http_webhook.method, "POST");
+    }
+
+    #[test]
+    fn invalid_values_fall_back_to_defaults_at_load_time() {
+        let config = AppConfig::from_value(json!({
+            "THRESHOLD": "1.5",
+            "INTERVAL": "50",
+            "MINIMIZE_ON_MONITOR_START": "yes",
+            "RESUME_MONITORING_ON_STARTUP": "true",
+            "NTFY_PRIORITY": "high",
+            "NOTIFY_LANGUAGE": "fr",
+            "NOTIFY_RETRY_MAX_ATTEMPTS": "abc",
+            "ROUTING_RULES": [
+                { "name": "chat", "title_pattern": "Chat*", "notifiers": ["slack"] },
+                { "name": "empty", "notifiers": ["slack"] },
+                "not a rule"
+            ],
+            "NOTIFIER_INSTANCES": [
+                { "id": "team", "kind": "discord" },
+                { "id": "team", "kind": "line" },
+                { "id": "unknown", "kind": "fax" }
+            ],
+            "TARGET_SETTINGS": {
+                "chat": { "interval": 2000 },
+                "bad": { "threshold": 2.0 },
+                "broken": { "interval": "soon" }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(config.threshold, 0.05);
+        assert_eq!(config.interval, 1000);
+        assert!(config.minimize_on_monitor_start);
+        assert!(config.resume_monitoring_on_startup);
+        assert_eq!(config.ntfy.priority, None);
+        assert_eq!(config.notify_language, Language::Ja);
+        assert_eq!(config.notify_retry_max_attempts, 3);
+        assert_eq!(config.routing_rules.len(), 1);
+        assert_eq!(config.notifier_instances.len(), 1);
+        assert_eq!(config.notifier_instances[0].id, "team");
+        assert_eq!(
+            config.target_settings.keys().collect::<Vec<_>>(),
+            vec!["chat"]
+        );
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn invalid_values_are_rejected_when_saving() {
+        let config = AppConfig {
+            interval: 50,
+            ..AppConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = AppConfig {
+            threshold: 1.5,
+            ..AppConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = AppConfig {
+            notify_retry_max_attempts: 0,
+            ..AppConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        for invalid in [
+            json!({ "PUSHOVER_PRIORITY": "9" }),
+            json!({ "PUSHOVER_RETRY": "10" }),
+            json!({ "NTFY_PRIORITY": "0" }),
+            json!({ "GOTIFY_PRIORITY": "-1" }),
+            json!({ "SMTP_SECURITY": "foo" }),
+            json!({ "COMMAND_TIMEOUT_MS": "0" }),
+            json!({ "LINE_IMAGE_BASE_URL": "http://example.com/icons" }),
+            json!({ "DISCORD_MENTION_ROLE_IDS": "123, admins" }),
+            json!({ "HTTP_WEBHOOK_METHOD": "NOT A METHOD" }),
+            json!({ "HTTP_WEBHOOK_HEADERS": { "X-Count": 1 } }),
+            json!({ "MESSAGE_TEMPLATES": { "webhook": "{app_name}" } }),
+            json!({ "MESSAGE_TEMPLATES": { "slack": "{title}" } }),
+            json!({
+                "NOTIFIER_INSTANCES": [
+                    { "id": "mine", "kind": "email", "settings": { "SMTP_SECURITY": "foo" } }
+                ]
+            }),
+        ] {
+            let config = AppConfig::from_value(invalid.clone()).unwrap();
+            assert!(config.validate().is_err(), "{}", invalid);
+        }
+        assert!(AppConfig::from_value(json!({
+            "PUSHOVER_PRIORITY": "2",
+            "SMTP_SECURITY": "tls",
+            "LINE_IMAGE_BASE_URL": "https://example.com/icons",
+            "MESSAGE_TEMPLATES": { "slack": "{app_name}" }
+        }))
+        .unwrap()
+        .validate()
+        .is_ok());
+    }
+
+    #[test]
+    fn discarded_values_are_written_back_when_saving() {
+        let path = std::env::temp_dir().join(format!(
+            "flash-code-config-test-{}.json",
+            std::process::id()
+        ));
+        let mut config = AppConfig::from_value(json!({
+            "INTERVAL": "50",
+            "THRESHOLD": "1.5",
+            "NTFY_PRIORITY": "high",
+            "ROUTING_RULES": [
+                { "name": "chat", "title_pattern": "Chat*", "notifiers": ["slack"] },
+                "not a rule"
+            ],
+            "TARGET_SETTINGS": { "broken": { "interval": "soon" } }
+        }))
+        .unwrap();
+        config.threshold = 0.1;
+        config.target_settings.insert(
+            "chat".to_string(),
+            TargetSettings {
+                interval: Some(2000),
+                ..TargetSettings::default()
+            },
+        );
+
+        config.save(&path).unwrap();
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(saved["INTERVAL"], "50");
+        assert_eq!(saved["NTFY_PRIORITY"], "high");
+        assert_eq!(saved["THRESHOLD"], config.to_value().unwrap()["THRESHOLD"]);
+        assert_eq!(saved["ROUTING_RULES"].as_array().unwrap().len(), 2);
+        assert_eq!(saved["ROUTING_RULES"][1], "not a rule");
+        assert_eq!(
+            saved["TARGET_SETTINGS"]["broken"],
+            json!({ "interval": "soon" })
+        );
+        assert_eq!(
+            saved["TARGET_SETTINGS"]["chat"],
+            json!({ "interval": 2000 })
+        );
+    }
+
+    #[test]
+    fn round_trip_writes_strings_and_keeps_unknown_keys() {
+        let mut config = AppConfig::from_value(json!({
+            "THRESHOLD": "0.050",
+            "FUTURE_SETTING": { "nested": 1 }
+        }))
+        .unwrap();
+        assert_eq!(
+            config.other.keys().collect::<Vec<_>>(),
+            vec!["FUTURE_SETTING"]
+        );
+        config.interval = 1500;
+        config.gotify.priority = Some(8);
+
+        let value = config.to_value().unwrap();
+
+        assert_eq!(value["INTERVAL"], "1500");
+        assert_eq!(value["MINIMIZE_ON_MONITOR_START"], "true");
+        assert_eq!(value["GOTIFY_PRIORITY"], "8");
+        assert_eq!(value["NTFY_PRIORITY"], "");
+        assert_eq!(value["NOTIFY_LANGUAGE"], "ja");
+        assert_eq!(value["FUTURE_SETTING"], json!({ "nested": 1 }));
+        assert_eq!(AppConfig::from_value(value).unwrap(), config);
+    }
+}
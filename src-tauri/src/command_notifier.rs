@@ -0,0 +1,313 @@
+use async_trait::async_trait;
+use log::{info, warn};
+use std::{process::Stdio, time::Duration};
+use tokio::{io::AsyncWriteExt, process::Command, time::timeout};
+
+use crate::app_config::AppConfig;
+use crate::notifier::{
+    non_empty, render_template, template_values, DeliveryOutcome, NotificationEvent, Notifier,
+};
+
+const DEFAULT_COMMAND_TIMEOUT_MS: u64 = 10_000;
+
+/// コンソールウィンドウを表示せずにプロセスを起動するフラグ。
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/// 変化を検知した際に、設定したプログラムを実行するアクション。
+///
+/// # 概要
+/// 効果音の再生、CLI からのスマートライトの操作、ウィンドウの前面表示など、ローカルの自動化に使用します。
+///
+/// - 検知イベントを JSON として標準入力へ渡します。
+/// - `FLASH_CODE_APP_NAME`、`FLASH_CODE_PID`、`FLASH_CODE_HWND`、`FLASH_CODE_TIMESTAMP`、
+///   `FLASH_CODE_DIFF_SCORE`、`FLASH_CODE_TRIGGER` を環境変数として渡します。
+/// - 引数ではテンプレートと同じプレースホルダー（`{app_name}` など）を使用できます。
+/// - タイムアウトした場合はプロセスを終了し、失敗として扱います。
+/// - 標準出力と標準エラー出力はログへ出力します。
+///
+/// # 設定
+/// - `COMMAND_PROGRAM`: 実行するプログラム。空の場合、アクションとして登録されません。
+/// - `COMMAND_ARGS`: 引数の JSON 配列。
+/// - `COMMAND_TIMEOUT_MS`: タイムアウト（ミリ秒）。既定値は `10000`。
+pub struct CommandNotifier {
+    program: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+/// 実行したプログラムの終了コードと出力。
+#[derive(Debug)]
+struct CommandOutput {
+    success: bool,
+    code: Option<i32>,
+    stdout: String,
+    stderr: String,
+}
+
+impl CommandNotifier {
+    pub fn from_config(config: &AppConfig) -> Option<Self> {
+        let command = &config.command;
+        let program = non_empty(&command.program)?;
+        let timeout_ms = match command.timeout_ms {
+            None => DEFAULT_COMMAND_TIMEOUT_MS,
+            Some(timeout_ms) if timeout_ms > 0 => timeout_ms,
+            Some(_) => {
+                warn!(
+                    "COMMAND_TIMEOUT_MSは1以上の整数で指定してください。既定値({})を使用します。",
+                    DEFAULT_COMMAND_TIMEOUT_MS
+                );
+                DEFAULT_COMMAND_TIMEOUT_MS
+            }
+        };
+        Some(Self {
+            program,
+            args: command.args.clone(),
+            timeout: Duration::from_millis(timeout_ms),
+        })
+    }
+
+    async fn run(&self, event: &NotificationEvent) -> Result<CommandOutput, (String, bool)> {
+        let input = serde_json::to_vec(event).map_err(|e| (e.to_string(), true))?;
+
+        let mut command = Command::new(&self.program);
+        command
+            .args(
+                self.args
+                    .iter()
+                    .map(|arg| render_template(arg, event, str::to_string)),
+            )
+            .envs(
+                template_values(event)
+                    .into_iter()
+                    .filter_map(|(name, value)| {
+                        (name != "icon_base64")
+                            .then(|| (format!("FLASH_CODE_{}", name.to_ascii_uppercase()), value))
+                    }),
+            )
+            .env("FLASH_CODE_HWND", event.app.hwnd.to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        #[cfg(windows)]
+        command.creation_flags(CREATE_NO_WINDOW);
+
+        // プログラムが見つからない場合などは、再実行しても成功しないため恒久的な失敗とします。
+        let mut child = command.spawn().map_err(|e| {
+            (
+                format!("{} の起動に失敗しました: {}", self.program, e),
+                true,
+            )
+        })?;
+        let stdin = child.stdin.take();
+        let write_input = async move {
+            if let Some(mut stdin) = stdin {
+                // 標準入力を読まずに終了するプログラムもあるため、書き込みの失敗は無視します。
+                let _ = stdin.write_all(&input).await;
+            }
+        };
+        // 標準入力を読まないプログラムで書き込みが止まってもタイムアウトするよう、
+        // 書き込みと終了の待機を並行してタイムアウトの対象にします。
+        let run = async { tokio::join!(write_input, child.wait_with_output()).1 };
+
+        match timeout(self.timeout, run).await {
+            Ok(Ok(output)) => Ok(CommandOutput {
+                success: output.status.success(),
+                code: output.status.code(),
+                stdout: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            }),
+            Ok(Err(e)) => Err((e.to_string(), false)),
+            // タイムアウトした場合、`kill_on_drop` によりプロセスは終了されます。
+            Err(_) => Err((
+                format!(
+                    "{} が {} ミリ秒以内に終了しませんでした。",
+                    self.program,
+                    self.timeout.as_millis()
+                ),
+                false,
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for CommandNotifier {
+    fn name(&self) -> &str {
+        "command"
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> DeliveryOutcome {
+        match self.run(event).await {
+            Ok(output) => {
+                if !output.stdout.is_empty() {
+                    info!("{} の標準出力: {}", self.program, output.stdout);
+                }
+                if !output.stderr.is_empty() {
+                    warn!("{} の標準エラー出力: {}", self.program, output.stderr);
+                }
+                if output.success {
+                    DeliveryOutcome::Sent
+                } else {
+                    DeliveryOutcome::Failed {
+                        status: None,
+                        message: format!(
+                            "{} が終了コード {:?} で終了しました。{}",
+                            self.program, output.code, output.stderr
+                        ),
+                        permanent: false,
+                    }
+                }
+            }
+            Err((message, permanent)) => DeliveryOutcome::Failed {
+                status: None,
+                message,
+                permanent,
+            },
+        }
+    }
+
+    /// ローカルの自動化は検知した時点で実行する必要があるため、送信待ちキューへは保存しません。
+    fn queues_when_undelivered(&self) -> bool {
+        false
+    }
+
+    /// タイムアウトや終了コードによる失敗でもコマンドの処理が実行されている場合があるため、再実行しません。
+    fn retries_when_failed(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommandNotifier;
+    use crate::app_config::AppConfig;
+    use crate::notifier::{DeliveryOutcome, NotificationEvent, Notifier};
+    use crate::test_support::{app_config, sample_app};
+    use crate::window_utils::AppInfo;
+    use serde_json::json;
+
+    /// OS ごとのシェルでスクリプトを実行する設定を作成します。
+    fn shell_config(unix_script: &str, windows_script: &str, timeout_ms: u64) -> AppConfig {
+        if cfg!(windows) {
+            app_config(json!({
+                "COMMAND_PROGRAM": "cmd",
+                "COMMAND_ARGS": ["/C", windows_script],
+                "COMMAND_TIMEOUT_MS": timeout_ms.to_string()
+            }))
+        } else {
+            app_config(json!({
+                "COMMAND_PROGRAM": "sh",
+                "COMMAND_ARGS": ["-c", unix_script],
+                "COMMAND_TIMEOUT_MS": timeout_ms.to_string()
+            }))
+        }
+    }
+
+    fn event() -> NotificationEvent {
+        let app = AppInfo {
+            hwnd: 7,
+            process_id: 42,
+            ..sample_app()
+        };
+        NotificationEvent::icon_changed(app, 0.2)
+    }
+
+    #[tokio::test]
+    async fn run_passes_event_on_stdin_and_env() {
+        let notifier = CommandNotifier::from_config(&shell_config(
+            r#"cat; echo; echo "$FLASH_CODE_APP_NAME/$FLASH_CODE_PID/$FLASH_CODE_HWND/$FLASH_CODE_TRIGGER""#,
+            "more & echo %FLASH_CODE_APP_NAME%/%FLASH_CODE_PID%/%FLASH_CODE_HWND%/%FLASH_CODE_TRIGGER%",
+            5_000,
+        ))
+        .unwrap();
+
+        let output = notifier.run(&event()).await.unwrap();
+
+        assert!(output.success);
+        assert!(output.stdout.contains(r#""name":"Chat""#));
+        assert!(output.stdout.ends_with("Chat/42/7/icon_changed"));
+    }
+
+    #[tokio::test]
+    async fn args_support_placeholders() {
+        let notifier = CommandNotifier::from_config(&app_config(json!({
+            "COMMAND_PROGRAM": if cfg!(windows) { "cmd" } else { "echo" },
+            "COMMAND_ARGS": if cfg!(windows) {
+                json!(["/C", "echo", "{app_name}:{pid}"])
+            } else {
+                json!(["{app_name}:{pid}"])
+            }
+        })))
+        .unwrap();
+
+        let output = notifier.run(&event()).await.unwrap();
+
+        assert_eq!(output.stdout, "Chat:42");
+    }
+
+    #[tokio::test]
+    async fn non_zero_exit_is_reported_as_failure() {
+        let notifier =
+            CommandNotifier::from_config(&shell_config("exit 3", "exit /b 3", 5_000)).unwrap();
+
+        match notifier.send(&event()).await {
+            DeliveryOutcome::Failed {
+                message, permanent, ..
+            } => {
+                assert!(message.contains('3'));
+                assert!(!permanent);
+            }
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_program_is_permanent_failure() {
+        let notifier = CommandNotifier::from_config(&app_config(json!({
+            "COMMAND_PROGRAM": "flash-code-command-that-does-not-exist"
+        })))
+        .unwrap();
+
+        assert!(matches!(
+            notifier.send(&event()).await,
+            DeliveryOutcome::Failed {
+                permanent: true,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn slow_command_times_out() {
+        let notifier =
+            CommandNotifier::from_config(&shell_config("sleep 5", "ping -n 6 127.0.0.1 >nul", 100))
+                .unwrap();
+
+        match notifier.send(&event()).await {
+            DeliveryOutcome::Failed { message, .. } => assert!(message.contains("100")),
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn command_that_does_not_read_stdin_times_out() {
+        let notifier =
+            CommandNotifier::from_config(&shell_config("sleep 5", "ping -n 6 127.0.0.1 >nul", 100))
+                .unwrap();
+        // パイプのバッファを超える大きさの標準入力を渡します。
+        let app = AppInfo {
+            icon: Some("A".repeat(1 << 20)),
+            ..sample_app()
+        };
+
+        match notifier
+            .send(&NotificationEvent::icon_changed(app, 0.2))
+            .await
+        {
+            DeliveryOutcome::Failed { message, .. } => assert!(message.contains("100")),
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+    }
+}
@@ -1,7 +1,19 @@
 use log::{debug, error, info, warn};
+use serde_json::Value;
 use tauri::State;
 
-use crate::window_utils::{AppInfo, ConfigState, MonitorState};
+use crate::config_manager::{MessageTemplateSettings, NotifierInstanceSummary, TargetSettings};
+use crate::delivery_history::{DeliveryHistory, HistoryEntry, HistoryFilter};
+use crate::message_template::{
+    default_template, preview_event, validate_message_template, Language, MessageTemplate,
+};
+use crate::monitor::MonitorSettings;
+use crate::monitor_persistence::{ResumeReport, ResumeReportState};
+use crate::notification_queue::{NotificationQueue, QueuedNotification};
+use crate::notification_routing::RoutingRule;
+use crate::notifier::{NotifierRegistry, TestNotificationResult};
+use crate::notifier_instance::NotifierInstance;
+use crate::window_utils::{AppInfo, ConfigState, MonitorState, TargetStatus};
 
 /// 監視を開始するコマンド。
 ///
@@ -12,7 +24,6 @@ use crate::window_utils::{AppInfo, ConfigState, MonitorState};
 /// # 引数
 /// * `monitor_state` - `MonitorState`の状態。監視の管理に使用されます。
 /// * `config_state` - `ConfigState`の状態。アプリケーションの設定を提供します。
-/// * `app_handle` - Tauriの`AppHandle`。
 /// * `apps` - 監視対象のアプリケーション情報のリスト。
 ///
 /// # 使用例
@@ -24,7 +35,6 @@ use crate::window_utils::{AppInfo, ConfigState, MonitorState};
 /// async fn start_monitoring_example(
 ///     monitor_state: State<'_, MonitorState>,
 ///     config_state: State<'_, ConfigState>,
-///     app_handle: tauri::AppHandle,
 ///     apps: Vec<AppInfo>,
 /// ) {
 ///     start_monitoring(monitor_state, config_state, apps).await.unwrap();
@@ -35,69 +45,18 @@ use crate::window_utils::{AppInfo, ConfigState, MonitorState};
 pub async fn start_monitoring(
     monitor_state: State<'_, MonitorState>,
     config_state: State<'_, ConfigState>,
-    app_handle: tauri::AppHandle,
     apps: Vec<AppInfo>,
 ) -> Result<(), String> {
     info!("start_monitoringを呼び出しました。");
-    let minimize_on_start = match get_minimize_on_monitor_start(config_state.clone()).await {
-        Ok(val) => val,
-        Err(e) => {
-            error!("最小化設定の取得に失敗しました: {}", e);
-            return Err(e);
-        }
-    };
-    monitor_state.stop_all(apps.clone()).await;
-    monitor_state
-        .set_restore_windows_on_stop(minimize_on_start)
-        .await;
+    let mut targets = Vec::with_capacity(apps.len());
     for app in apps {
-        let interval_str = match get_interval(config_state.clone()).await {
-            Ok(val) => val,
-            Err(e) => {
-                error!("監視間隔の取得に失敗しました: {}", e);
-                return Err(e);
-            }
-        };
-        let interval = match interval_str.parse::<u64>() {
-            Ok(val) if val >= 100 => val,
-            Ok(_) => {
-                let message = "監視間隔は100ms以上を指定してください。".to_string();
-                error!("{}", message);
-                return Err(message);
-            }
-            Err(e) => {
-                error!("監視間隔の文字列の解析に失敗しました: {}", e);
-                return Err(format!("監視間隔のパースに失敗しました: {}", e));
-            }
-        };
-        let threshold_str = match get_threshold(config_state.clone()).await {
-            Ok(val) => val,
-            Err(e) => {
-                error!("しきい値の取得に失敗しました: {}", e);
-                return Err(e);
-            }
-        };
-        let threshold = match threshold_str.parse::<f32>() {
-            Ok(val) if val.is_finite() && (0.0..=1.0).contains(&val) => val,
-            Ok(_) => {
-                let message = "画像しきい値は0.0から1.0の有限数を指定してください。".to_string();
-                error!("{}", message);
-                return Err(message);
-            }
-            Err(e) => {
-                error!("しきい値の文字列の解析に失敗しました: {}", e);
-                return Err(format!("しきい値のパースに失敗しました: {}", e));
-            }
-        };
+        let settings = load_monitor_settings(config_state.clone(), &app).await?;
+        targets.push((app, settings));
+    }
+    monitor_state.stop_all().await;
+    for (app, settings) in targets {
         monitor_state
-            .monitor_target(
-                app,
-                interval,
-                threshold,
-                minimize_on_start,
-                config_state.clone(),
-                app_handle.clone(),
-            )
+            .monitor_target(app, settings, config_state.clone())
             .await;
     }
     Ok(())
@@ -107,6 +66,7 @@ pub async fn start_monitoring(
 ///
 /// # 概要
 /// 現在実行中のすべての監視プロセスを停止します。  
+/// 保存済みの監視対象の一覧も空にするため、次回起動時に監視は再開されません。  
 /// 停止が正常に完了した場合、成功の結果を返します。
 ///
 /// # 引数
@@ -136,421 +96,661 @@ pub async fn start_monitoring(
 /// ```
 ///
 #[tauri::command]
-pub async fn stop_monitoring(
-    monitor_state: State<'_, MonitorState>,
-    apps: Vec<AppInfo>,
-) -> Result<(), String> {
-    monitor_state.stop_all(apps).await;
+pub async fn stop_monitoring(monitor_state: State<'_, MonitorState>) -> Result<(), String> {
+    monitor_state.stop_all().await;
     Ok(())
 }
 
-/// タスクバーに表示されているアプリ情報を取得するコマンド。
+/// 監視対象を 1 件追加するコマンド。
 ///
 /// # 概要
-/// タスクバーに現在表示されているすべてのアプリケーションの情報を取得します。
+/// 指定したアプリケーションの監視を開始します。他の監視対象の監視タスクは停止しません。  
+/// 同じ監視対象がすでに監視中の場合は、現在の設定で監視を開始し直します。
 ///
-/// # 戻り値
-/// タスクバーに表示されている`AppInfo`オブジェクトのベクターを返します。
-///
-/// # 使用例
-/// ```rust
-/// use flash_code::AppInfo;
+/// # 引数
+/// * `monitor_state` - 監視タスクの管理を行う `MonitorState`。
+/// * `config_state` - 監視間隔やしきい値を取得する設定ファイルの `ConfigState`。
+/// * `app` - 追加する監視対象のアプリケーション情報。
 ///
-/// #[tauri::command]
-/// pub fn get_taskbar_apps_example() -> Vec<AppInfo> {
-///     get_taskbar_apps()
-/// }
-/// ```
+/// # 戻り値
+/// `Result`:
+/// - `Ok(())`: 監視を開始した場合。
+/// - `Err(String)`: 設定値の取得または検証に失敗した場合。
 ///
 #[tauri::command]
-pub fn get_taskbar_apps() -> Vec<AppInfo> {
-    crate::window_collection::get_taskbar_apps()
+pub async fn add_monitoring_target(
+    monitor_state: State<'_, MonitorState>,
+    config_state: State<'_, ConfigState>,
+    app: AppInfo,
+) -> Result<(), String> {
+    info!("add_monitoring_targetを呼び出しました。");
+    let settings = load_monitor_settings(config_state.clone(), &app).await?;
+    monitor_state
+        .monitor_target(app, settings, config_state)
+        .await;
+    Ok(())
 }
 
-/// Webhook URLを更新する関数。
+/// 監視対象を 1 件削除するコマンド。
 ///
 /// # 概要
-/// 設定ファイル（appsettings.json）の `DISCORD_WEBHOOK_URL` 項目を更新します。  
-/// 新しいWebhook URLを指定し、設定ファイルに保存します。
+/// 指定したアプリケーションの監視タスクを停止し、監視対象から削除します。他の監視対象には影響しません。  
+/// 監視開始時に最小化したウィンドウは復元します。
 ///
 /// # 引数
-/// * `config_state` - 設定ファイルのパスを管理する `ConfigState`。
-/// * `url` - 新しいWebhook URL。
+/// * `monitor_state` - 監視タスクの管理を行う `MonitorState`。
+/// * `app` - 削除する監視対象のアプリケーション情報。
 ///
 /// # 戻り値
 /// `Result`:
-/// - `Ok(())`: Webhook URLが正常に更新された場合。
-/// - `Err(String)`: 設定ファイルの読み書きや解析に失敗した場合、そのエラー内容を含むメッセージ。
-///
-/// # 注意点
-/// - URLの形式が正しいかどうかはこの関数では検証されません。
-///
-/// # 使用例
-/// ```rust
-/// use tauri::State;
-/// use flash_code::ConfigState;
-///
-/// #[tauri::command]
-/// async fn update_webhook_url_example(config_state: State<'_, ConfigState>, url: String) {
-///     match update_webhook_url(config_state, url).await {
-///         Ok(_) => println!("Webhook URLが正常に更新されました。"),
-///         Err(e) => eprintln!("Webhook URLの更新に失敗しました: {}", e),
-///     }
-/// }
-/// ```
+/// - `Ok(())`: 削除した場合、または監視対象が存在しなかった場合。
 ///
-/// # 概要
-/// 設定ファイル（appsettings.json）の`DISCORD_WEBHOOK_URL`項目を更新します。  
-/// 新しいWebhook URLを指定し、設定ファイルに保存します。
+#[tauri::command]
+pub async fn remove_monitoring_target(
+    monitor_state: State<'_, MonitorState>,
+    app: AppInfo,
+) -> Result<(), String> {
+    info!("remove_monitoring_targetを呼び出しました。");
+    if !monitor_state.remove_target(&app.target_key()).await {
+        warn!("監視対象：{:?}は監視されていません。", app.name);
+    }
+    Ok(())
+}
+
+/// 監視対象の監視を一時停止するコマンド。
 ///
 /// # 引数
-/// * `config_state` - 設定ファイルのパスを管理する `ConfigState`。
-/// * `url` - 新しいWebhook URL。
+/// * `monitor_state` - 監視タスクの管理を行う `MonitorState`。
+/// * `app` - 一時停止する監視対象のアプリケーション情報。
 ///
 /// # 戻り値
 /// `Result`:
-/// - `Ok(())`: Webhook URLが正常に更新された場合。
-/// - `Err(String)`: 設定ファイルの読み書きや解析に失敗した場合、そのエラー内容を含むメッセージ。
-///
-/// # 注意点
-/// - URLの形式が正しいかどうかはこの関数では検証されません。
-/// - 書き込み権限がない場合や設定ファイルが壊れている場合、エラーが発生します。
-///
-/// # 使用例
-/// ```rust
-/// use tauri::State;
-/// use flash_code::ConfigState;
-///
-/// #[tauri::command]
-/// async fn update_webhook_url_example(config_state: State<'_, ConfigState>, url: String) {
-///     match update_webhook_url(config_state, url).await {
-///         Ok(_) => println!("Webhook URLが正常に更新されました。"),
-///         Err(e) => eprintln!("Webhook URLの更新に失敗しました: {}", e),
-///     }
-/// }
-/// ```
+/// - `Ok(())`: 一時停止した場合。
+/// - `Err(String)`: 監視対象が存在しない、または実行中でない場合。
 ///
 #[tauri::command]
-pub async fn update_webhook_url(
-    config_state: State<'_, ConfigState>,
-    url: String,
+pub async fn pause_monitoring_target(
+    monitor_state: State<'_, MonitorState>,
+    app: AppInfo,
 ) -> Result<(), String> {
-    match crate::config_manager::update_webhook_url(config_state, url).await {
+    match monitor_state.pause_target(&app.target_key()).await {
         Ok(_) => Ok(()),
         Err(e) => {
-            error!("Webhook URLの更新に失敗しました: {}", e);
+            error!("監視の一時停止に失敗しました: {}", e);
             Err(e)
         }
     }
 }
 
-/// しきい値を更新する関数。
+/// 一時停止中の監視対象の監視を再開するコマンド。
 ///
 /// # 概要
-/// 設定ファイル（appsettings.json）の `THRESHOLD` 項目を更新します。  
-/// 新しいしきい値を指定し、設定ファイルに保存します。
+/// 一時停止時に保持していた設定で監視を再開します。比較元のアイコン画像は再開時点で取得し直します。
 ///
 /// # 引数
-/// * `config_state` - 設定ファイルのパスを管理する `ConfigState`。
-/// * `threshold` - 新しいしきい値（文字列形式）。
+/// * `monitor_state` - 監視タスクの管理を行う `MonitorState`。
+/// * `config_state` - 設定ファイルの `ConfigState`。
+/// * `app` - 再開する監視対象のアプリケーション情報。
 ///
 /// # 戻り値
 /// `Result`:
-/// - `Ok(())`: しきい値が正常に更新された場合。
-/// - `Err(String)`: 設定ファイルの読み書きまたは解析に失敗した場合、そのエラー内容を含むメッセージ。
-///
-/// # 注意点
-/// - しきい値の形式が正しいかどうかはこの関数では検証されません。
-///
-/// # 使用例
-/// ```rust
-/// use tauri::State;
-/// use flash_code::ConfigState;
-///
-/// #[tauri::command]
-/// async fn update_threshold_example(config_state: State<'_, ConfigState>, threshold: String) {
-///     match update_threshold(config_state, threshold).await {
-///         Ok(_) => println!("しきい値が正常に更新されました。"),
-///         Err(e) => eprintln!("しきい値の更新に失敗しました: {}", e),
-///     }
-/// }
-/// ```
+/// - `Ok(())`: 再開した場合。
+/// - `Err(String)`: 監視対象が存在しない、またはすでに実行中の場合。
 ///
 #[tauri::command]
-pub async fn update_threshold(
+pub async fn resume_monitoring_target(
+    monitor_state: State<'_, MonitorState>,
     config_state: State<'_, ConfigState>,
-    threshold: String,
+    app: AppInfo,
 ) -> Result<(), String> {
-    match crate::config_manager::update_threshold(config_state, threshold).await {
+    match monitor_state
+        .resume_target(&app.target_key(), config_state)
+        .await
+    {
         Ok(_) => Ok(()),
         Err(e) => {
-            error!("しきい値の更新に失敗しました: {}", e);
+            error!("監視の再開に失敗しました: {}", e);
             Err(e)
         }
     }
 }
 
-/// 監視間隔を更新する関数。
+/// 検知した変化を確認済みにするコマンド。
 ///
 /// # 概要
-/// 設定ファイル（appsettings.json）の `INTERVAL` 項目を更新します。  
-/// 新しい監視間隔（ミリ秒単位）を指定し、設定ファイルに保存します。
+/// エスカレーションポリシーを設定した監視対象が確認待ち（unacknowledged）の場合に、保留中のエスカレーションを取り消します。  
+/// 監視対象のウィンドウを元のサイズへ戻す・前面に表示する、またはアイコンが監視開始時の状態へ戻った場合も、確認済みとして扱われます。
 ///
 /// # 引数
-/// * `config_state` - 設定ファイルのパスを管理する `ConfigState`。
-/// * `interval` - 新しい監視間隔（文字列形式、ミリ秒単位）。
+/// * `monitor_state` - 監視タスクの管理を行う `MonitorState`。
+/// * `app` - 確認済みにする監視対象のアプリケーション情報。
 ///
 /// # 戻り値
 /// `Result`:
-/// - `Ok(())`: 監視間隔が正常に更新された場合。
-/// - `Err(String)`: 設定ファイルの読み書きまたは解析に失敗した場合、そのエラー内容を含むメッセージ。
-///
-/// # 注意点
-/// - 監視間隔の形式が正しいかどうかはこの関数では検証されません。
-///
-/// # 使用例
-/// ```rust
-/// use tauri::State;
-/// use flash_code::ConfigState;
-///
-/// #[tauri::command]
-/// async fn update_interval_example(config_state: State<'_, ConfigState>, interval: String) {
-///     match update_interval(config_state, interval).await {
-///         Ok(_) => println!("監視間隔が正常に更新されました。"),
-///         Err(e) => eprintln!("監視間隔の更新に失敗しました: {}", e),
-///     }
-/// }
-/// ```
+/// - `Ok(())`: 確認済みにした場合。
+/// - `Err(String)`: 監視対象が存在しない、または確認待ちでない場合。
 ///
 #[tauri::command]
-pub async fn update_interval(
-    config_state: State<'_, ConfigState>,
-    interval: String,
+pub async fn acknowledge_alert(
+    monitor_state: State<'_, MonitorState>,
+    app: AppInfo,
 ) -> Result<(), String> {
-    match crate::config_manager::update_interval(config_state, interval).await {
+    match monitor_state.acknowledge_target(&app.target_key()).await {
         Ok(_) => Ok(()),
         Err(e) => {
-            error!("監視間隔の更新に失敗しました: {}", e);
+            error!("検知した変化の確認に失敗しました: {}", e);
             Err(e)
         }
     }
 }
 
-/// Webhook URLを取得する関数。
+/// 監視状況を取得するコマンド。
 ///
 /// # 概要
-/// 設定ファイル（appsettings.json）から`DISCORD_WEBHOOK_URL`項目を取得します。
+/// 監視対象ごとの状態（starting / watching / paused / unacknowledged / triggered / lost / error）、
+/// 最後にアイコン画像を取得した時刻、最後の差分値、連続取得失敗回数、監視開始時刻を返します。  
+/// 監視状況が変化した際には `monitor_status` イベントでも同じ内容が通知されます。
 ///
 /// # 引数
-/// * `config_state` - 設定ファイルのパスを管理する `ConfigState`。
+/// * `monitor_state` - 監視タスクの管理を行う `MonitorState`。
 ///
 /// # 戻り値
-/// `Result`:
-/// - `Ok(String)`: Webhook URLが正常に取得された場合。その値を返します。
-/// - `Err(String)`: 設定ファイルの読み取りや解析に失敗した場合、または`DISCORD_WEBHOOK_URL`が存在しない場合にエラーメッセージを返します。
+/// 監視対象ごとの `TargetStatus` のリスト。ウィンドウタイトル順に並びます。
 ///
-/// # 注意点
-/// - 設定ファイルが存在しない、または正しく構成されていない場合、エラーとなります。
-/// - `DISCORD_WEBHOOK_URL`が設定されていない場合、エラーが返されます。
+#[tauri::command]
+pub async fn get_monitoring_status(
+    monitor_state: State<'_, MonitorState>,
+) -> Result<Vec<TargetStatus>, String> {
+    Ok(monitor_state.statuses().await)
+}
+
+/// 送信待ちキューの内容を取得するコマンド。
 ///
-/// # 使用例
-/// ```rust
-/// use tauri::State;
-/// use flash_code::ConfigState;
+/// # 概要
+/// オフラインなどで送信できず、送信待ちキューに保存されている通知を保存した順に返します。  
+/// 各通知には元の検知時刻（`event.detected_at`）、送信を試みた回数、最後のエラー内容が含まれます。
 ///
-/// #[tauri::command]
-/// async fn get_webhook_url_example(config_state: State<'_, ConfigState>) {
-///     match get_webhook_url(config_state).await {
-///         Ok(url) => println!("Webhook URL: {}", url),
-///         Err(e) => eprintln!("Webhook URLの取得に失敗しました: {}", e),
-///     }
-/// }
-/// ```
+/// # 引数
+/// * `notification_queue` - 送信待ちキューを管理する `NotificationQueue`。
 ///
 #[tauri::command]
-pub async fn update_line_enabled(
-    config_state: State<'_, ConfigState>,
-    enabled: String,
-) -> Result<(), String> {
-    match crate::config_manager::update_line_enabled(config_state, enabled).await {
-        Ok(_) => Ok(()),
+pub async fn get_notification_queue(
+    notification_queue: State<'_, NotificationQueue>,
+) -> Result<Vec<QueuedNotification>, String> {
+    Ok(notification_queue.entries().await)
+}
+
+/// 配信履歴を取得するコマンド。
+///
+/// # 概要
+/// 検知や送信待ちキューからの送信ごとに記録した、通知先ごとの送信結果と送信を試みた回数を新しい順に返します。  
+/// `filter` で記録した時刻の範囲、監視対象（ウィンドウタイトルまたは実行ファイル名）、件数の上限を指定できます。
+///
+/// # 引数
+/// * `delivery_history` - 配信履歴を管理する `DeliveryHistory`。
+/// * `filter` - 検索条件。指定しない場合はすべての履歴を返します。
+///
+/// # 戻り値
+/// `Result`:
+/// - `Ok(Vec<HistoryEntry>)`: 検索条件に一致した履歴。
+/// - `Err(String)`: 履歴のファイルの読み込みに失敗した場合。
+///
+#[tauri::command]
+pub async fn get_delivery_history(
+    delivery_history: State<'_, DeliveryHistory>,
+    filter: Option<HistoryFilter>,
+) -> Result<Vec<HistoryEntry>, String> {
+    match delivery_history.query(&filter.unwrap_or_default()).await {
+        Ok(entries) => Ok(entries),
         Err(e) => {
-            error!("LINE enabled flag update failed: {}", e);
+            error!("配信履歴の取得に失敗しました: {}", e);
             Err(e)
         }
     }
 }
 
+/// 配信履歴をすべて削除するコマンド。
+///
+/// # 引数
+/// * `delivery_history` - 配信履歴を管理する `DeliveryHistory`。
+///
 #[tauri::command]
-pub async fn update_minimize_on_monitor_start(
-    config_state: State<'_, ConfigState>,
-    enabled: bool,
+pub async fn clear_delivery_history(
+    delivery_history: State<'_, DeliveryHistory>,
 ) -> Result<(), String> {
-    match crate::config_manager::update_minimize_on_monitor_start(config_state, enabled).await {
+    match delivery_history.clear().await {
         Ok(_) => Ok(()),
         Err(e) => {
-            error!("最小化設定の更新に失敗しました: {}", e);
+            error!("配信履歴の削除に失敗しました: {}", e);
             Err(e)
         }
     }
 }
 
+/// すべての設定を取得するコマンド。
+///
+/// # 概要
+/// 設定ファイル（appsettings.json）の内容を、従来と同じキー（`THRESHOLD` など）の JSON オブジェクトとして返します。  
+/// 不正な値は既定値に置き換えられます。Webhook URL やトークンなどの秘密情報は、設定済みの場合は `null`、未設定の場合は空文字で返します。
+///
+/// # 戻り値
+/// `Result`:
+/// - `Ok(Value)`: 設定。
+/// - `Err(String)`: 設定ファイルの読み込みや解析に失敗した場合。
+///
 #[tauri::command]
-pub async fn update_line_channel_access_token(
-    config_state: State<'_, ConfigState>,
-    token: String,
-) -> Result<(), String> {
-    match crate::config_manager::update_line_channel_access_token(config_state, token).await {
-        Ok(_) => Ok(()),
+pub async fn get_app_config(config_state: State<'_, ConfigState>) -> Result<Value, String> {
+    match crate::config_manager::get_app_config_without_secrets(config_state).await {
+        Ok(config) => Ok(config),
         Err(e) => {
-            error!("LINE channel access token update failed: {}", e);
+            error!("設定の取得に失敗しました: {}", e);
             Err(e)
         }
     }
 }
 
+/// すべての設定を更新するコマンド。
+///
+/// # 概要
+/// 設定ファイル（appsettings.json）を `config` で置き換えます。値は保存前に検証し、不正な値がある場合は保存しません。  
+/// 秘密情報の値が `null` の場合、またはキーがない場合は保存済みの値を変更せず、空文字の場合は削除します。  
+/// `get_app_config` で取得した設定を変更して渡してください。
+///
+/// # 戻り値
+/// `Result`:
+/// - `Ok(())`: 設定を更新した場合。
+/// - `Err(String)`: 値の変換や検証に失敗した場合、または設定ファイルの書き込みに失敗した場合。
+///
 #[tauri::command]
-pub async fn update_line_target(
+pub async fn update_app_config(
     config_state: State<'_, ConfigState>,
-    target: String,
+    config: Value,
 ) -> Result<(), String> {
-    match crate::config_manager::update_line_target(config_state, target).await {
+    match crate::config_manager::replace_app_config(config_state, config).await {
         Ok(_) => Ok(()),
         Err(e) => {
-            error!("LINE target update failed: {}", e);
+            error!("設定の更新に失敗しました: {}", e);
             Err(e)
         }
     }
 }
 
+/// 監視対象ごとの設定を取得するコマンド。
+///
+/// # 概要
+/// 設定ファイル（appsettings.json）の `TARGET_SETTINGS` から、指定したアプリケーションの設定を取得します。  
+/// アプリケーションは実行ファイル名とウィンドウタイトル（`AppInfo::identity`）で識別されます。
+///
+/// # 引数
+/// * `config_state` - 設定ファイルのパスを管理する `ConfigState`。
+/// * `app` - 設定を取得するアプリケーション情報。
+///
+/// # 戻り値
+/// `Result`:
+/// - `Ok(TargetSettings)`: 監視対象ごとの設定。未設定の項目は `null` で、全体設定が使用されます。
+/// - `Err(String)`: 設定ファイルの読み込みや解析に失敗した場合。
+///
 #[tauri::command]
-pub async fn get_webhook_url(config_state: State<'_, ConfigState>) -> Result<String, String> {
-    match crate::config_manager::get_webhook_url(config_state).await {
-        Ok(url) => Ok(url),
+pub async fn get_target_settings(
+    config_state: State<'_, ConfigState>,
+    app: AppInfo,
+) -> Result<TargetSettings, String> {
+    match crate::config_manager::get_target_settings(config_state, &app.identity()).await {
+        Ok(settings) => Ok(settings),
         Err(e) => {
-            error!("Webhook URLの取得に失敗しました: {}", e);
+            error!("監視対象ごとの設定の取得に失敗しました: {}", e);
             Err(e)
         }
     }
 }
 
-/// 設定ファイルからしきい値を取得する関数。
+/// 監視対象ごとの設定を更新するコマンド。
 ///
 /// # 概要
-/// 設定ファイル（例: appsettings.json）に保存されている `THRESHOLD` 値を取得します。
+/// 設定ファイル（appsettings.json）の `TARGET_SETTINGS` に、指定したアプリケーションの設定を保存します。  
+/// すべての項目が `null` の場合は監視対象ごとの設定を削除し、全体設定へ戻します。  
+/// 保存した設定は次回の監視開始時から反映されます。
 ///
 /// # 引数
-/// * `config_state` - 設定ファイルのパスや状態を管理する `ConfigState`。
+/// * `config_state` - 設定ファイルのパスを管理する `ConfigState`。
+/// * `app` - 設定を保存するアプリケーション情報。
+/// * `settings` - 保存する設定。
 ///
 /// # 戻り値
-/// `Result<String, String>`
-/// - `Ok(String)`: 取得に成功した場合、しきい値が文字列として返されます。
-/// - `Err(String)`: 取得に失敗した場合、エラー内容が文字列で返されます。
+/// `Result`:
+/// - `Ok(())`: 設定が正常に保存された場合。
+/// - `Err(String)`: 監視間隔やしきい値、エスカレーションの設定が範囲外の場合、または設定ファイルの読み書きに失敗した場合。
 ///
-/// # 使用例
-/// ```rust
-/// #[tauri::command]
-/// async fn example(config_state: State<'_, ConfigState>) {
-///     match get_threshold(config_state).await {
-///         Ok(threshold) => println!("しきい値: {}", threshold),
-///         Err(e) => eprintln!("取得に失敗: {}", e),
-///     }
-/// }
-/// ```
 #[tauri::command]
-pub async fn get_threshold(config_state: State<'_, ConfigState>) -> Result<String, String> {
-    match crate::config_manager::get_threshold(config_state).await {
-        Ok(url) => Ok(url),
+pub async fn update_target_settings(
+    config_state: State<'_, ConfigState>,
+    app: AppInfo,
+    settings: TargetSettings,
+) -> Result<(), String> {
+    settings.validate()?;
+    match crate::config_manager::update_target_settings(config_state, &app.identity(), settings)
+        .await
+    {
+        Ok(_) => Ok(()),
         Err(e) => {
-            error!("しきい値の取得に失敗しました: {}", e);
+            error!("監視対象ごとの設定の更新に失敗しました: {}", e);
             Err(e)
         }
     }
 }
 
-/// 設定ファイルから監視間隔を取得する関数。
+/// 設定ファイルから監視設定を読み込み、検証します。
 ///
 /// # 概要
-/// 設定ファイル（例: appsettings.json）に保存されている `INTERVAL` 値を取得します。
+/// 監視対象ごとの設定（`TARGET_SETTINGS`）に値がある項目はその値を、
+/// ない項目は全体設定（`INTERVAL`、`THRESHOLD`、`MINIMIZE_ON_MONITOR_START`）を使用します。
 ///
-/// # 引数
-/// * `config_state` - 設定ファイルのパスや状態を管理する `ConfigState`。
+/// # 戻り値
+/// * `Ok(MonitorSettings)` - 監視間隔・しきい値・最小化設定・判定方式・デスクトップ通知の有無・エスカレーションポリシー。
+/// * `Err(String)` - 設定ファイルの読み込み、解析、または検証に失敗した場合。
+pub(crate) async fn load_monitor_settings(
+    config_state: State<'_, ConfigState>,
+    app: &AppInfo,
+) -> Result<MonitorSettings, String> {
+    let mut config = match crate::config_manager::get_app_config(config_state).await {
+        Ok(val) => val,
+        Err(e) => {
+            error!("設定の取得に失敗しました: {}", e);
+            return Err(e);
+        }
+    };
+    let target_settings = config
+        .target_settings
+        .remove(&app.identity())
+        .unwrap_or_default();
+
+    Ok(MonitorSettings {
+        interval: target_settings.interval.unwrap_or(config.interval),
+        threshold: target_settings.threshold.unwrap_or(config.threshold),
+        minimize_on_start: target_settings
+            .minimize_on_start
+            .unwrap_or(config.minimize_on_monitor_start),
+        detector: target_settings.detector.unwrap_or_default(),
+        desktop_notification: target_settings.desktop_notification.unwrap_or(false),
+        escalation: target_settings
+            .escalation
+            .filter(|escalation| !escalation.is_empty()),
+    })
+}
+
+/// タスクバーに表示されているアプリ情報を取得するコマンド。
+///
+/// # 概要
+/// タスクバーに現在表示されているすべてのアプリケーションの情報を取得します。
 ///
 /// # 戻り値
-/// `Result<String, String>`
-/// - `Ok(String)`: 取得に成功した場合、監視間隔が文字列として返されます。
-/// - `Err(String)`: 取得に失敗した場合、エラー内容が文字列で返されます。
+/// タスクバーに表示されている`AppInfo`オブジェクトのベクターを返します。
 ///
 /// # 使用例
 /// ```rust
+/// use flash_code::AppInfo;
+///
 /// #[tauri::command]
-/// async fn example(config_state: State<'_, ConfigState>) {
-///     match get_interval(config_state).await {
-///         Ok(interval) => println!("監視間隔: {}", interval),
-///         Err(e) => eprintln!("取得に失敗: {}", e),
-///     }
+/// pub fn get_taskbar_apps_example() -> Vec<AppInfo> {
+///     get_taskbar_apps()
 /// }
 /// ```
 ///
 #[tauri::command]
-pub async fn get_interval(config_state: State<'_, ConfigState>) -> Result<String, String> {
-    match crate::config_manager::get_interval(config_state).await {
-        Ok(url) => Ok(url),
+pub fn get_taskbar_apps() -> Vec<AppInfo> {
+    crate::window_collection::get_taskbar_apps()
+}
+
+/// 通知の言語と通知先ごとの本文テンプレートを取得するコマンド。
+///
+/// # 概要
+/// 設定ファイル（appsettings.json）の `NOTIFY_LANGUAGE` と `MESSAGE_TEMPLATES` から、
+/// 本文テンプレートを設定できるすべての通知先のテンプレートと、現在の言語の既定のテンプレートを取得します。
+///
+#[tauri::command]
+pub async fn get_message_template_settings(
+    config_state: State<'_, ConfigState>,
+) -> Result<MessageTemplateSettings, String> {
+    match crate::config_manager::get_message_template_settings(config_state).await {
+        Ok(settings) => Ok(settings),
+        Err(e) => {
+            error!("本文テンプレートの設定の取得に失敗しました: {}", e);
+            Err(e)
+        }
+    }
+}
+
+/// 本文テンプレートをサンプルの検知イベントで置き換えた本文を返すコマンド。
+///
+/// # 概要
+/// 保存前のテンプレートを確認できるよう、`template` と `language` を指定した場合はその値を使用します。  
+/// 指定しない場合は設定ファイルの値を使用し、テンプレートが未設定の場合は既定のテンプレートを使用します。
+///
+/// # 戻り値
+/// `Result`:
+/// - `Ok(String)`: 置き換えた本文。
+/// - `Err(String)`: 本文テンプレートを設定できない通知先の場合、使用できないプレースホルダーを含む場合、
+///   または設定ファイルの読み込みに失敗した場合。
+///
+#[tauri::command]
+pub async fn preview_message_template(
+    config_state: State<'_, ConfigState>,
+    notifier: String,
+    template: Option<String>,
+    language: Option<Language>,
+) -> Result<String, String> {
+    let template = template.unwrap_or_default();
+    validate_message_template(&notifier, &template)?;
+    let settings = crate::config_manager::get_message_template_settings(config_state).await?;
+    let language = language.unwrap_or(settings.language);
+    let template = if template.trim().is_empty() {
+        settings
+            .templates
+            .into_iter()
+            .find(|configured| configured.notifier == notifier)
+            .map(|configured| configured.template)
+            .filter(|configured| !configured.trim().is_empty())
+            .unwrap_or_else(|| default_template(language, &notifier).to_string())
+    } else {
+        template
+    };
+    Ok(MessageTemplate { language, template }.render(&preview_event()))
+}
+
+/// ルーティングルールの一覧を取得するコマンド。
+///
+/// # 概要
+/// 設定ファイル（appsettings.json）の `ROUTING_RULES` を評価順に取得します。
+///
+#[tauri::command]
+pub async fn get_routing_rules(
+    config_state: State<'_, ConfigState>,
+) -> Result<Vec<RoutingRule>, String> {
+    match crate::config_manager::get_routing_rules(config_state).await {
+        Ok(rules) => Ok(rules),
         Err(e) => {
-            error!("監視間隔の取得に失敗しました: {}", e);
+            error!("ルーティングルールの取得に失敗しました: {}", e);
             Err(e)
         }
     }
 }
 
+/// ルーティングルールの一覧を更新するコマンド。
+///
+/// # 概要
+/// 設定ファイル（appsettings.json）の `ROUTING_RULES` を指定した一覧で置き換えます。  
+/// ルールは上から順に評価され、最初に一致したルールの通知先へ送信します。監視中の対象には、次に監視を開始したときから反映されます。
+///
+/// # 戻り値
+/// `Result`:
+/// - `Ok(())`: 設定を更新した場合。
+/// - `Err(String)`: 条件または通知先が指定されていないルールがある場合、または設定ファイルの書き込みに失敗した場合。
+///
 #[tauri::command]
-pub async fn get_minimize_on_monitor_start(
+pub async fn update_routing_rules(
     config_state: State<'_, ConfigState>,
-) -> Result<bool, String> {
-    match crate::config_manager::get_minimize_on_monitor_start(config_state).await {
-        Ok(enabled) => Ok(enabled),
+    rules: Vec<RoutingRule>,
+) -> Result<(), String> {
+    for rule in &rules {
+        rule.validate()?;
+    }
+    match crate::config_manager::update_routing_rules(config_state, rules).await {
+        Ok(_) => Ok(()),
         Err(e) => {
-            error!("最小化設定の取得に失敗しました: {}", e);
+            error!("ルーティングルールの更新に失敗しました: {}", e);
             Err(e)
         }
     }
 }
 
+/// 通知先のインスタンスの一覧を取得するコマンド。
+///
+/// # 概要
+/// 設定ファイル（appsettings.json）の `NOTIFIER_INSTANCES` を取得します。  
+/// Webhook URL やトークンは返さず、設定済みかどうかだけを `configured_secrets` で返します。
+///
 #[tauri::command]
-pub async fn get_line_enabled(config_state: State<'_, ConfigState>) -> Result<String, String> {
-    match crate::config_manager::get_line_enabled(config_state).await {
-        Ok(enabled) => Ok(enabled),
+pub async fn list_notifier_instances(
+    config_state: State<'_, ConfigState>,
+) -> Result<Vec<NotifierInstanceSummary>, String> {
+    match crate::config_manager::list_notifier_instances(config_state).await {
+        Ok(instances) => Ok(instances),
         Err(e) => {
-            error!("LINE enabled flag read failed: {}", e);
+            error!("通知先の一覧の取得に失敗しました: {}", e);
             Err(e)
         }
     }
 }
 
+/// 通知先のインスタンスを追加するコマンド。
+///
+/// # 概要
+/// 同じ種類の通知先（複数の Discord Webhook や LINE の送信先など）を、名前を付けて追加します。  
+/// 監視中の対象には、次に監視を開始したときから反映されます。
+///
+/// # 戻り値
+/// `Result`:
+/// - `Ok(())`: 追加した場合。
+/// - `Err(String)`: 名前や種類、設定キーが不正な場合、名前が既に使用されている場合、または設定ファイルの書き込みに失敗した場合。
+///
 #[tauri::command]
-pub async fn get_line_channel_access_token_configured(
+pub async fn add_notifier_instance(
     config_state: State<'_, ConfigState>,
-) -> Result<bool, String> {
-    match crate::config_manager::get_line_channel_access_token_configured(config_state).await {
-        Ok(configured) => Ok(configured),
+    mut instance: NotifierInstance,
+) -> Result<(), String> {
+    instance.id = instance.id.trim().to_string();
+    instance.validate()?;
+    match crate::config_manager::add_notifier_instance(config_state, instance).await {
+        Ok(_) => Ok(()),
         Err(e) => {
-            error!("LINE channel access token state read failed: {}", e);
+            error!("通知先の追加に失敗しました: {}", e);
             Err(e)
         }
     }
 }
 
+/// 通知先のインスタンスを更新するコマンド。
+///
+/// # 概要
+/// 同じ名前のインスタンスの表示名、有効状態、設定を更新します。  
+/// Webhook URL やトークンを `null` で送信した場合、または省略した場合は保存済みの値を変更せず、空文字で送信した場合は削除します。
+///
+/// # 戻り値
+/// `Result`:
+/// - `Ok(())`: 更新した場合。
+/// - `Err(String)`: 種類や設定キーが不正な場合、通知先が存在しない場合、または設定ファイルの書き込みに失敗した場合。
+///
 #[tauri::command]
-pub async fn get_line_target(config_state: State<'_, ConfigState>) -> Result<String, String> {
-    match crate::config_manager::get_line_target(config_state).await {
-        Ok(target) => Ok(target),
+pub async fn update_notifier_instance(
+    config_state: State<'_, ConfigState>,
+    mut instance: NotifierInstance,
+) -> Result<(), String> {
+    instance.id = instance.id.trim().to_string();
+    instance.validate()?;
+    match crate::config_manager::update_notifier_instance(config_state, instance).await {
+        Ok(_) => Ok(()),
         Err(e) => {
-            error!("LINE target read failed: {}", e);
+            error!("通知先の更新に失敗しました: {}", e);
             Err(e)
         }
     }
 }
 
+/// 通知先のインスタンスを削除するコマンド。
+///
+/// # 概要
+/// 削除した通知先を指定しているルーティングルールは、その通知先へ送信しなくなります。
+///
+#[tauri::command]
+pub async fn remove_notifier_instance(
+    config_state: State<'_, ConfigState>,
+    id: String,
+) -> Result<(), String> {
+    match crate::config_manager::remove_notifier_instance(config_state, &id).await {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("通知先の削除に失敗しました: {}", e);
+            Err(e)
+        }
+    }
+}
+
+/// テスト通知を送信するコマンド。
+///
+/// # 概要
+/// Webhook URL やトークンを設定した後、実際の検知を待たずに送信できるかを確認するため、  
+/// サンプルの検知イベントを設定ファイルの通知先へ送信します。再送は行わず、送信待ちキューにも保存しません。
+///
+/// # 引数
+/// * `notifier` - 送信する通知先の名前（`discord` や、追加した通知先の名前）。指定しない場合はすべての通知先へ送信します。
+///
+/// # 戻り値
+/// `Result`:
+/// - `Ok(Vec<TestNotificationResult>)`: 通知先ごとの成否、HTTP ステータスコード、エラー内容。
+/// - `Err(String)`: 設定ファイルの読み込みに失敗した場合、指定した通知先が設定されていない場合、
+///   または通知先が 1 つも設定されていない場合。
+///
+#[tauri::command]
+pub async fn send_test_notification(
+    config_state: State<'_, ConfigState>,
+    notifier: Option<String>,
+) -> Result<Vec<TestNotificationResult>, String> {
+    let notifier = notifier
+        .as_deref()
+        .map(str::trim)
+        .filter(|notifier| !notifier.is_empty());
+    let result = match NotifierRegistry::load(&config_state.path) {
+        Ok(registry) => registry.send_test(notifier, &preview_event()).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(results) => Ok(results),
+        Err(e) => {
+            error!("テスト通知の送信に失敗しました: {}", e);
+            Err(e)
+        }
+    }
+}
+
+/// 起動時の監視再開の結果を取得するコマンド。
+///
+/// # 概要
+/// 起動時に前回の監視対象の監視を再開した場合、再開できた監視対象と
+/// ウィンドウが見つからなかった監視対象を返します。  
+/// 同じ内容は `monitoring_resumed` イベントでも通知されますが、
+/// フロントエンドの初期化前にイベントが発行される場合があるため、このコマンドでも取得できます。
+///
+/// # 戻り値
+/// - `Ok(Some(ResumeReport))`: 起動時に監視の再開を試みた場合。
+/// - `Ok(None)`: 監視の再開が無効、または再開する監視対象がなかった場合。
+///
+#[tauri::command]
+pub async fn get_resume_report(
+    resume_report_state: State<'_, ResumeReportState>,
+) -> Result<Option<ResumeReport>, String> {
+    Ok(resume_report_state.report.lock().await.clone())
+}
+
 #[tauri::command]
 pub fn log_from_frontend(level: String, message: String) {
     match level.as_str() {
@@ -1,378 +1,556 @@
 use log::{error, info, warn};
-use serde_json::Value;
-use std::fs;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{collections::HashMap, fs};
 use tauri::State;
 
+use crate::app_config::{validate_interval, validate_threshold, AppConfig};
+use crate::escalation::EscalationPolicy;
+use crate::image_comparison::DetectorConfig;
+use crate::message_template::{default_template, Language, TEMPLATE_NOTIFIERS};
+use crate::notification_routing::RoutingRule;
+use crate::notifier_instance::{NotifierInstance, NOTIFIER_INSTANCES_KEY};
 use crate::window_utils::{get_or_create_config_file_path, initilize_config_file, ConfigState};
 
-const KEY_NOT_FOUND_PREFIX: &str = "CONFIG_KEY_NOT_FOUND:";
-
-/// Discord Webhook URL を設定ファイルへ保存します。
+/// 値をフロントエンドへ返さない秘密情報の設定キー。
+const SECRET_CONFIG_KEYS: &[&str] = &[
+    "DISCORD_WEBHOOK_URL",
+    "LINE_CHANNEL_ACCESS_TOKEN",
+    "SLACK_WEBHOOK_URL",
+    "TEAMS_WEBHOOK_URL",
+    "TELEGRAM_BOT_TOKEN",
+    "NTFY_TOPIC_URL",
+    "NTFY_ACCESS_TOKEN",
+    "GOTIFY_APP_TOKEN",
+    "PUSHOVER_USER_KEY",
+    "PUSHOVER_APP_TOKEN",
+    "SMTP_PASSWORD",
+    "HTTP_WEBHOOK_URL",
+];
+
+/// 項目の値をフロントエンドへ返さない秘密情報の設定キー。JSON オブジェクトの設定で、項目名だけを返します。
+const SECRET_MAP_CONFIG_KEYS: &[&str] = &["HTTP_WEBHOOK_HEADERS"];
+
+/// 監視対象ごとの設定。
 ///
 /// # 概要
-/// `appsettings.json` の `DISCORD_WEBHOOK_URL` を更新します。
-/// 空文字も有効な値として保存され、その場合 Discord 通知側で送信がスキップされます。
+/// `appsettings.json` の `TARGET_SETTINGS` に、`AppInfo::identity` をキーとして保存されます。
+/// `None` の項目は全体設定（`INTERVAL`、`THRESHOLD`、`MINIMIZE_ON_MONITOR_START`）と既定の判定方式を使用します。
+/// `desktop_notification` が `None` の場合、デスクトップ通知は表示しません。
+/// `escalation` が `None` の場合、検知後すぐに監視を終了し、エスカレーションは行いません。
+///
+/// # フィールド
+/// - `interval`: 監視間隔（ミリ秒）。
+/// - `threshold`: 画像差分のしきい値（`0.0〜1.0`）。
+/// - `minimize_on_start`: 監視開始時に対象ウィンドウを最小化するかどうか。
+/// - `detector`: アイコンの変化の判定方式。
+/// - `desktop_notification`: 変化を検知した際にデスクトップ通知（トースト通知）を表示するかどうか。
+/// - `escalation`: 検知した変化が確認されない場合のエスカレーションポリシー。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TargetSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimize_on_start: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detector: Option<DetectorConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desktop_notification: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escalation: Option<EscalationPolicy>,
+}
+
+impl TargetSettings {
+    fn is_empty(&self) -> bool {
+        self == &TargetSettings::default()
+    }
+
+    /// 監視間隔・しきい値の範囲と、判定方式、エスカレーションポリシーを検証します。
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(interval) = self.interval {
+            validate_interval(interval)?;
+        }
+        if let Some(threshold) = self.threshold {
+            validate_threshold(threshold)?;
+        }
+        if let Some(detector) = &self.detector {
+            detector.validate()?;
+        }
+        if let Some(escalation) = &self.escalation {
+            escalation.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// 通知先ごとの本文テンプレート。
+///
+/// # フィールド
+/// - `notifier`: 通知先の名前（`Notifier::name`）。
+/// - `template`: 設定されているテンプレート。空の場合は既定のテンプレートを使用します。
+/// - `default_template`: 現在の言語の既定のテンプレート。
+#[derive(Clone, Debug, Serialize)]
+pub struct NotifierMessageTemplate {
+    pub notifier: String,
+    pub template: String,
+    pub default_template: String,
+}
+
+/// 通知の言語と本文テンプレートの設定。`get_message_template_settings` コマンドで使用します。
+#[derive(Clone, Debug, Serialize)]
+pub struct MessageTemplateSettings {
+    pub language: Language,
+    pub templates: Vec<NotifierMessageTemplate>,
+}
+
+/// 通知先のインスタンス。`list_notifier_instances` コマンドの戻り値として使用します。
+///
+/// # フィールド
+/// - `instance`: インスタンスの設定。Webhook URL やトークンなどの秘密情報は、設定済みの場合は `null`、未設定の場合は空文字に置き換えます。
+/// - `configured_secrets`: 設定済みの秘密情報の設定キー。値自体はフロントエンドへ返しません。
+#[derive(Clone, Debug, Serialize)]
+pub struct NotifierInstanceSummary {
+    #[serde(flatten)]
+    pub instance: NotifierInstance,
+    pub configured_secrets: Vec<String>,
+}
+
+/// 監視対象ごとの設定を取得します。
 ///
 /// # 引数
 /// * `config_state` - Tauri state に保持されている設定ファイルパス。
-/// * `url` - 保存する Discord Webhook URL。
+/// * `identity` - 監視対象の識別キー（`AppInfo::identity`）。
 ///
 /// # 戻り値
-/// * `Ok(())` - 設定ファイルの更新に成功した場合。
-/// * `Err(String)` - 設定ファイルの読み込み、JSON 解析、または書き込みに失敗した場合。
-///
-/// # 注意
-/// Webhook URL は秘密情報に近いため、更新ログでは値をマスクします。
-pub async fn update_webhook_url(
+/// * `Ok(TargetSettings)` - 保存されている設定。未設定の場合はすべて `None` の設定。
+/// * `Err(String)` - 設定ファイルの読み込み、または JSON 解析に失敗した場合。
+pub async fn get_target_settings(
     config_state: State<'_, ConfigState>,
-    url: String,
-) -> Result<(), String> {
-    update_config_value(
-        config_state,
-        "DISCORD_WEBHOOK_URL",
-        &url,
-        "Webhook URL updated",
-    )
-    .await
+    identity: &str,
+) -> Result<TargetSettings, String> {
+    let mut all_settings = get_all_target_settings(config_state).await?;
+    Ok(all_settings.remove(identity).unwrap_or_default())
 }
 
-/// 画像差分のしきい値を設定ファイルへ保存します。
-///
-/// # 概要
-/// `appsettings.json` の `THRESHOLD` を更新します。
-/// この関数では文字列として保存するだけで、数値範囲の検証は監視開始時に行います。
-///
-/// # 引数
-/// * `config_state` - Tauri state に保持されている設定ファイルパス。
-/// * `threshold` - 保存するしきい値。想定値は `0.0` から `1.0` までの文字列です。
+/// すべての監視対象ごとの設定を取得します。
 ///
 /// # 戻り値
-/// * `Ok(())` - 設定ファイルの更新に成功した場合。
-/// * `Err(String)` - 設定ファイルの読み込み、JSON 解析、または書き込みに失敗した場合。
-pub async fn update_threshold(
+/// * `Ok(HashMap<String, TargetSettings>)` - 識別キーと設定のマップ。`TARGET_SETTINGS` が存在しない場合は空。
+/// * `Err(String)` - 設定ファイルの読み込み、解析、または検証に失敗した場合。
+pub async fn get_all_target_settings(
     config_state: State<'_, ConfigState>,
-    threshold: String,
-) -> Result<(), String> {
-    update_config_value(config_state, "THRESHOLD", &threshold, "Threshold updated").await
+) -> Result<HashMap<String, TargetSettings>, String> {
+    Ok(get_app_config(config_state).await?.target_settings)
 }
 
-/// 監視間隔を設定ファイルへ保存します。
+/// 監視対象ごとの設定を設定ファイルへ保存します。
 ///
 /// # 概要
-/// `appsettings.json` の `INTERVAL` を更新します。
-/// この関数では文字列として保存するだけで、最小値などの検証は監視開始時に行います。
+/// `appsettings.json` の `TARGET_SETTINGS` の `identity` の項目を更新します。
+/// すべての項目が `None` の場合は項目を削除し、全体設定へ戻します。
 ///
 /// # 引数
 /// * `config_state` - Tauri state に保持されている設定ファイルパス。
-/// * `interval` - 保存する監視間隔。単位はミリ秒です。
+/// * `identity` - 監視対象の識別キー（`AppInfo::identity`）。
+/// * `settings` - 保存する設定。
 ///
 /// # 戻り値
 /// * `Ok(())` - 設定ファイルの更新に成功した場合。
-/// * `Err(String)` - 設定ファイルの読み込み、JSON 解析、または書き込みに失敗した場合。
-pub async fn update_interval(
+/// * `Err(String)` - 設定値が範囲外の場合、または設定ファイルの読み込み、検証、書き込みに失敗した場合。
+pub async fn update_target_settings(
     config_state: State<'_, ConfigState>,
-    interval: String,
+    identity: &str,
+    settings: TargetSettings,
 ) -> Result<(), String> {
-    update_config_value(config_state, "INTERVAL", &interval, "Interval updated").await
+    update_app_config(config_state, |config| {
+        if settings.is_empty() {
+            config.target_settings.remove(identity);
+        } else {
+            config
+                .target_settings
+                .insert(identity.to_string(), settings);
+        }
+        Ok(())
+    })
+    .await?;
+    info!("Target settings updated: {}", identity);
+    Ok(())
 }
 
-/// 監視開始時に対象ウィンドウを最小化するかどうかを設定ファイルへ保存します。
-pub async fn update_minimize_on_monitor_start(
+/// 通知の言語と、本文テンプレートを設定できるすべての通知先のテンプレートを設定ファイルから取得します。
+///
+/// 既存ユーザーの設定ファイルにキーが存在しない場合は、日本語と既定のテンプレートとして扱います。
+pub async fn get_message_template_settings(
     config_state: State<'_, ConfigState>,
-    enabled: bool,
-) -> Result<(), String> {
-    update_config_value(
-        config_state,
-        "MINIMIZE_ON_MONITOR_START",
-        if enabled { "true" } else { "false" },
-        "Minimize on monitor start flag updated",
-    )
-    .await
+) -> Result<MessageTemplateSettings, String> {
+    let config = get_app_config(config_state).await?;
+    let language = config.notify_language;
+    let mut configured = config.message_templates;
+    let templates = TEMPLATE_NOTIFIERS
+        .iter()
+        .map(|notifier| NotifierMessageTemplate {
+            notifier: notifier.to_string(),
+            template: configured.remove(*notifier).unwrap_or_default(),
+            default_template: default_template(language, notifier).to_string(),
+        })
+        .collect();
+    Ok(MessageTemplateSettings {
+        language,
+        templates,
+    })
 }
 
-/// LINE 通知の有効状態を設定ファイルへ保存します。
-///
-/// # 概要
-/// `appsettings.json` の `LINE_ENABLED` を更新します。
-/// LINE 通知は任意機能のため、`"true"` の場合だけ送信対象になります。
-/// 現在の設定ファイルは既存実装に合わせて文字列値を保存するため、boolean ではなく文字列として扱います。
-///
-/// # 引数
-/// * `config_state` - Tauri state に保持されている設定ファイルパス。
-/// * `enabled` - `"true"` または `"false"` を想定した文字列。
+/// ルーティングルールの一覧を設定ファイルから取得します。
 ///
 /// # 戻り値
-/// * `Ok(())` - 設定ファイルの更新に成功した場合。
-/// * `Err(String)` - 設定ファイルの読み込み、JSON 解析、または書き込みに失敗した場合。
-pub async fn update_line_enabled(
+/// * `Ok(Vec<RoutingRule>)` - 評価順のルール。`ROUTING_RULES` が存在しない場合は空。
+/// * `Err(String)` - 設定ファイルの読み込み、解析、または検証に失敗した場合。
+pub async fn get_routing_rules(
     config_state: State<'_, ConfigState>,
-    enabled: String,
-) -> Result<(), String> {
-    update_config_value(
-        config_state,
-        "LINE_ENABLED",
-        &enabled,
-        "LINE enabled flag updated",
-    )
-    .await
+) -> Result<Vec<RoutingRule>, String> {
+    Ok(get_app_config(config_state).await?.routing_rules)
 }
 
-/// LINE Channel Access Token を設定ファイルへ保存します。
+/// ルーティングルールの一覧を設定ファイルへ保存します。
 ///
 /// # 概要
-/// `appsettings.json` の `LINE_CHANNEL_ACCESS_TOKEN` を更新します。
-/// LINE 通知は `LINE_ENABLED` が `"true"` で、かつ token と target が両方設定されている場合だけ送信されます。
-///
-/// # 引数
-/// * `config_state` - Tauri state に保持されている設定ファイルパス。
-/// * `token` - 保存する LINE Messaging API の Channel Access Token。
+/// `appsettings.json` の `ROUTING_RULES` を `rules` で置き換えます。
+/// 各ルールの設定値は保存時に検証し、通知先の名前の検証は呼び出し元で行います。
+pub async fn update_routing_rules(
+    config_state: State<'_, ConfigState>,
+    rules: Vec<RoutingRule>,
+) -> Result<(), String> {
+    let count = rules.len();
+    update_app_config(config_state, |config| {
+        config.routing_rules = rules;
+        Ok(())
+    })
+    .await?;
+    info!("Routing rules updated: {} rule(s)", count);
+    Ok(())
+}
+
+/// 通知先のインスタンスの一覧を設定ファイルから取得します。
 ///
 /// # 戻り値
-/// * `Ok(())` - 設定ファイルの更新に成功した場合。
-/// * `Err(String)` - 設定ファイルの読み込み、JSON 解析、または書き込みに失敗した場合。
-///
-/// # 注意
-/// Channel Access Token は秘密情報のため、更新ログでは値をマスクします。
-pub async fn update_line_channel_access_token(
+/// * `Ok(Vec<NotifierInstance>)` - インスタンスの一覧。`NOTIFIER_INSTANCES` が存在しない場合は空。
+/// * `Err(String)` - 設定ファイルの読み込み、解析、または検証に失敗した場合。
+pub async fn get_notifier_instances(
     config_state: State<'_, ConfigState>,
-    token: String,
-) -> Result<(), String> {
-    update_config_value(
-        config_state,
-        "LINE_CHANNEL_ACCESS_TOKEN",
-        &token,
-        "LINE channel access token updated",
-    )
-    .await
+) -> Result<Vec<NotifierInstance>, String> {
+    Ok(get_app_config(config_state).await?.notifier_instances)
+}
+
+/// 通知先のインスタンスの一覧を、秘密情報を除いて設定ファイルから取得します。
+pub async fn list_notifier_instances(
+    config_state: State<'_, ConfigState>,
+) -> Result<Vec<NotifierInstanceSummary>, String> {
+    let instances = get_notifier_instances(config_state).await?;
+    Ok(instances
+        .into_iter()
+        .map(|mut instance| {
+            let configured_secrets = redact_secrets(&mut instance.settings);
+            NotifierInstanceSummary {
+                instance,
+                configured_secrets,
+            }
+        })
+        .collect())
 }
 
-/// LINE の送信先 ID を設定ファイルへ保存します。
+/// 通知先のインスタンスを設定ファイルへ追加します。
 ///
 /// # 概要
-/// `appsettings.json` の `LINE_TARGET` を更新します。
-/// 送信先 ID が空の場合、LINE 通知は送信されません。
-///
-/// # 引数
-/// * `config_state` - Tauri state に保持されている設定ファイルパス。
-/// * `target` - 保存する LINE のユーザー ID、グループ ID、またはルーム ID。
+/// `appsettings.json` の `NOTIFIER_INSTANCES` の末尾に追加します。インスタンスの検証は呼び出し元で行います。
+/// 値が `null` の秘密情報の設定キーは取り除きます。
 ///
 /// # 戻り値
 /// * `Ok(())` - 設定ファイルの更新に成功した場合。
-/// * `Err(String)` - 設定ファイルの読み込み、JSON 解析、または書き込みに失敗した場合。
-pub async fn update_line_target(
+/// * `Err(String)` - 同じ名前のインスタンスが存在する場合、または設定ファイルの読み込みや書き込みに失敗した場合。
+pub async fn add_notifier_instance(
     config_state: State<'_, ConfigState>,
-    target: String,
+    mut instance: NotifierInstance,
 ) -> Result<(), String> {
-    update_config_value(config_state, "LINE_TARGET", &target, "LINE target updated").await
+    let mut instances = get_notifier_instances(config_state.clone()).await?;
+    keep_saved_secrets(&mut instance.settings, &Map::new());
+    if instances.iter().any(|existing| existing.id == instance.id) {
+        return Err(format!(
+            "通知先の名前「{}」は既に使用されています。",
+            instance.id
+        ));
+    }
+    let id = instance.id.clone();
+    instances.push(instance);
+    save_notifier_instances(config_state, instances).await?;
+    info!("Notifier instance added: {}", id);
+    Ok(())
 }
 
-/// Discord Webhook URL を設定ファイルから取得します。
+/// 通知先のインスタンスを更新します。
 ///
-/// # 戻り値
-/// * `Ok(String)` - `DISCORD_WEBHOOK_URL` の値。
-/// * `Err(String)` - 設定ファイルの読み込み、JSON 解析、またはキー取得に失敗した場合。
-pub async fn get_webhook_url(config_state: State<'_, ConfigState>) -> Result<String, String> {
-    get_config_value(config_state, "DISCORD_WEBHOOK_URL").await
-}
-
-/// 画像差分のしきい値を設定ファイルから取得します。
+/// # 概要
+/// `NOTIFIER_INSTANCES` の同じ名前のインスタンスを置き換えます。
+/// 秘密情報の設定キーの値が `null` の場合、またはキーがない場合は保存済みの値を変更しません。
+/// 空文字を指定した秘密情報は削除します。種類を変更した場合は保存済みの秘密情報を引き継ぎません。
 ///
 /// # 戻り値
-/// * `Ok(String)` - `THRESHOLD` の値。
-/// * `Err(String)` - 設定ファイルの読み込み、JSON 解析、またはキー取得に失敗した場合。
-pub async fn get_threshold(config_state: State<'_, ConfigState>) -> Result<String, String> {
-    get_config_value(config_state, "THRESHOLD").await
+/// * `Ok(())` - 設定ファイルの更新に成功した場合。
+/// * `Err(String)` - インスタンスが存在しない場合、または設定ファイルの読み込みや書き込みに失敗した場合。
+pub async fn update_notifier_instance(
+    config_state: State<'_, ConfigState>,
+    mut instance: NotifierInstance,
+) -> Result<(), String> {
+    let mut instances = get_notifier_instances(config_state.clone()).await?;
+    let existing = instances
+        .iter_mut()
+        .find(|existing| existing.id == instance.id)
+        .ok_or_else(|| format!("通知先「{}」が見つかりません。", instance.id))?;
+    let empty = Map::new();
+    let saved = if existing.kind == instance.kind {
+        &existing.settings
+    } else {
+        &empty
+    };
+    keep_saved_secrets(&mut instance.settings, saved);
+    let id = instance.id.clone();
+    *existing = instance;
+    save_notifier_instances(config_state, instances).await?;
+    info!("Notifier instance updated: {}", id);
+    Ok(())
 }
 
-/// 監視間隔を設定ファイルから取得します。
+/// 通知先のインスタンスを設定ファイルから削除します。
 ///
 /// # 戻り値
-/// * `Ok(String)` - `INTERVAL` の値。
-/// * `Err(String)` - 設定ファイルの読み込み、JSON 解析、またはキー取得に失敗した場合。
-pub async fn get_interval(config_state: State<'_, ConfigState>) -> Result<String, String> {
-    get_config_value(config_state, "INTERVAL").await
+/// * `Ok(())` - 設定ファイルの更新に成功した場合。
+/// * `Err(String)` - インスタンスが存在しない場合、または設定ファイルの読み込みや書き込みに失敗した場合。
+pub async fn remove_notifier_instance(
+    config_state: State<'_, ConfigState>,
+    id: &str,
+) -> Result<(), String> {
+    let mut instances = get_notifier_instances(config_state.clone()).await?;
+    let count = instances.len();
+    instances.retain(|instance| instance.id != id);
+    if instances.len() == count {
+        return Err(format!("通知先「{}」が見つかりません。", id));
+    }
+    save_notifier_instances(config_state, instances).await?;
+    info!("Notifier instance removed: {}", id);
+    Ok(())
 }
 
-/// 監視開始時に対象ウィンドウを最小化するかどうかを設定ファイルから取得します。
-///
-/// 既存ユーザーの設定ファイルにキーが存在しない場合は、従来挙動を保つため `"true"` を返します。
-pub async fn get_minimize_on_monitor_start(
+async fn save_notifier_instances(
     config_state: State<'_, ConfigState>,
-) -> Result<bool, String> {
-    let value =
-        get_config_value_or_default(config_state, "MINIMIZE_ON_MONITOR_START", "true").await?;
-    parse_bool_config_value("MINIMIZE_ON_MONITOR_START", &value)
+    instances: Vec<NotifierInstance>,
+) -> Result<(), String> {
+    update_app_config(config_state, |config| {
+        config.notifier_instances = instances;
+        Ok(())
+    })
+    .await
 }
 
-/// LINE 通知の有効状態を設定ファイルから取得します。
+/// 設定ファイルを読み込み、型付きの設定として返します。
 ///
 /// # 概要
-/// 既存ユーザーの設定ファイルに `LINE_ENABLED` が存在しない場合は、後方互換のため `"false"` を返します。
-/// 設定ファイルの読み込みや JSON 解析に失敗した場合は、デフォルト値ではなくエラーを返します。
+/// 設定値は読み込み時に型へ変換します。キーが存在しない項目や不正な値は既定値を使用します（`AppConfig::from_value` を参照）。
+/// 設定ファイルが読み込めない場合は、親ディレクトリが存在する限り初期設定ファイルを作成して再試行します。
 ///
 /// # 戻り値
-/// * `Ok(String)` - `"true"` または `"false"`。
-/// * `Err(String)` - 設定ファイルの読み込み、または JSON 解析に失敗した場合。
-pub async fn get_line_enabled(config_state: State<'_, ConfigState>) -> Result<String, String> {
-    get_config_value_or_default(config_state, "LINE_ENABLED", "false").await
+/// * `Ok(AppConfig)` - 設定。
+/// * `Err(String)` - 読み込み、または JSON 解析に失敗した場合。
+pub async fn get_app_config(config_state: State<'_, ConfigState>) -> Result<AppConfig, String> {
+    AppConfig::from_value(read_or_initialize_config_file(&config_state)?)
 }
 
-/// LINE Channel Access Token が設定済みかどうかを取得します。
+/// 型付きの設定を更新して設定ファイルへ保存します。
 ///
 /// # 概要
-/// token 自体はフロントエンドへ返さず、設定済みかどうかだけを返します。
-/// 既存ユーザーの設定ファイルに `LINE_CHANNEL_ACCESS_TOKEN` が存在しない場合は未設定扱いにします。
+/// 設定ファイルを読み込んで `update` で変更し、検証してから保存します。
+/// 検証に失敗した場合は設定ファイルを変更しません。設定ファイルの読み込みは `get_app_config` と同じです。
 ///
 /// # 戻り値
-/// * `Ok(true)` - 空白以外の token が保存されている場合。
-/// * `Ok(false)` - token が空、またはキーが存在しない場合。
-/// * `Err(String)` - 設定ファイルの読み込み、または JSON 解析に失敗した場合。
-pub async fn get_line_channel_access_token_configured(
+/// * `Ok(())` - 設定ファイルの更新に成功した場合。
+/// * `Err(String)` - 読み込み、`update`、検証、または書き込みに失敗した場合。
+pub async fn update_app_config<F>(
     config_state: State<'_, ConfigState>,
-) -> Result<bool, String> {
-    let token = get_config_value_or_default(config_state, "LINE_CHANNEL_ACCESS_TOKEN", "").await?;
-    Ok(!token.trim().is_empty())
+    update: F,
+) -> Result<(), String>
+where
+    F: FnOnce(&mut AppConfig) -> Result<(), String>,
+{
+    let mut config = AppConfig::from_value(read_or_initialize_config_file(&config_state)?)?;
+    update(&mut config)?;
+    config.save(&config_state.path)
 }
 
-/// LINE の送信先 ID を設定ファイルから取得します。
-///
-/// # 概要
-/// 既存ユーザーの設定ファイルに `LINE_TARGET` が存在しない場合は、後方互換のため空文字を返します。
+/// すべての設定を、秘密情報を除いて設定ファイルから取得します。`get_app_config` コマンドで使用します。
 ///
-/// # 戻り値
-/// * `Ok(String)` - `LINE_TARGET` の値。未設定の場合は空文字。
-/// * `Err(String)` - 設定ファイルの読み込み、または JSON 解析に失敗した場合。
-pub async fn get_line_target(config_state: State<'_, ConfigState>) -> Result<String, String> {
-    get_config_value_or_default(config_state, "LINE_TARGET", "").await
+/// Webhook URL やトークンなど `SECRET_CONFIG_KEYS` の値と `SECRET_MAP_CONFIG_KEYS` の項目の値は、
+/// 通知先のインスタンスの設定も含めて、設定済みの場合は `null`、未設定の場合は空文字に置き換えます。
+pub async fn get_app_config_without_secrets(
+    config_state: State<'_, ConfigState>,
+) -> Result<Value, String> {
+    let config = get_app_config(config_state).await?;
+    let mut entries = config_entries(&config)?;
+    redact_secrets(&mut entries);
+    if let Some(Value::Array(instances)) = entries.get_mut(NOTIFIER_INSTANCES_KEY) {
+        for settings in instances
+            .iter_mut()
+            .filter_map(|instance| instance.get_mut("settings"))
+            .filter_map(Value::as_object_mut)
+        {
+            redact_secrets(settings);
+        }
+    }
+    Ok(Value::Object(entries))
 }
 
-/// 指定されたキーの設定値を取得します。
+/// すべての設定を `config` で置き換えて設定ファイルへ保存します。`update_app_config` コマンドで使用します。
 ///
 /// # 概要
-/// 設定ファイルを読み込み、指定キーの文字列値を返します。
-/// 設定ファイルが読み込めない場合は、親ディレクトリが存在する限り初期設定ファイルを作成して再試行します。
-///
-/// # 引数
-/// * `config_state` - Tauri state に保持されている設定ファイルパス。
-/// * `key` - 取得対象の設定キー。
+/// `get_app_config_without_secrets` で取得した設定をそのまま保存できるよう、秘密情報の値が `null` の場合、
+/// またはキーがない場合は保存済みの値を変更しません。空文字を指定した秘密情報は削除します。
+/// 通知先のインスタンスは、名前と種類が同じ保存済みのインスタンスの秘密情報を引き継ぎます。
 ///
 /// # 戻り値
-/// * `Ok(String)` - 指定キーに対応する文字列値。
-/// * `Err(String)` - 読み込み、JSON 解析、またはキー取得に失敗した場合。
-///
-/// # 注意
-/// キーが存在しない場合は `CONFIG_KEY_NOT_FOUND:<key>` 形式のエラーを返します。
-/// これは、後方互換用のデフォルト値を返す処理と、設定ファイル破損などの実エラーを区別するためです。
-async fn get_config_value(
+/// * `Ok(())` - 設定ファイルの更新に成功した場合。
+/// * `Err(String)` - 設定値の解析や検証、または設定ファイルの読み込みや書き込みに失敗した場合。
+pub async fn replace_app_config(
     config_state: State<'_, ConfigState>,
-    key: &str,
-) -> Result<String, String> {
-    loop {
-        let config_path = config_state.path.to_string_lossy().to_string();
-        let json_value = match read_config_file(&config_path) {
-            Ok(value) => value,
-            Err(e) => {
-                error!("Failed to read config file: {:?}", e);
-                if let Some(parent_dir) = config_state.path.parent() {
-                    let config_file =
-                        get_or_create_config_file_path(parent_dir, "appsettings.json");
-                    initilize_config_file(&config_file);
-                    continue;
+    config: Value,
+) -> Result<(), String> {
+    let Value::Object(mut entries) = config else {
+        return Err("parse error: 設定はJSONオブジェクトで指定してください。".to_string());
+    };
+    update_app_config(config_state, |saved| {
+        keep_saved_secrets(&mut entries, &config_entries(saved)?);
+        if let Some(Value::Array(instances)) = entries.get_mut(NOTIFIER_INSTANCES_KEY) {
+            for instance in instances.iter_mut().filter_map(Value::as_object_mut) {
+                let existing = saved.notifier_instances.iter().find(|existing| {
+                    instance.get("id").and_then(Value::as_str) == Some(existing.id.as_str())
+                        && instance.get("kind").and_then(Value::as_str)
+                            == Some(existing.kind.as_str())
+                });
+                let empty = Map::new();
+                let existing = existing.map_or(&empty, |existing| &existing.settings);
+                if let Some(Value::Object(settings)) = instance.get_mut("settings") {
+                    keep_saved_secrets(settings, existing);
                 }
-
-                warn!("Config parent directory was not found.");
-                return Err(e);
             }
-        };
-
-        if let Some(value_str) = json_value[key].as_str() {
-            return Ok(value_str.to_string());
         }
-
-        return Err(format!("{}{}", KEY_NOT_FOUND_PREFIX, key));
-    }
+        let updated = serde_json::from_value(Value::Object(entries)).map_err(|e| {
+            error!("Failed to parse config: {:?}", e);
+            format!("parse error: {}", e)
+        })?;
+        saved.replace(updated);
+        Ok(())
+    })
+    .await?;
+    info!("App config updated");
+    Ok(())
 }
 
-/// 指定キーの設定値を取得し、キー欠落時だけデフォルト値を返します。
-///
-/// # 概要
-/// 既存ユーザーの古い設定ファイルに新しい任意設定キーが存在しない場合の後方互換用ヘルパーです。
-/// 設定ファイルの読み込み失敗や JSON 解析失敗はデフォルト値で握りつぶさず、エラーとして返します。
-///
-/// # 引数
-/// * `config_state` - Tauri state に保持されている設定ファイルパス。
-/// * `key` - 取得対象の設定キー。
-/// * `default_value` - キーが存在しない場合だけ返すデフォルト値。
-///
-/// # 戻り値
-/// * `Ok(String)` - 設定値、またはキー欠落時のデフォルト値。
-/// * `Err(String)` - 設定ファイルの読み込み、または JSON 解析に失敗した場合。
-async fn get_config_value_or_default(
-    config_state: State<'_, ConfigState>,
-    key: &str,
-    default_value: &str,
-) -> Result<String, String> {
-    match get_config_value(config_state, key).await {
-        Ok(value) => Ok(value),
-        Err(e) if e.starts_with(KEY_NOT_FOUND_PREFIX) => Ok(default_value.to_string()),
-        Err(e) => Err(e),
+/// 設定を設定ファイルと同じキーと値の JSON オブジェクトへ変換します。
+fn config_entries(config: &AppConfig) -> Result<Map<String, Value>, String> {
+    match config.to_value()? {
+        Value::Object(entries) => Ok(entries),
+        _ => Err("serialize error: 設定をJSONオブジェクトへ変換できません。".to_string()),
     }
 }
 
-/// 指定されたキーの設定値を更新します。
+/// 秘密情報の設定キーの値を置き換え、値が設定されていた設定キーを返します。
 ///
-/// # 概要
-/// 設定ファイルを JSON として読み込み、指定キーを文字列値で更新して保存します。
-/// 存在しないキーも追加されます。
-///
-/// # 引数
-/// * `config_state` - Tauri state に保持されている設定ファイルパス。
-/// * `key` - 更新対象の設定キー。
-/// * `value` - 保存する文字列値。
-/// * `success_log` - 更新成功時に出力するログメッセージ。
-///
-/// # 戻り値
-/// * `Ok(())` - 設定ファイルの更新に成功した場合。
-/// * `Err(String)` - 設定ファイルの読み込み、JSON 解析、または書き込みに失敗した場合。
-///
-/// # 注意
-/// `DISCORD_WEBHOOK_URL` と `LINE_CHANNEL_ACCESS_TOKEN` はログ出力時に値をマスクします。
-async fn update_config_value(
-    config_state: State<'_, ConfigState>,
-    key: &str,
-    value: &str,
-    success_log: &str,
-) -> Result<(), String> {
-    let config_path = config_state.path.to_string_lossy().to_string();
-    let mut json_value = match read_config_file(&config_path) {
-        Ok(value) => value,
-        Err(e) => {
-            error!("Failed to read config file: {:?}", e);
-            return Err(e);
+/// 設定済みの値は `null`、未設定の値は空文字に置き換えます。
+/// `SECRET_MAP_CONFIG_KEYS` の設定は項目名を残し、項目の値を `null` に置き換えます。
+fn redact_secrets(settings: &mut Map<String, Value>) -> Vec<String> {
+    let mut configured_secrets = Vec::new();
+    for (key, value) in settings.iter_mut() {
+        if SECRET_CONFIG_KEYS.contains(&key.as_str()) {
+            if is_blank(Some(value)) {
+                *value = Value::from("");
+            } else {
+                configured_secrets.push(key.clone());
+                *value = Value::Null;
+            }
+        } else if SECRET_MAP_CONFIG_KEYS.contains(&key.as_str()) {
+            if let Some(values) = value.as_object_mut() {
+                if !values.is_empty() {
+                    configured_secrets.push(key.clone());
+                }
+                for value in values.values_mut() {
+                    *value = Value::Null;
+                }
+            }
         }
-    };
-
-    if let Some(obj) = json_value.as_object_mut() {
-        obj.insert(key.to_string(), Value::String(value.to_string()));
     }
+    configured_secrets
+}
 
-    match write_config_file(&config_path, &json_value) {
-        Ok(_) => {
-            let log_value = if key == "LINE_CHANNEL_ACCESS_TOKEN" || key == "DISCORD_WEBHOOK_URL" {
-                "<redacted>"
-            } else {
-                value
+/// 秘密情報の設定キーの値が `null` の場合、またはキーがない場合は、`saved` の値を引き継ぎます。
+///
+/// 空文字を指定した値はそのまま保存するため、秘密情報を削除できます。
+/// `SECRET_MAP_CONFIG_KEYS` の設定は、値が `null` の項目ごとに `saved` の同じ名前の項目の値を引き継ぎ、
+/// `saved` にない項目は取り除きます。
+fn keep_saved_secrets(settings: &mut Map<String, Value>, saved: &Map<String, Value>) {
+    for key in SECRET_CONFIG_KEYS.iter().chain(SECRET_MAP_CONFIG_KEYS) {
+        if settings.get(*key).map_or(true, Value::is_null) {
+            match saved.get(*key) {
+                Some(saved) => settings.insert(key.to_string(), saved.clone()),
+                None => settings.remove(*key),
             };
-            info!("{}: {}", success_log, log_value);
-            Ok(())
         }
-        Err(e) => {
-            error!("Failed to write config file: {:?}", e);
-            Err(e)
+    }
+    for key in SECRET_MAP_CONFIG_KEYS {
+        if let Some(Value::Object(values)) = settings.get_mut(*key) {
+            let saved = saved.get(*key).and_then(Value::as_object);
+            values.retain(|name, value| {
+                if !value.is_null() {
+                    return true;
+                }
+                match saved.and_then(|saved| saved.get(name)) {
+                    Some(saved) => {
+                        *value = saved.clone();
+                        true
+                    }
+                    None => false,
+                }
+            });
         }
     }
 }
 
+/// 値が存在しない、または空白だけの文字列かどうか。
+fn is_blank(value: Option<&Value>) -> bool {
+    value
+        .and_then(Value::as_str)
+        .map_or("", str::trim)
+        .is_empty()
+}
+
+/// 設定ファイルを読み込み、JSON として解析します。
+///
+/// 読み込めない場合は、親ディレクトリが存在する限り初期設定ファイルを作成して再試行します。
+fn read_or_initialize_config_file(config_state: &ConfigState) -> Result<Value, String> {
+    let config_path = config_state.path.to_string_lossy().to_string();
+    match read_config_file(&config_path) {
+        Ok(value) => Ok(value),
+        Err(e) => match config_state.path.parent() {
+            Some(parent_dir) => {
+                let config_file = get_or_create_config_file_path(parent_dir, "appsettings.json");
+                initilize_config_file(&config_file);
+                read_config_file(&config_path)
+            }
+            None => {
+                warn!("Config parent directory was not found.");
+                Err(e)
+            }
+        },
+    }
+}
+
 /// 設定ファイルを読み込み、JSON として解析します。
 ///
 /// # 引数
@@ -393,52 +571,105 @@ fn read_config_file(config_path: &str) -> Result<Value, String> {
     })
 }
 
-/// JSON 値を設定ファイルへ整形して書き込みます。
-///
-/// # 引数
-/// * `config_path` - 書き込み先の設定ファイルパス。
-/// * `json_value` - 保存する JSON 値。
-///
-/// # 戻り値
-/// * `Ok(())` - 書き込みに成功した場合。
-/// * `Err(String)` - JSON の整形、またはファイル書き込みに失敗した場合。
-fn write_config_file(config_path: &str, json_value: &Value) -> Result<(), String> {
-    let updated_content = serde_json::to_string_pretty(json_value).map_err(|e| {
-        error!("Failed to serialize config file: {:?}", e);
-        format!("serialize error: {:?}", e)
-    })?;
-
-    fs::write(config_path, updated_content).map_err(|e| {
-        error!("Failed to write config file: {:?}", e);
-        format!("write error: {:?}", e)
-    })
-}
-
-fn parse_bool_config_value(key: &str, value: &str) -> Result<bool, String> {
-    match value {
-        "true" => Ok(true),
-        "false" => Ok(false),
-        _ => Err(format!(
-            "{} must be either \"true\" or \"false\", but got {:?}",
-            key, value
-        )),
-    }
-}
-
 #[cfg(test)]
 mod tests {
-    use super::parse_bool_config_value;
+    use super::{keep_saved_secrets, redact_secrets};
+    use serde_json::{json, Map, Value};
 
     #[test]
-    fn parse_bool_config_value_accepts_true_and_false() {
-        assert_eq!(parse_bool_config_value("FLAG", "true"), Ok(true));
-        assert_eq!(parse_bool_config_value("FLAG", "false"), Ok(false));
+    fn secrets_are_redacted_and_null_secrets_keep_saved_values() {
+        let saved = json!({
+            "SLACK_WEBHOOK_URL": "https://hooks.slack.com/services/secret",
+            "TELEGRAM_BOT_TOKEN": "123:secret",
+            "TELEGRAM_CHAT_ID": "-100",
+            "PUSHOVER_APP_TOKEN": ""
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+        let mut redacted = saved.clone();
+
+        let configured = redact_secrets(&mut redacted);
+
+        assert_eq!(configured, vec!["SLACK_WEBHOOK_URL", "TELEGRAM_BOT_TOKEN"]);
+        assert_eq!(redacted["SLACK_WEBHOOK_URL"], Value::Null);
+        assert_eq!(redacted["PUSHOVER_APP_TOKEN"], "");
+        assert_eq!(redacted["TELEGRAM_CHAT_ID"], "-100");
+
+        redacted.insert("TELEGRAM_BOT_TOKEN".to_string(), Value::from("456:new"));
+        redacted.remove("PUSHOVER_APP_TOKEN");
+        keep_saved_secrets(&mut redacted, &saved);
+
+        assert_eq!(redacted["SLACK_WEBHOOK_URL"], saved["SLACK_WEBHOOK_URL"]);
+        assert_eq!(redacted["TELEGRAM_BOT_TOKEN"], "456:new");
+        assert_eq!(redacted["PUSHOVER_APP_TOKEN"], "");
     }
 
     #[test]
-    fn parse_bool_config_value_rejects_non_canonical_values() {
-        assert!(parse_bool_config_value("FLAG", "TRUE").is_err());
-        assert!(parse_bool_config_value("FLAG", "").is_err());
-        assert!(parse_bool_config_value("FLAG", "foo").is_err());
+    fn empty_secrets_clear_saved_values() {
+        let saved = json!({
+            "SLACK_WEBHOOK_URL": "https://hooks.slack.com/services/secret",
+            "HTTP_WEBHOOK_HEADERS": { "Authorization": "Bearer secret" }
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+        let mut settings = json!({
+            "SLACK_WEBHOOK_URL": "",
+            "HTTP_WEBHOOK_HEADERS": { "Authorization": "" }
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+
+        keep_saved_secrets(&mut settings, &saved);
+
+        assert_eq!(settings["SLACK_WEBHOOK_URL"], "");
+        assert_eq!(
+            settings["HTTP_WEBHOOK_HEADERS"],
+            json!({ "Authorization": "" })
+        );
+
+        let mut settings = json!({ "SLACK_WEBHOOK_URL": null })
+            .as_object()
+            .unwrap()
+            .clone();
+        keep_saved_secrets(&mut settings, &Map::new());
+
+        assert!(!settings.contains_key("SLACK_WEBHOOK_URL"));
+    }
+
+    #[test]
+    fn header_values_are_redacted_and_null_values_keep_saved_headers() {
+        let saved = json!({
+            "NTFY_TOPIC_URL": "https://ntfy.sh/secret-topic",
+            "HTTP_WEBHOOK_HEADERS": {
+                "Authorization": "Bearer secret",
+                "X-Api-Key": "key"
+            }
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+        let mut redacted = saved.clone();
+
+        let configured = redact_secrets(&mut redacted);
+
+        assert_eq!(configured, vec!["HTTP_WEBHOOK_HEADERS", "NTFY_TOPIC_URL"]);
+        assert_eq!(redacted["NTFY_TOPIC_URL"], Value::Null);
+        assert_eq!(
+            redacted["HTTP_WEBHOOK_HEADERS"],
+            json!({ "Authorization": null, "X-Api-Key": null })
+        );
+
+        redacted["HTTP_WEBHOOK_HEADERS"] =
+            json!({ "Authorization": null, "X-Trace": "on", "X-Unknown": null });
+        keep_saved_secrets(&mut redacted, &saved);
+
+        assert_eq!(redacted["NTFY_TOPIC_URL"], saved["NTFY_TOPIC_URL"]);
+        assert_eq!(
+            redacted["HTTP_WEBHOOK_HEADERS"],
+            json!({ "Authorization": "Bearer secret", "X-Trace": "on" })
+        );
     }
 }
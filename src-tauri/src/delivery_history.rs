@@ -0,0 +1,428 @@
+use chrono::{DateTime, Duration, Local};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+use tauri::async_runtime::Mutex;
+
+use crate::notifier::{DeliveryResult, NotificationEvent, NotificationTrigger};
+
+/// 配信履歴を保存するファイル名。アプリのデータフォルダに JSON Lines 形式で作成されます。
+pub const DELIVERY_HISTORY_FILE_NAME: &str = "delivery_history.jsonl";
+
+/// 配信履歴を保存する日数。これより前に記録した履歴は削除します。
+const HISTORY_RETENTION_DAYS: i64 = 30;
+
+/// 配信履歴のファイルサイズの上限（バイト）。超えた場合は古い履歴から削除し、上限の半分まで縮めます。
+const MAX_HISTORY_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// 配信履歴に記録した送信の種類。
+///
+/// - `Detection`: 変化を検知した際の通知。
+/// - `Escalation`: 確認されないまま時間が経過した際のエスカレーションの通知。
+/// - `Queued`: 送信待ちキューから遅れて送信した通知。
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryKind {
+    Detection,
+    Escalation,
+    Queued,
+}
+
+/// 配信履歴の 1 件。
+///
+/// # フィールド
+/// - `kind`: 送信の種類。
+/// - `recorded_at`: 履歴へ記録した時刻。
+/// - `app_name`: 監視対象のウィンドウタイトル。
+/// - `executable`: 監視対象の実行ファイルのフルパス。
+/// - `trigger`: 通知のきっかけとなった事象の種類。
+/// - `detected_at`: 変化を検知した時刻。
+/// - `diff_score`: 初期画像との差分値（`0.0〜1.0`）。
+/// - `threshold`: 検知時の画像差分しきい値。送信待ちキューから送信した通知では `None`。
+/// - `escalated_after_minutes`: エスカレーションの通知の場合、検知からの経過時間（分）。
+/// - `deliveries`: 通知先ごとの送信結果と、送信を試みた回数（再送を含む）。
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub kind: HistoryKind,
+    pub recorded_at: DateTime<Local>,
+    pub app_name: String,
+    #[serde(default)]
+    pub executable: Option<String>,
+    pub trigger: NotificationTrigger,
+    pub detected_at: DateTime<Local>,
+    pub diff_score: f32,
+    #[serde(default)]
+    pub threshold: Option<f32>,
+    #[serde(default)]
+    pub escalated_after_minutes: Option<u64>,
+    #[serde(default)]
+    pub deliveries: Vec<DeliveryResult>,
+}
+
+impl HistoryEntry {
+    pub fn new(
+        kind: HistoryKind,
+        event: &NotificationEvent,
+        threshold: Option<f32>,
+        deliveries: Vec<DeliveryResult>,
+    ) -> Self {
+        Self {
+            kind,
+            recorded_at: Local::now(),
+            app_name: event.app.name.clone(),
+            executable: event.app.executable.clone(),
+            trigger: event.trigger,
+            detected_at: event.detected_at,
+            diff_score: event.diff_score,
+            threshold,
+            escalated_after_minutes: event.escalated_after_minutes,
+            deliveries,
+        }
+    }
+
+    /// 監視対象のウィンドウタイトル、または実行ファイルのパスに `target` が含まれるかどうかを、大文字と小文字を区別せずに返します。
+    fn matches_target(&self, target: &str) -> bool {
+        let target = target.to_lowercase();
+        self.app_name.to_lowercase().contains(&target)
+            || self
+                .executable
+                .as_deref()
+                .is_some_and(|executable| executable.to_lowercase().contains(&target))
+    }
+}
+
+/// 配信履歴の検索条件。`get_delivery_history` コマンドの引数として使用します。
+///
+/// # フィールド
+/// - `from`: この時刻以降に記録した履歴に絞り込みます。
+/// - `to`: この時刻以前に記録した履歴に絞り込みます。
+/// - `target`: ウィンドウタイトルまたは実行ファイルのパスに含まれる文字列。大文字と小文字は区別しません。
+/// - `limit`: 返す件数の上限。新しい順に数えます。
+///
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct HistoryFilter {
+    #[serde(default)]
+    pub from: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub to: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub target: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl HistoryFilter {
+    fn matches(&self, entry: &HistoryEntry) -> bool {
+        self.from.map_or(true, |from| entry.recorded_at >= from)
+            && self.to.map_or(true, |to| entry.recorded_at <= to)
+            && self
+                .target
+                .as_deref()
+                .map(str::trim)
+                .filter(|target| !target.is_empty())
+                .map_or(true, |target| entry.matches_target(target))
+    }
+}
+
+/// 検知と通知の送信結果を記録する配信履歴。
+///
+/// # 概要
+/// 通知が届かなかった原因を後から確認できるよう、検知ごとの通知先別の送信結果を
+/// JSON Lines 形式（1 行に 1 件）でファイルへ追記します。
+///
+/// ファイルが際限なく大きくならないよう、起動時とファイルサイズが上限（`MAX_HISTORY_FILE_BYTES`）を
+/// 超えた時に、保存期間（`HISTORY_RETENTION_DAYS`）を過ぎた履歴と古い履歴を削除します。
+///
+/// # フィールド
+/// - `path`: 履歴を保存するファイルのパス。`None` の場合は記録しません。
+/// - `lock`: 複数の監視タスクからの追記と削除が重ならないようにするためのロック。
+///
+pub struct DeliveryHistory {
+    path: Option<PathBuf>,
+    lock: Mutex<()>,
+}
+
+impl DeliveryHistory {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        if let Some(path) = &path {
+            compact(
+                path,
+                Duration::days(HISTORY_RETENTION_DAYS),
+                MAX_HISTORY_FILE_BYTES,
+            );
+        }
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// 履歴を 1 件追記します。書き込みに失敗した場合はエラーを出力し、監視は継続します。
+    pub async fn record(&self, entry: HistoryEntry) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+        let line = match serde_json::to_string(&entry) {
+            Ok(line) => line,
+            Err(e) => {
+                error!("配信履歴のシリアライズに失敗しました: {:?}", e);
+                return;
+            }
+        };
+        let _guard = self.lock.lock().await;
+        let result = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{}", line));
+        if let Err(e) = result {
+            error!("配信履歴の保存に失敗しました: {:?}", e);
+            return;
+        }
+        let oversized =
+            fs::metadata(path).is_ok_and(|metadata| metadata.len() > MAX_HISTORY_FILE_BYTES);
+        if oversized {
+            compact(
+                path,
+                Duration::days(HISTORY_RETENTION_DAYS),
+                MAX_HISTORY_FILE_BYTES,
+            );
+        }
+    }
+
+    /// 検索条件に一致する履歴を新しい順に返します。
+    ///
+    /// 形式が不正な行は警告を出力して無視します。
+    ///
+    /// # 戻り値
+    /// * `Ok(Vec<HistoryEntry>)` - 一致した履歴。履歴のファイルが存在しない場合は空。
+    /// * `Err(String)` - 履歴のファイルの読み込みに失敗した場合。
+    pub async fn query(&self, filter: &HistoryFilter) -> Result<Vec<HistoryEntry>, String> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(Vec::new()),
+        };
+        let _guard = self.lock.lock().await;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(path).map_err(|e| {
+            error!("配信履歴の読み込みに失敗しました: {:?}", e);
+            format!("read error: {:?}", e)
+        })?;
+        let mut entries: Vec<HistoryEntry> = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str::<HistoryEntry>(line) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    warn!("配信履歴の形式が不正な行を無視します: {:?}", e);
+                    None
+                }
+            })
+            .filter(|entry| filter.matches(entry))
+            .collect();
+        entries.reverse();
+        if let Some(limit) = filter.limit {
+            entries.truncate(limit);
+        }
+        Ok(entries)
+    }
+
+    /// 配信履歴をすべて削除します。
+    ///
+    /// # 戻り値
+    /// * `Ok(())` - 削除した場合、または履歴のファイルが存在しない場合。
+    /// * `Err(String)` - 履歴のファイルの削除に失敗した場合。
+    pub async fn clear(&self) -> Result<(), String> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let _guard = self.lock.lock().await;
+        if !path.exists() {
+            return Ok(());
+        }
+        fs::remove_file(path).map_err(|e| {
+            error!("配信履歴の削除に失敗しました: {:?}", e);
+            format!("remove error: {:?}", e)
+        })?;
+        info!("配信履歴を削除しました。");
+        Ok(())
+    }
+}
+
+/// 配信履歴のファイルから、`retention` より前に記録した履歴と形式が不正な行を削除し、
+/// ファイルサイズが `max_bytes` の半分以下になるまで古い履歴から削除します。
+///
+/// 削除する履歴がない場合はファイルを書き換えません。失敗した場合はエラーを出力し、ファイルはそのまま残します。
+fn compact(path: &Path, retention: Duration, max_bytes: u64) {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return,
+    };
+    let cutoff = Local::now() - retention;
+    let mut lines: Vec<&str> = content
+        .lines()
+        .filter(|line| {
+            serde_json::from_str::<HistoryEntry>(line)
+                .is_ok_and(|entry| entry.recorded_at >= cutoff)
+        })
+        .collect();
+    let mut size: u64 = lines.iter().map(|line| line.len() as u64 + 1).sum();
+    let mut skip = 0;
+    if size > max_bytes {
+        while skip < lines.len() && size > max_bytes / 2 {
+            size -= lines[skip].len() as u64 + 1;
+            skip += 1;
+        }
+    }
+    lines.drain(..skip);
+    let removed = content.lines().count() - lines.len();
+    if removed == 0 {
+        return;
+    }
+
+    let temp_path = path.with_extension("jsonl.tmp");
+    let mut compacted = lines.join("\n");
+    if !compacted.is_empty() {
+        compacted.push('\n');
+    }
+    let result = fs::write(&temp_path, compacted).and_then(|_| fs::rename(&temp_path, path));
+    match result {
+        Ok(_) => info!("古い配信履歴を {} 件削除しました。", removed),
+        Err(e) => {
+            error!("古い配信履歴の削除に失敗しました: {:?}", e);
+            let _ = fs::remove_file(&temp_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compact, DeliveryHistory, HistoryEntry, HistoryFilter, HistoryKind};
+    use crate::notifier::{DeliveryOutcome, DeliveryResult, NotificationEvent};
+    use crate::test_support::sample_app;
+    use crate::window_utils::AppInfo;
+    use chrono::{Duration, Local};
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "flash-code-history-test-{}-{}.jsonl",
+            name,
+            std::process::id()
+        ))
+    }
+
+    fn entry(name: &str, executable: &str, outcome: DeliveryOutcome) -> HistoryEntry {
+        let app = AppInfo {
+            name: name.to_string(),
+            executable: Some(format!("C:\\Apps\\{}", executable)),
+            ..sample_app()
+        };
+        let event = NotificationEvent::icon_changed(app, 0.2);
+        let deliveries = vec![DeliveryResult {
+            notifier: "discord".to_string(),
+            delivered_at: Local::now(),
+            attempts: 3,
+            result: outcome,
+        }];
+        HistoryEntry::new(HistoryKind::Detection, &event, Some(0.05), deliveries)
+    }
+
+    #[tokio::test]
+    async fn records_are_returned_newest_first_with_results() {
+        let path = temp_path("query");
+        let _ = std::fs::remove_file(&path);
+        let history = DeliveryHistory::new(Some(path.clone()));
+        history
+            .record(entry("Chat", "chat.exe", DeliveryOutcome::Sent))
+            .await;
+        history
+            .record(entry(
+                "Mail",
+                "mail.exe",
+                DeliveryOutcome::failed(None, "offline"),
+            ))
+            .await;
+
+        let entries = history.query(&HistoryFilter::default()).await.unwrap();
+
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].app_name, "Mail");
+        assert_eq!(entries[0].deliveries[0].attempts, 3);
+        assert_eq!(
+            entries[0].deliveries[0].result,
+            DeliveryOutcome::failed(None, "offline")
+        );
+        assert_eq!(entries[1].threshold, Some(0.05));
+    }
+
+    #[tokio::test]
+    async fn query_filters_by_target_and_date_and_clear_removes_history() {
+        let path = temp_path("filter");
+        let _ = std::fs::remove_file(&path);
+        let history = DeliveryHistory::new(Some(path.clone()));
+        for (name, executable) in [("Chat", "chat.exe"), ("Inbox", "OUTLOOK.EXE")] {
+            history
+                .record(entry(name, executable, DeliveryOutcome::Sent))
+                .await;
+        }
+
+        let by_target = history
+            .query(&HistoryFilter {
+                target: Some("outlook".to_string()),
+                ..HistoryFilter::default()
+            })
+            .await
+            .unwrap();
+        let future = history
+            .query(&HistoryFilter {
+                from: Some(Local::now() + Duration::hours(1)),
+                ..HistoryFilter::default()
+            })
+            .await
+            .unwrap();
+        history.clear().await.unwrap();
+        let cleared = history.query(&HistoryFilter::default()).await.unwrap();
+
+        assert_eq!(by_target.len(), 1);
+        assert_eq!(by_target[0].app_name, "Inbox");
+        assert!(future.is_empty());
+        assert!(cleared.is_empty());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn compact_removes_expired_and_oldest_entries() {
+        let path = temp_path("compact");
+        let mut expired = entry("Old", "old.exe", DeliveryOutcome::Sent);
+        expired.recorded_at = Local::now() - Duration::days(31);
+        let lines: Vec<String> = std::iter::once(expired)
+            .chain((0..10).map(|i| entry(&format!("App{}", i), "app.exe", DeliveryOutcome::Sent)))
+            .map(|entry| serde_json::to_string(&entry).unwrap())
+            .chain(std::iter::once("not json".to_string()))
+            .collect();
+        std::fs::write(&path, lines.join("\n") + "\n").unwrap();
+        let max_bytes = (lines[1].len() as u64 + 1) * 8;
+
+        compact(&path, Duration::days(30), max_bytes);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let names: Vec<String> = content
+            .lines()
+            .map(|line| serde_json::from_str::<HistoryEntry>(line).unwrap().app_name)
+            .collect();
+        assert_eq!(names, ["App6", "App7", "App8", "App9"]);
+    }
+}
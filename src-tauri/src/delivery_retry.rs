@@ -0,0 +1,239 @@
+use log::warn;
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::app_config::AppConfig;
+use crate::notifier::{DeliveryOutcome, NotificationEvent, Notifier};
+
+/// 送信を試みる回数の既定値（初回を含む）。
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+/// 初回の再送までの待ち時間の既定値（ミリ秒）。
+pub const DEFAULT_INITIAL_DELAY_MS: u64 = 1000;
+/// 再送までの待ち時間の上限。送信先がこれより長い待ち時間を指定した場合は再送しません。
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// 通知の再送方針。
+///
+/// # 概要
+/// 通信エラーやサーバーエラーなどの一時的な失敗は、指数バックオフにジッターを加えた間隔で再送します。
+/// レート制限（HTTP 429）で送信先が待ち時間を指定した場合は、その待ち時間の後に再送します。
+/// 認証エラーや送信先が存在しないなどの恒久的な失敗は再送しません。
+///
+/// # 設定
+/// - `NOTIFY_RETRY_MAX_ATTEMPTS`: 送信を試みる回数（初回を含む）。`1` の場合は再送しません。既定値は `3`。
+/// - `NOTIFY_RETRY_INITIAL_DELAY_MS`: 初回の再送までの待ち時間（ミリ秒）。再送のたびに 2 倍になります。既定値は `1000`。
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_delay: Duration::from_millis(DEFAULT_INITIAL_DELAY_MS),
+            max_delay: MAX_DELAY,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 設定値から再送方針を構築します。不正な値は `AppConfig` の読み込み時に既定値へ置き換えられます。
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            max_attempts: config.notify_retry_max_attempts.max(1),
+            initial_delay: Duration::from_millis(config.notify_retry_initial_delay_ms),
+            ..Self::default()
+        }
+    }
+
+    /// `attempt` 回目（1 始まり）の送信に失敗した後、再送までの待ち時間を返します。
+    ///
+    /// 待ち時間は `initial_delay * 2^(attempt - 1)`（上限 `max_delay`）の半分から全体までの範囲でランダムに決まります。
+    pub fn backoff_delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let base = self
+            .initial_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        let half = base / 2;
+        half + half.mul_f64(fastrand::f64())
+    }
+
+    /// 送信結果に応じて再送までの待ち時間を返します。再送しない場合は `None` を返します。
+    fn delay_after(&self, attempt: u32, outcome: &DeliveryOutcome) -> Option<Duration> {
+        if attempt >= self.max_attempts || !outcome.is_retryable() {
+            return None;
+        }
+        match outcome {
+            DeliveryOutcome::RateLimited {
+                retry_after_ms: Some(retry_after_ms),
+                ..
+            } => Some(Duration::from_millis(*retry_after_ms))
+                .filter(|delay| *delay <= self.max_delay),
+            _ => Some(self.backoff_delay(attempt)),
+        }
+    }
+}
+
+/// 再送方針に従って検知イベントを送信し、最後の送信結果と送信を試みた回数を返します。
+///
+/// `Notifier::retries_when_failed` が `false` の通知先へは 1 回だけ送信します。
+pub async fn send_with_retry(
+    notifier: &dyn Notifier,
+    event: &NotificationEvent,
+    policy: &RetryPolicy,
+) -> (DeliveryOutcome, u32) {
+    let mut attempt = 1;
+    loop {
+        let outcome = notifier.send(event).await;
+        let delay = match policy
+            .delay_after(attempt, &outcome)
+            .filter(|_| notifier.retries_when_failed())
+        {
+            Some(delay) => delay,
+            None => return (outcome, attempt),
+        };
+        warn!(
+            "{}への通知の送信に失敗しました。{}ms 後に再送します。({}/{}回目): {:?}",
+            notifier.name(),
+            delay.as_millis(),
+            attempt,
+            policy.max_attempts,
+            outcome
+        );
+        sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{send_with_retry, RetryPolicy};
+    use crate::notifier::DeliveryOutcome;
+    use crate::test_support::{app_config, sample_event, ScriptedNotifier};
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(50),
+        }
+    }
+
+    #[test]
+    fn from_config_falls_back_to_defaults_for_invalid_values() {
+        let policy = RetryPolicy::from_config(&app_config(json!({
+            "NOTIFY_RETRY_MAX_ATTEMPTS": "0",
+            "NOTIFY_RETRY_INITIAL_DELAY_MS": "250"
+        })));
+
+        assert_eq!(policy.max_attempts, 3);
+        assert_eq!(policy.initial_delay, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn backoff_delay_grows_exponentially_with_jitter() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+        };
+
+        for _ in 0..20 {
+            let first = policy.backoff_delay(1);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let second = policy.backoff_delay(2);
+            assert!(second >= Duration::from_millis(100) && second <= Duration::from_millis(200));
+            let capped = policy.backoff_delay(10);
+            assert!(capped >= Duration::from_millis(150) && capped <= Duration::from_millis(300));
+        }
+    }
+
+    #[tokio::test]
+    async fn transient_failures_are_retried_until_sent() {
+        let notifier = ScriptedNotifier::new(
+            "scripted",
+            vec![
+                DeliveryOutcome::failed(None, "connection reset"),
+                DeliveryOutcome::failed(Some(502), "Bad Gateway"),
+                DeliveryOutcome::Sent,
+            ],
+        );
+
+        let (outcome, attempts) = send_with_retry(&notifier, &sample_event(), &policy()).await;
+
+        assert_eq!(outcome, DeliveryOutcome::Sent);
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn notifiers_that_opt_out_are_sent_only_once() {
+        let notifier = ScriptedNotifier {
+            retries: false,
+            ..ScriptedNotifier::new("scripted", vec![DeliveryOutcome::failed(None, "timed out")])
+        };
+
+        let (outcome, attempts) = send_with_retry(&notifier, &sample_event(), &policy()).await;
+
+        assert!(matches!(
+            outcome,
+            DeliveryOutcome::Failed {
+                permanent: false,
+                ..
+            }
+        ));
+        assert_eq!(attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn permanent_failures_are_not_retried() {
+        let notifier = ScriptedNotifier::new(
+            "scripted",
+            vec![DeliveryOutcome::failed(Some(404), "Unknown Webhook")],
+        );
+
+        let (outcome, attempts) = send_with_retry(&notifier, &sample_event(), &policy()).await;
+
+        assert!(matches!(
+            outcome,
+            DeliveryOutcome::Failed {
+                permanent: true,
+                ..
+            }
+        ));
+        assert_eq!(attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_waits_for_retry_after_within_max_delay() {
+        let notifier = ScriptedNotifier::new(
+            "scripted",
+            vec![
+                DeliveryOutcome::RateLimited {
+                    retry_after_ms: Some(10),
+                    message: "You are being rate limited.".to_string(),
+                },
+                DeliveryOutcome::Sent,
+            ],
+        );
+        let (outcome, attempts) = send_with_retry(&notifier, &sample_event(), &policy()).await;
+        assert_eq!(outcome, DeliveryOutcome::Sent);
+        assert_eq!(attempts, 2);
+
+        let notifier = ScriptedNotifier::new(
+            "scripted",
+            vec![DeliveryOutcome::RateLimited {
+                retry_after_ms: Some(60_000),
+                message: "You are being rate limited.".to_string(),
+            }],
+        );
+        let (outcome, attempts) = send_with_retry(&notifier, &sample_event(), &policy()).await;
+        assert!(matches!(outcome, DeliveryOutcome::RateLimited { .. }));
+        assert_eq!(attempts, 1);
+    }
+}
@@ -0,0 +1,190 @@
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine};
+use log::{error, info};
+use std::{env, fs, path::PathBuf};
+use tauri_winrt_notification::{IconCrop, Toast};
+use windows::Win32::{
+    Foundation::HWND,
+    UI::WindowsAndMessaging::{IsIconic, SetForegroundWindow},
+};
+
+use crate::message_template::MessageTemplate;
+use crate::notifier::{DeliveryOutcome, NotificationEvent, Notifier};
+use crate::window_utils::restore_window;
+
+/// トースト通知のアイコン画像を書き出すフォルダ名。一時フォルダ内に作成されます。
+const TOAST_ICON_DIR_NAME: &str = "flash-code-toast";
+
+/// Windows のトースト通知でローカルに通知する通知先。
+///
+/// # 概要
+/// 監視対象のアプリケーションのアイコンを表示したトースト通知を表示します。
+/// トースト通知をクリックすると、監視対象のウィンドウを復元して前面に表示します。
+///
+/// Tauri のノーティフィケーション API（`tauri::api::notification`）はクリック時の処理を指定できないため、
+/// Tauri が内部で使用している `tauri-winrt-notification` を直接使用します。
+///
+/// # 設定
+/// 監視対象ごとの設定（`TargetSettings::desktop_notification`）で有効にした場合のみ、監視タスクで登録されます。
+/// 本文は `MESSAGE_TEMPLATES.desktop` のテンプレート（`MessageTemplate`）で作成します。
+pub struct DesktopNotifier {
+    app_id: String,
+    message: MessageTemplate,
+}
+
+impl DesktopNotifier {
+    /// アプリケーションの識別子（`tauri.conf.json` の `identifier`）からトースト通知の送信元を決定して作成します。
+    ///
+    /// インストールされていない開発ビルドでは識別子がシステムに登録されていないため、
+    /// Tauri と同様に PowerShell の AppUserModelID を使用します。
+    pub fn new(identifier: &str, message: MessageTemplate) -> Self {
+        let app_id = if is_dev_build() {
+            Toast::POWERSHELL_APP_ID.to_string()
+        } else {
+            identifier.to_string()
+        };
+        Self { app_id, message }
+    }
+}
+
+/// 実行ファイルが `target\debug` または `target\release` にあるかどうかを返します。
+fn is_dev_build() -> bool {
+    env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()))
+        .map(|dir| dir.ends_with("target/debug") || dir.ends_with("target/release"))
+        .unwrap_or(false)
+}
+
+/// トースト通知に表示するアイコン画像（PNG）を返します。
+///
+/// ウィンドウのアイコン（`AppInfo::icon`）を優先し、ない場合は検知時にキャプチャしたアイコン画像を使用します。
+fn toast_icon_png(event: &NotificationEvent) -> Option<Vec<u8>> {
+    event
+        .app
+        .icon
+        .as_deref()
+        .and_then(|icon| general_purpose::STANDARD.decode(icon).ok())
+        .or_else(|| event.captured_icon_png.clone())
+}
+
+/// トースト通知のアイコン画像を一時フォルダへ書き出し、そのパスを返します。
+///
+/// トースト通知の画像はファイルのパスで指定する必要があるため、ウィンドウハンドルごとにファイルを作成します。
+fn write_toast_icon(event: &NotificationEvent) -> Option<PathBuf> {
+    let png = toast_icon_png(event)?;
+    let dir = env::temp_dir().join(TOAST_ICON_DIR_NAME);
+    let path = dir.join(format!("{}.png", event.app.hwnd));
+    match fs::create_dir_all(&dir).and_then(|_| fs::write(&path, png)) {
+        Ok(_) => Some(path),
+        Err(e) => {
+            error!(
+                "トースト通知のアイコン画像の書き出しに失敗しました: {:?}",
+                e
+            );
+            None
+        }
+    }
+}
+
+/// 指定したウィンドウが最小化されている場合は復元し、前面に表示します。
+fn focus_window(hwnd: isize) {
+    unsafe {
+        if IsIconic(HWND(hwnd as *mut _)).as_bool() && !restore_window(hwnd) {
+            return;
+        }
+        if SetForegroundWindow(HWND(hwnd as *mut _)).as_bool() {
+            info!("ウィンドウを前面に表示しました。");
+        } else {
+            error!("ウィンドウを前面に表示できませんでした。");
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for DesktopNotifier {
+    fn name(&self) -> &str {
+        "desktop"
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> DeliveryOutcome {
+        let app_id = self.app_id.clone();
+        let text = self.message.render(event);
+        let detected_at_label = self.message.labels().detected_at;
+        let event = event.clone();
+        let shown = tauri::async_runtime::spawn_blocking(move || {
+            let hwnd = event.app.hwnd;
+            let mut toast = Toast::new(&app_id)
+                .title(&event.app.name)
+                .text1(&text)
+                .text2(&format!(
+                    "{}: {}",
+                    detected_at_label,
+                    event.detected_at.format("%Y-%m-%d %H:%M:%S")
+                ))
+                .on_activated(move |_| {
+                    focus_window(hwnd);
+                    Ok(())
+                });
+            if let Some(path) = write_toast_icon(&event) {
+                toast = toast.icon(&path, IconCrop::Square, &event.app.name);
+            }
+            toast.show()
+        })
+        .await;
+
+        match shown {
+            Ok(Ok(())) => DeliveryOutcome::Sent,
+            Ok(Err(e)) => DeliveryOutcome::failed(None, e.to_string()),
+            Err(e) => DeliveryOutcome::failed(None, e.to_string()),
+        }
+    }
+
+    /// 遅れて表示してもウィンドウの前面表示に意味がないため、送信待ちキューへは保存しません。
+    fn queues_when_undelivered(&self) -> bool {
+        false
+    }
+
+    /// 表示に失敗したトースト通知を繰り返し表示しても解消しないため、再送しません。
+    fn retries_when_failed(&self) -> bool {
+        false
+    }
+
+    /// 監視対象ごとの設定で有効にする通知先のため、ルーティングルールに関係なく表示します。
+    fn routable(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::toast_icon_png;
+    use crate::notifier::NotificationEvent;
+    use crate::test_support::sample_app;
+    use crate::window_utils::AppInfo;
+
+    fn event(icon: Option<&str>) -> NotificationEvent {
+        let app = AppInfo {
+            icon: icon.map(str::to_string),
+            ..sample_app()
+        };
+        NotificationEvent::icon_changed(app, 0.2)
+    }
+
+    #[test]
+    fn toast_icon_prefers_window_icon() {
+        let mut event = event(Some("aWNvbg=="));
+        event.captured_icon_png = Some(b"captured".to_vec());
+
+        assert_eq!(toast_icon_png(&event), Some(b"icon".to_vec()));
+    }
+
+    #[test]
+    fn toast_icon_falls_back_to_captured_icon() {
+        let mut event = event(None);
+        assert_eq!(toast_icon_png(&event), None);
+
+        event.captured_icon_png = Some(b"captured".to_vec());
+        assert_eq!(toast_icon_png(&event), Some(b"captured".to_vec()));
+    }
+}
@@ -1,93 +1,308 @@
-use log::{error, info, warn};
-use reqwest::Client;
+use async_trait::async_trait;
+use reqwest::{multipart, Client};
 use serde_json::Value;
-use std::fs;
-use std::path::PathBuf;
 
-/// Discordに通知を送信する非同期関数。
+use crate::app_config::AppConfig;
+use crate::message_template::MessageTemplate;
+use crate::notifier::{non_empty, DeliveryOutcome, NotificationEvent, Notifier};
+
+/// 埋め込み（embed）の左端に表示する色（オレンジ）。
+const EMBED_COLOR: u32 = 0xF5_8A_07;
+/// 監視開始時のアイコン画像の添付ファイル名。
+const BEFORE_ICON_FILE_NAME: &str = "before.png";
+/// 検知時のアイコン画像の添付ファイル名。
+const AFTER_ICON_FILE_NAME: &str = "after.png";
+
+/// Discord に通知を送信する通知先。
 ///
 /// # 概要
-/// 指定されたアプリケーション名に基づき、DiscordのWebhook URLを使用して通知を送信します。
-/// 通知内容はアプリケーションのアイコンの変化を示します。
-///
-/// # 引数
-/// - `app_name`:
-///   - 変化が検知されたアプリケーション名。通知メッセージ内で使用されます。
-/// - `config_path`:
-///   - `appsettings.json` のパス。ここからDiscord Webhook URLを取得します。
+/// Discord の Webhook URL を使用して、アプリケーションのアイコンの変化を埋め込み（embed）で通知します。
+/// 埋め込みにはプロセス ID、検知内容、差分値を表示し、アイコン画像がある場合は
+/// 監視開始時（サムネイル）と検知時（画像）のアイコンを multipart でアップロードして表示します。
 ///
-/// # 処理概要
-/// 1. 指定された設定ファイルを読み込む。
-/// 2. 設定ファイルから`DISCORD_WEBHOOK_URL`を取得。
-/// 3. Webhook URLに対して通知メッセージを送信。
-/// 4. 送信結果に応じてログを出力。
-///
-/// # 注意点
-/// - 設定ファイルが存在しない、または内容が不正な場合、通知は送信されません。
-/// - Webhook URLが設定ファイルに含まれていない場合もエラーとなります。
+/// # 設定
+/// - `DISCORD_WEBHOOK_URL`: Discord Webhook URL。空の場合、通知先として登録されません。
+/// - `DISCORD_USERNAME`: 投稿者名の上書き。空の場合は Webhook の設定を使用します。
+/// - `DISCORD_AVATAR_URL`: アバター画像の URL の上書き。空の場合は Webhook の設定を使用します。
+/// - `DISCORD_MENTION_ROLE_IDS`: メンションするロール ID（カンマ区切り）。
+/// - `DISCORD_MENTION_USER_IDS`: メンションするユーザー ID（カンマ区切り）。
+/// - `MESSAGE_TEMPLATES.discord`: 本文のテンプレート（`MessageTemplate`）。
 ///
 /// # 使用例
 /// ```rust
-/// use std::path::PathBuf;
-/// use my_crate::discord_notifier::send_discord_notification;
+/// use my_crate::app_config::AppConfig;
+/// use my_crate::discord_notifier::DiscordNotifier;
+/// use my_crate::notifier::{http_client, NotificationEvent, Notifier};
 ///
 /// #[tokio::main]
 /// async fn main() {
-///     let config_path = PathBuf::from("path/to/appsettings.json");
-///     send_discord_notification("ExampleApp", config_path).await;
+///     let config = AppConfig::from_value(serde_json::json!({
+///         "DISCORD_WEBHOOK_URL": "https://discord.com/api/webhooks/..."
+///     }))
+///     .unwrap();
+///     let notifier = DiscordNotifier::from_config(&config, &http_client()).unwrap();
+///     let event = NotificationEvent::icon_changed(app_info, 0.12);
+///     let outcome = notifier.send(&event).await;
 /// }
 /// ```
-pub async fn send_discord_notification(app_name: &str, config_path: PathBuf) {
-    let webhook_url = {
-        let config_data = match fs::read_to_string(config_path) {
-            Ok(content) => content,
-            Err(e) => {
-                error!("appsettings.jsonの読み込みに失敗しました: {:?}", e);
-                return;
-            }
-        };
-        let config: Value = match serde_json::from_str(&config_data) {
-            Ok(json) => json,
-            Err(e) => {
-                error!("appsettings.jsonの解析に失敗しました: {:?}", e);
-                return;
-            }
-        };
-        match config["DISCORD_WEBHOOK_URL"].as_str() {
-            Some(url) => url.to_string(),
-            None => {
-                error!("appsettings.jsonにDISCORD_WEBHOOK_URLが設定されていません。");
-                return;
+pub struct DiscordNotifier {
+    client: Client,
+    webhook_url: String,
+    username: Option<String>,
+    avatar_url: Option<String>,
+    mention_role_ids: Vec<String>,
+    mention_user_ids: Vec<String>,
+    message: MessageTemplate,
+}
+
+impl DiscordNotifier {
+    pub fn from_config(config: &AppConfig, client: &Client) -> Option<Self> {
+        let discord = &config.discord;
+        let webhook_url = non_empty(&discord.webhook_url)?;
+        Some(Self {
+            client: client.clone(),
+            webhook_url,
+            username: non_empty(&discord.username),
+            avatar_url: non_empty(&discord.avatar_url),
+            mention_role_ids: split_ids(&discord.mention_role_ids),
+            mention_user_ids: split_ids(&discord.mention_user_ids),
+            message: MessageTemplate::from_config(config, "discord"),
+        })
+    }
+
+    /// Webhook へ送信する JSON を作成します。
+    ///
+    /// `attach_before` / `attach_after` が `true` の場合、添付ファイルのアイコン画像を埋め込みに表示します。
+    fn build_payload(
+        &self,
+        event: &NotificationEvent,
+        attach_before: bool,
+        attach_after: bool,
+    ) -> Value {
+        let mentions: Vec<String> = self
+            .mention_role_ids
+            .iter()
+            .map(|id| format!("<@&{}>", id))
+            .chain(self.mention_user_ids.iter().map(|id| format!("<@{}>", id)))
+            .collect();
+        let labels = self.message.labels();
+        let trigger = self.message.trigger_label(event.trigger);
+        let mut content = self.message.render(event);
+        if !mentions.is_empty() {
+            content = format!("{} {}", mentions.join(" "), content);
+        }
+
+        let mut embed = serde_json::json!({
+            "title": event.app.name,
+            "description": format!("{}{}", trigger, labels.detected_suffix),
+            "color": EMBED_COLOR,
+            "timestamp": event.detected_at.to_rfc3339(),
+            "fields": [
+                { "name": labels.process_id, "value": event.app.process_id.to_string(), "inline": true },
+                { "name": labels.trigger, "value": trigger, "inline": true },
+                { "name": labels.diff_score, "value": format!("{:.4}", event.diff_score), "inline": true }
+            ]
+        });
+        let mut attachments = Vec::new();
+        if attach_before {
+            embed["thumbnail"] =
+                serde_json::json!({ "url": format!("attachment://{}", BEFORE_ICON_FILE_NAME) });
+            attachments.push(serde_json::json!({
+                "id": attachments.len(),
+                "filename": BEFORE_ICON_FILE_NAME,
+                "description": labels.before_icon
+            }));
+        }
+        if attach_after {
+            embed["image"] =
+                serde_json::json!({ "url": format!("attachment://{}", AFTER_ICON_FILE_NAME) });
+            attachments.push(serde_json::json!({
+                "id": attachments.len(),
+                "filename": AFTER_ICON_FILE_NAME,
+                "description": labels.after_icon
+            }));
+        }
+
+        let mut payload = serde_json::json!({
+            "content": content,
+            "embeds": [embed],
+            // 本文中のメンションのうち、設定したロールとユーザーのみ通知します。
+            "allowed_mentions": {
+                "parse": [],
+                "roles": self.mention_role_ids,
+                "users": self.mention_user_ids
             }
+        });
+        if !attachments.is_empty() {
+            payload["attachments"] = Value::Array(attachments);
+        }
+        if let Some(username) = &self.username {
+            payload["username"] = Value::String(username.clone());
         }
-    };
+        if let Some(avatar_url) = &self.avatar_url {
+            payload["avatar_url"] = Value::String(avatar_url.clone());
+        }
+        payload
+    }
+}
+
+/// カンマ区切りの ID を分割します。空の要素は除きます。
+fn split_ids(ids: &str) -> Vec<String> {
+    ids.split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn png_part(png: &[u8], file_name: &'static str) -> Result<multipart::Part, reqwest::Error> {
+    multipart::Part::bytes(png.to_vec())
+        .file_name(file_name)
+        .mime_str("image/png")
+}
 
-    if webhook_url.trim().is_empty() {
-        info!("Discord webhook URL is not configured. Skipping notification.");
-        return;
+#[async_trait]
+impl Notifier for DiscordNotifier {
+    fn name(&self) -> &str {
+        "discord"
     }
 
-    let client = Client::new();
-    let content = format!(
-        "アプリケーション「{}」のアイコンに変化がありました。",
-        app_name
-    );
-    let payload = serde_json::json!({
-        "content": content
-    });
-    let _ = match client.post(&webhook_url).json(&payload).send().await {
-        Ok(response) => {
-            if response.status().is_success() {
-                info!("通知を送信しました。");
-            } else {
-                warn!(
-                    "通知の送信に失敗しました。ステータスコード: {}",
-                    response.status()
-                );
-            }
+    async fn send(&self, event: &NotificationEvent) -> DeliveryOutcome {
+        let icons: Vec<(&Vec<u8>, &'static str)> = [
+            (&event.baseline_icon_png, BEFORE_ICON_FILE_NAME),
+            (&event.captured_icon_png, AFTER_ICON_FILE_NAME),
+        ]
+        .into_iter()
+        .filter_map(|(png, file_name)| png.as_ref().map(|png| (png, file_name)))
+        .collect();
+        let payload = self.build_payload(
+            event,
+            event.baseline_icon_png.is_some(),
+            event.captured_icon_png.is_some(),
+        );
+
+        if icons.is_empty() {
+            return DeliveryOutcome::from_response(
+                self.client
+                    .post(&self.webhook_url)
+                    .json(&payload)
+                    .send()
+                    .await,
+            )
+            .await;
         }
-        Err(e) => {
-            error!("通知の送信中にエラーが発生しました: {:?}", e);
+
+        let mut form = multipart::Form::new().text("payload_json", payload.to_string());
+        for (index, (png, file_name)) in icons.into_iter().enumerate() {
+            match png_part(png, file_name) {
+                Ok(part) => form = form.part(format!("files[{}]", index), part),
+                Err(e) => return DeliveryOutcome::failed(None, e.without_url().to_string()),
+            }
         }
-    };
+        DeliveryOutcome::from_response(
+            self.client
+                .post(&self.webhook_url)
+                .multipart(form)
+                .send()
+                .await,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiscordNotifier;
+    use crate::notifier::{http_client, DeliveryOutcome, NotificationEvent, Notifier};
+    use crate::test_support::{app_config, sample_app, stand_in_server};
+    use crate::window_utils::AppInfo;
+    use serde_json::json;
+
+    fn event() -> NotificationEvent {
+        let app = AppInfo {
+            name: "Build".to_string(),
+            process_id: 42,
+            ..sample_app()
+        };
+        NotificationEvent::icon_changed(app, 0.125)
+    }
+
+    #[tokio::test]
+    async fn rate_limit_reports_retry_after() {
+        let (base_url, server) = stand_in_server(
+            "429 Too Many Requests",
+            r#"{"message":"You are being rate limited.","retry_after":1.5,"global":false}"#,
+        );
+        let notifier = DiscordNotifier::from_config(
+            &app_config(json!({ "DISCORD_WEBHOOK_URL": base_url })),
+            &http_client(),
+        )
+        .unwrap();
+
+        let outcome = notifier.send(&event()).await;
+        server.join().unwrap();
+
+        assert!(matches!(
+            outcome,
+            DeliveryOutcome::RateLimited {
+                retry_after_ms: Some(1500),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn payload_contains_embed_mentions_and_overrides() {
+        let notifier = DiscordNotifier::from_config(
+            &app_config(json!({
+                "DISCORD_WEBHOOK_URL": "https://discord.com/api/webhooks/1/abc",
+                "DISCORD_USERNAME": "Flash Code",
+                "DISCORD_AVATAR_URL": "https://example.com/avatar.png",
+                "DISCORD_MENTION_ROLE_IDS": "111, 222",
+                "DISCORD_MENTION_USER_IDS": "333"
+            })),
+            &http_client(),
+        )
+        .unwrap();
+
+        let payload = notifier.build_payload(&event(), true, true);
+
+        assert!(payload["content"]
+            .as_str()
+            .unwrap()
+            .starts_with("<@&111> <@&222> <@333> "));
+        assert_eq!(payload["allowed_mentions"]["roles"], json!(["111", "222"]));
+        assert_eq!(payload["allowed_mentions"]["users"], json!(["333"]));
+        assert_eq!(payload["username"], "Flash Code");
+        assert_eq!(payload["avatar_url"], "https://example.com/avatar.png");
+        let embed = &payload["embeds"][0];
+        assert_eq!(embed["title"], "Build");
+        assert_eq!(embed["fields"][0]["value"], "42");
+        assert_eq!(embed["fields"][2]["value"], "0.1250");
+        assert_eq!(embed["thumbnail"]["url"], "attachment://before.png");
+        assert_eq!(embed["image"]["url"], "attachment://after.png");
+        assert_eq!(payload["attachments"][1]["filename"], "after.png");
+    }
+
+    #[tokio::test]
+    async fn icons_are_uploaded_as_multipart_files() {
+        let (base_url, server) = stand_in_server("200 OK", "");
+        let notifier = DiscordNotifier::from_config(
+            &app_config(json!({ "DISCORD_WEBHOOK_URL": base_url })),
+            &http_client(),
+        )
+        .unwrap();
+        let mut event = event();
+        event.baseline_icon_png = Some(b"\x89PNG-before".to_vec());
+        event.captured_icon_png = Some(b"\x89PNG-after".to_vec());
+
+        let outcome = notifier.send(&event).await;
+        let request = server.join().unwrap();
+
+        assert_eq!(outcome, DeliveryOutcome::Sent);
+        assert!(request.contains("multipart/form-data"));
+        assert!(request.contains("name=\"payload_json\""));
+        assert!(request.contains("name=\"files[0]\"; filename=\"before.png\""));
+        assert!(request.contains("name=\"files[1]\"; filename=\"after.png\""));
+    }
 }
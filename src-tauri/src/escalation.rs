@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+
+use crate::notifier::NotificationEvent;
+
+/// エスカレーションの待ち時間（分）の上限。
+const MAX_AFTER_MINUTES: u64 = 24 * 60;
+
+/// 確認されないまま時間が経過した場合に行う操作。
+///
+/// - `Resend`: 最初の通知と同じ通知先へ再度送信する。
+/// - `Notify`: `notifiers` の通知先（`Notifier::name`）へ送信する。
+/// - `RaisePriority`: 最初の通知と同じ通知先へ、優先度を上げて送信する。優先度を指定できない通知先には通常どおり送信します。
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum EscalationAction {
+    Resend,
+    Notify { notifiers: Vec<String> },
+    RaisePriority,
+}
+
+/// エスカレーションの 1 段階。
+///
+/// # フィールド
+/// - `after_minutes`: 変化を検知してから操作を行うまでの時間（分）。
+/// - `action`: 確認されていない場合に行う操作。
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EscalationStep {
+    pub after_minutes: u64,
+    #[serde(flatten)]
+    pub action: EscalationAction,
+}
+
+/// 監視対象ごとのエスカレーションポリシー。`TargetSettings::escalation` として保存されます。
+///
+/// # 概要
+/// 変化を検知した後、確認（acknowledge）されないまま `after_minutes` が経過した段階から順に操作を行います。
+/// 次のいずれかで確認されたとみなし、残りの段階は行いません。
+/// - 画面の確認ボタン（`acknowledge_alert` コマンド）。
+/// - 監視対象のウィンドウが元のサイズへ戻された、または前面に表示された。
+/// - タスクバーアイコンが監視開始時の状態へ戻った。
+///
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EscalationPolicy {
+    #[serde(default)]
+    pub steps: Vec<EscalationStep>,
+}
+
+impl EscalationPolicy {
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// 設定値を検証します。
+    pub fn validate(&self) -> Result<(), String> {
+        for step in &self.steps {
+            if !(1..=MAX_AFTER_MINUTES).contains(&step.after_minutes) {
+                return Err(format!(
+                    "エスカレーションまでの時間は1から{}分の範囲で指定してください。",
+                    MAX_AFTER_MINUTES
+                ));
+            }
+            if let EscalationAction::Notify { notifiers } = &step.action {
+                if notifiers.iter().all(|notifier| notifier.trim().is_empty()) {
+                    return Err(format!(
+                        "{}分後のエスカレーション: 通知先を 1 つ以上指定してください。",
+                        step.after_minutes
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 経過時間の短い順に並べた段階を返します。
+    pub fn sorted_steps(&self) -> Vec<EscalationStep> {
+        let mut steps = self.steps.clone();
+        steps.sort_by_key(|step| step.after_minutes);
+        steps
+    }
+}
+
+impl EscalationStep {
+    /// この段階で送信する検知イベントを、最初の検知イベントから作成します。
+    pub fn escalated_event(&self, event: &NotificationEvent) -> NotificationEvent {
+        let mut escalated = event.clone();
+        escalated.escalated_after_minutes = Some(self.after_minutes);
+        escalated.raised_priority = self.action == EscalationAction::RaisePriority;
+        escalated
+    }
+}
+
+/// 検知した変化が確認されたきっかけ。
+///
+/// - `Button`: 画面の確認ボタン。
+/// - `WindowActivated`: 監視対象のウィンドウが元のサイズへ戻された、または前面に表示された。
+/// - `IconRestored`: タスクバーアイコンが監視開始時の状態へ戻った。
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AcknowledgeSource {
+    Button,
+    WindowActivated,
+    IconRestored,
+}
+
+/// 監視対象のウィンドウの表示状態。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WindowActivity {
+    /// 最小化されているかどうか。
+    pub minimized: bool,
+    /// 前面に表示されているかどうか。
+    pub foreground: bool,
+}
+
+impl WindowActivity {
+    /// 前回の状態から、ウィンドウが元のサイズへ戻された、または前面に表示されたかどうかを返します。
+    ///
+    /// 検知時点ですでに前面に表示されていたウィンドウを確認済みとしないよう、状態の変化だけを判定します。
+    pub fn activated_since(&self, previous: &WindowActivity) -> bool {
+        (previous.minimized && !self.minimized) || (!previous.foreground && self.foreground)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EscalationAction, EscalationPolicy, WindowActivity};
+    use crate::test_support::sample_event;
+    use serde_json::json;
+
+    #[test]
+    fn policy_deserializes_actions_and_sorts_steps() {
+        let policy: EscalationPolicy = serde_json::from_value(json!({
+            "steps": [
+                { "after_minutes": 15, "action": "raise_priority" },
+                { "after_minutes": 5, "action": "resend" },
+                { "after_minutes": 10, "action": "notify", "notifiers": ["line"] }
+            ]
+        }))
+        .unwrap();
+
+        assert!(policy.validate().is_ok());
+        let actions: Vec<EscalationAction> = policy
+            .sorted_steps()
+            .into_iter()
+            .map(|step| step.action)
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                EscalationAction::Resend,
+                EscalationAction::Notify {
+                    notifiers: vec!["line".to_string()]
+                },
+                EscalationAction::RaisePriority,
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_rejects_zero_minutes_and_empty_notifiers() {
+        let zero: EscalationPolicy = serde_json::from_value(json!({
+            "steps": [{ "after_minutes": 0, "action": "resend" }]
+        }))
+        .unwrap();
+        let empty: EscalationPolicy = serde_json::from_value(json!({
+            "steps": [{ "after_minutes": 5, "action": "notify", "notifiers": [" "] }]
+        }))
+        .unwrap();
+
+        assert!(zero.validate().is_err());
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn escalated_event_marks_elapsed_minutes_and_priority() {
+        let policy: EscalationPolicy = serde_json::from_value(json!({
+            "steps": [
+                { "after_minutes": 5, "action": "resend" },
+                { "after_minutes": 10, "action": "raise_priority" }
+            ]
+        }))
+        .unwrap();
+        let steps = policy.sorted_steps();
+
+        let resend = steps[0].escalated_event(&sample_event());
+        let raised = steps[1].escalated_event(&sample_event());
+
+        assert_eq!(resend.escalated_after_minutes, Some(5));
+        assert!(!resend.raised_priority);
+        assert_eq!(raised.escalated_after_minutes, Some(10));
+        assert!(raised.raised_priority);
+    }
+
+    #[test]
+    fn window_activity_detects_restore_and_focus_changes() {
+        let minimized = WindowActivity {
+            minimized: true,
+            foreground: false,
+        };
+        let background = WindowActivity {
+            minimized: false,
+            foreground: false,
+        };
+        let focused = WindowActivity {
+            minimized: false,
+            foreground: true,
+        };
+
+        assert!(background.activated_since(&minimized));
+        assert!(focused.activated_since(&background));
+        assert!(!focused.activated_since(&focused));
+        assert!(!minimized.activated_since(&minimized));
+    }
+}
@@ -0,0 +1,115 @@
+use async_trait::async_trait;
+use log::warn;
+use reqwest::Client;
+
+use crate::app_config::AppConfig;
+use crate::message_template::MessageTemplate;
+use crate::notifier::{non_empty, DeliveryOutcome, NotificationEvent, Notifier};
+
+const DEFAULT_GOTIFY_PRIORITY: i64 = 5;
+/// エスカレーションで優先度を上げて送信する場合の最低の優先度。Gotify のクライアントは `8` 以上を高い優先度として扱います。
+const RAISED_GOTIFY_PRIORITY: i64 = 8;
+
+/// Gotify サーバーへ通知を送信する通知先。
+///
+/// # 設定
+/// - `GOTIFY_SERVER_URL`: Gotify サーバーの URL（例: `https://gotify.example.com`）。
+/// - `GOTIFY_APP_TOKEN`: アプリケーションのトークン。
+/// - `GOTIFY_PRIORITY`: 優先度（`0` 以上の整数）。既定値は `5`。優先度を上げて送信する通知では `8` 以上を使用します。
+/// - `MESSAGE_TEMPLATES.gotify`: 本文のテンプレート（`MessageTemplate`）。
+///
+/// サーバーの URL またはトークンが空の場合、通知先として登録されません。
+pub struct GotifyNotifier {
+    client: Client,
+    server_url: String,
+    app_token: String,
+    priority: i64,
+    message: MessageTemplate,
+}
+
+impl GotifyNotifier {
+    pub fn from_config(config: &AppConfig, client: &Client) -> Option<Self> {
+        let gotify = &config.gotify;
+        Some(Self {
+            client: client.clone(),
+            server_url: non_empty(&gotify.server_url)?
+                .trim_end_matches('/')
+                .to_string(),
+            app_token: non_empty(&gotify.app_token)?,
+            priority: parse_priority(gotify.priority),
+            message: MessageTemplate::from_config(config, "gotify"),
+        })
+    }
+}
+
+/// 優先度の設定値を確認します。`0` 以上の整数以外の場合は既定値を使用します。
+fn parse_priority(value: Option<i64>) -> i64 {
+    match value {
+        None => DEFAULT_GOTIFY_PRIORITY,
+        Some(priority) if priority >= 0 => priority,
+        Some(_) => {
+            warn!(
+                "GOTIFY_PRIORITYは0以上の整数で指定してください。既定値({})を使用します。",
+                DEFAULT_GOTIFY_PRIORITY
+            );
+            DEFAULT_GOTIFY_PRIORITY
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for GotifyNotifier {
+    fn name(&self) -> &str {
+        "gotify"
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> DeliveryOutcome {
+        let payload = serde_json::json!({
+            "title": event.app.name,
+            "message": self.message.render(event),
+            "priority": if event.raised_priority {
+                self.priority.max(RAISED_GOTIFY_PRIORITY)
+            } else {
+                self.priority
+            },
+        });
+        DeliveryOutcome::from_response(
+            self.client
+                .post(format!("{}/message", self.server_url))
+                .header("X-Gotify-Key", &self.app_token)
+                .json(&payload)
+                .send()
+                .await,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GotifyNotifier;
+    use crate::notifier::{http_client, DeliveryOutcome, Notifier};
+    use crate::test_support::{app_config, sample_event, stand_in_server};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn send_posts_message_with_app_token() {
+        let (base_url, server) = stand_in_server("200 OK", "{}");
+        let notifier = GotifyNotifier::from_config(
+            &app_config(json!({
+                "GOTIFY_SERVER_URL": format!("{}/", base_url),
+                "GOTIFY_APP_TOKEN": "A_secret",
+                "GOTIFY_PRIORITY": "8"
+            })),
+            &http_client(),
+        )
+        .unwrap();
+        let outcome = notifier.send(&sample_event()).await;
+        let request = server.join().unwrap();
+
+        assert_eq!(outcome, DeliveryOutcome::Sent);
+        assert!(request.starts_with("POST /message "));
+        assert!(request.contains("x-gotify-key: A_secret"));
+        assert!(request.contains(r#""priority":8"#));
+    }
+}
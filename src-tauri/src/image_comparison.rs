@@ -1,9 +1,66 @@
 use image::{DynamicImage, GenericImageView, Rgba};
 use log::info;
+use serde::{Deserialize, Serialize};
+
+/// アイコンの変化を判定する方式の設定。
+///
+/// - `OrangeBadge`: 差分値がしきい値を超え、かつバッジ領域のオレンジ色ピクセルの比率が
+///   `min_orange_ratio` を超える場合に変化ありと判定します。タスクバーの通知点滅を想定した従来の方式です。
+/// - `PixelDiff`: 差分値がしきい値を超えた場合に変化ありと判定します。オレンジ色以外のバッジや点滅を検知したい場合に使用します。
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DetectorConfig {
+    OrangeBadge {
+        #[serde(default = "default_min_orange_ratio")]
+        min_orange_ratio: f32,
+    },
+    PixelDiff,
+}
+
+impl Default for DetectorConfig {
+    fn default() -> Self {
+        DetectorConfig::OrangeBadge {
+            min_orange_ratio: default_min_orange_ratio(),
+        }
+    }
+}
+
+impl DetectorConfig {
+    /// 設定値を検証します。
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            DetectorConfig::OrangeBadge { min_orange_ratio }
+                if !(min_orange_ratio.is_finite() && (0.0..=1.0).contains(min_orange_ratio)) =>
+            {
+                Err("オレンジ色ピクセルの比率は0.0から1.0の有限数を指定してください。".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn default_min_orange_ratio() -> f32 {
+    0.25
+}
+
+/// 画像比較の結果。
+///
+/// # フィールド
+/// - `diff_score`: 画像全体の正規化された差分値（`0.0〜1.0`）。画像サイズが異なる場合は `1.0`。
+/// - `orange_ratio`: 比較対象画像のサブ領域に含まれるオレンジ色ピクセルの比率。
+/// - `is_significant`: 有意な差分があると判定されたかどうか。
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ComparisonResult {
+    pub diff_score: f32,
+    pub orange_ratio: f32,
+    pub is_significant: bool,
+}
 
 /// 2つの画像間のピクセルごとの差分を計算し、
-/// 画像全体の正規化された差分値と比較対象画像中のオレンジ色ピクセルの比率の両方が
-/// 指定された閾値を超える場合に、有意な差分があると判定する関数。
+/// 画像全体の正規化された差分値と比較対象画像中のオレンジ色ピクセルの比率から
+/// 判定方式（`DetectorConfig`）に従って有意な差分があるかを判定する関数。
 ///
 /// # 概要
 /// 指定された2つの画像 (`img1` と `img2`) をピクセル単位で比較します。
@@ -11,44 +68,51 @@ use log::info;
 /// それ以外の場合、各ピクセルのRGB値の絶対差の合計から画像全体の差分値を算出し、
 /// その値を画像の最大差分値で正規化します（0.0〜1.0の範囲）。
 /// さらに、`img2` 中のオレンジ色ピクセルの比率も計算し、
-/// 正規化された差分値が `diff_threshold` を超え、かつオレンジ色ピクセルの比率が 0.3 を超える場合に
-/// 有意な差分があると判定します。
+/// 既定の判定方式（`OrangeBadge`）では、正規化された差分値が `diff_threshold` を超え、
+/// かつオレンジ色ピクセルの比率が `min_orange_ratio` を超える場合に有意な差分があると判定します。
 ///
 /// # 引数
 /// - `img1`: 最初の画像 (`DynamicImage`)。
 /// - `img2`: 比較対象の画像 (`DynamicImage`)。
 /// - `diff_threshold`: 正規化された差分値のしきい値（`0.0〜1.0`）。
 ///   この値以上の場合、画像間の全体的な差分が大きいと見なされます。
+/// - `detector`: 判定方式の設定。
 ///
 /// # 戻り値
-/// - `bool`:
-///   - `true`: 画像全体の正規化された差分値が `diff_threshold` を超え、かつオレンジ色ピクセルの比率が 0.3 を超える場合。
-///   - `false`: それ以外の場合。
+/// - `ComparisonResult`:
+///   - `diff_score`: 画像全体の正規化された差分値。
+///   - `orange_ratio`: オレンジ色ピクセルの比率。
+///   - `is_significant`: 判定方式に従って有意な差分があると判定された場合に `true`。
 ///
 /// # 使用例
 /// ```rust
 /// use image::{open, DynamicImage};
-/// use my_crate::image_comparison::has_significant_difference;
+/// use my_crate::image_comparison::{compare_images, DetectorConfig};
 ///
 /// let img1 = open("image1.png").unwrap();
 /// let img2 = open("image2.png").unwrap();
 /// let diff_threshold = 0.05; // 画像全体の差分のしきい値
 ///
-/// if has_significant_difference(&img1, &img2, diff_threshold) {
+/// if compare_images(&img1, &img2, diff_threshold, &DetectorConfig::default()).is_significant {
 ///     println!("画像に有意な差分があります。");
 /// } else {
 ///     println!("画像の差分はしきい値以下です。");
 /// }
 /// ```
 ///
-pub fn has_significant_difference(
+pub fn compare_images(
     img1: &DynamicImage,
     img2: &DynamicImage,
     diff_threshold: f32,
-) -> bool {
+    detector: &DetectorConfig,
+) -> ComparisonResult {
     // 画像サイズが異なる場合は差分ありと判断
     if img1.dimensions() != img2.dimensions() {
-        return true;
+        return ComparisonResult {
+            diff_score: 1.0,
+            orange_ratio: 0.0,
+            is_significant: true,
+        };
     }
     let (width, height) = img1.dimensions();
     let total_pixels = (width as u64) * (height as u64);
@@ -86,7 +150,17 @@ pub fn has_significant_difference(
     info!("しきい値: {}", diff_threshold);
 
     // 閾値との比較
-    normalized_diff > diff_threshold && orange_ratio > 0.25
+    let is_significant = match detector {
+        DetectorConfig::OrangeBadge { min_orange_ratio } => {
+            normalized_diff > diff_threshold && orange_ratio > *min_orange_ratio
+        }
+        DetectorConfig::PixelDiff => normalized_diff > diff_threshold,
+    };
+    ComparisonResult {
+        diff_score: normalized_diff,
+        orange_ratio,
+        is_significant,
+    }
 }
 
 fn is_orange(pixel: Rgba<u8>) -> bool {
@@ -112,13 +186,21 @@ fn is_orange(pixel: Rgba<u8>) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::has_significant_difference;
+    use super::{compare_images, DetectorConfig};
     use image::{DynamicImage, ImageBuffer, Rgba};
 
     fn solid_image(color: Rgba<u8>) -> DynamicImage {
         DynamicImage::ImageRgba8(ImageBuffer::from_pixel(20, 20, color))
     }
 
+    fn has_significant_difference(
+        img1: &DynamicImage,
+        img2: &DynamicImage,
+        threshold: f32,
+    ) -> bool {
+        compare_images(img1, img2, threshold, &DetectorConfig::default()).is_significant
+    }
+
     #[test]
     fn unchanged_image_is_not_significant() {
         let image = solid_image(Rgba([0, 0, 0, 255]));
@@ -153,4 +235,48 @@ mod tests {
             0.01
         ));
     }
+
+    #[test]
+    fn compare_images_reports_diff_score() {
+        let image1 = solid_image(Rgba([0, 0, 0, 255]));
+        let image2 = solid_image(Rgba([255, 255, 255, 255]));
+
+        let result = compare_images(&image1, &image2, 0.05, &DetectorConfig::default());
+
+        assert_eq!(result.diff_score, 1.0);
+        assert_eq!(result.orange_ratio, 0.0);
+        assert!(!result.is_significant);
+    }
+
+    #[test]
+    fn pixel_diff_detector_ignores_badge_color() {
+        let image1 = solid_image(Rgba([0, 0, 0, 255]));
+        let image2 = solid_image(Rgba([255, 255, 255, 255]));
+
+        let result = compare_images(&image1, &image2, 0.05, &DetectorConfig::PixelDiff);
+
+        assert!(result.is_significant);
+    }
+
+    #[test]
+    fn detector_config_defaults_min_orange_ratio() {
+        let detector: DetectorConfig = serde_json::from_str(r#"{"kind":"orange_badge"}"#).unwrap();
+
+        assert_eq!(detector, DetectorConfig::default());
+    }
+
+    #[test]
+    fn detector_config_rejects_out_of_range_orange_ratio() {
+        for min_orange_ratio in [0.0, 0.25, 1.0] {
+            assert!(DetectorConfig::OrangeBadge { min_orange_ratio }
+                .validate()
+                .is_ok());
+        }
+        for min_orange_ratio in [-0.1, 1.5, f32::NAN, f32::INFINITY] {
+            assert!(DetectorConfig::OrangeBadge { min_orange_ratio }
+                .validate()
+                .is_err());
+        }
+        assert!(DetectorConfig::PixelDiff.validate().is_ok());
+    }
 }
@@ -1,73 +1,484 @@
-use std::{fs, path::PathBuf};
-
+use async_trait::async_trait;
 use log::{error, info, warn};
 use reqwest::Client;
 use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::{fs, path::PathBuf, time::Duration};
 
-pub async fn send_line_notification(app_name: &str, config_path: PathBuf) {
-    let config_data = match fs::read_to_string(&config_path) {
-        Ok(content) => content,
-        Err(e) => {
-            error!("Failed to read appsettings.json: {:?}", e);
-            return;
-        }
-    };
+use crate::app_config::AppConfig;
+use crate::message_template::MessageTemplate;
+use crate::notifier::{non_empty, DeliveryOutcome, NotificationEvent, Notifier};
 
-    let config: Value = match serde_json::from_str(&config_data) {
-        Ok(json) => json,
-        Err(e) => {
-            error!("Failed to parse appsettings.json: {:?}", e);
-            return;
+const LINE_MESSAGE_API_BASE_URL: &str = "https://api.line.me/v2/bot/message";
+/// マルチキャストメッセージで 1 回に送信できる送信先の上限。
+const MULTICAST_MAX_TARGETS: usize = 500;
+/// 公開したアイコン画像を残す期間。これより古い画像は、次に画像を保存する際に削除します。
+const IMAGE_RETENTION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// LINE Messaging API で通知を送信する通知先。
+///
+/// # 概要
+/// アプリケーション名、検知時刻、状態を Flex Message で通知します。
+/// 送信先が 1 件の場合はプッシュメッセージ、複数の場合はマルチキャストメッセージで送信します。
+///
+/// LINE の画像メッセージは公開された HTTPS の URL で画像を指定する必要があるため、
+/// `LINE_IMAGE_DIR` と `LINE_IMAGE_BASE_URL` の両方が設定されている場合のみ、検知時のアイコン画像を
+/// `LINE_IMAGE_DIR` へ保存し、`LINE_IMAGE_BASE_URL` 配下の URL として画像メッセージを送信します。
+/// 画像は検知ごとに 1 つだけ保存し、7 日を過ぎた画像は削除します。
+///
+/// 送信先が 500 件を超える場合は 500 件ずつ送信します。途中で送信に失敗した場合は、
+/// 送信済みの送信先へ重複して届かないよう、再送しない失敗として扱います。
+///
+/// # 設定
+/// - `LINE_ENABLED`: `"true"` の場合のみ通知先として登録されます。
+/// - `LINE_CHANNEL_ACCESS_TOKEN`: チャネルアクセストークン。
+/// - `LINE_TARGET`: 送信先 ID。カンマ区切りで複数指定した場合はマルチキャストで送信します（ユーザー ID のみ）。
+/// - `LINE_IMAGE_DIR`: アイコン画像を保存するフォルダ。Web サーバーの公開フォルダなどを指定します。
+/// - `LINE_IMAGE_BASE_URL`: `LINE_IMAGE_DIR` を公開している HTTPS の URL。
+/// - `MESSAGE_TEMPLATES.line`: 本文のテンプレート（`MessageTemplate`）。通知一覧に表示される代替テキストにも使用します。
+///
+/// トークンまたは送信先が空の場合も通知先として登録されません。
+///
+/// # レート制限
+/// LINE は短時間の送信数の上限を超えた場合と、月間のメッセージ数の上限を超えた場合のどちらも HTTP 429 を返します。
+/// 月間の上限は再送しても解消しないため、恒久的な失敗として扱います。
+pub struct LineNotifier {
+    client: Client,
+    api_base_url: String,
+    channel_access_token: String,
+    targets: Vec<String>,
+    image_hosting: Option<ImageHosting>,
+    message: MessageTemplate,
+}
+
+/// アイコン画像を公開するための保存先。
+struct ImageHosting {
+    dir: PathBuf,
+    base_url: String,
+    /// ファイル名から検知イベントを推測されないよう、ファイル名の作成に使用するランダムな値。
+    salt: u64,
+}
+
+impl ImageHosting {
+    /// 検知イベントのアイコン画像を保存し、公開 URL を返します。
+    ///
+    /// ファイル名は検知イベントごとに決まるため、再送やエスカレーションで同じ検知を送信する場合は保存済みの画像を使用します。
+    fn publish(&self, event: &NotificationEvent, png: &[u8]) -> Option<String> {
+        let file_name = self.file_name(event);
+        let path = self.dir.join(&file_name);
+        if !path.exists() {
+            self.remove_expired_images();
+            if let Err(e) = fs::create_dir_all(&self.dir).and_then(|_| fs::write(&path, png)) {
+                error!("LINE 用のアイコン画像の保存に失敗しました: {:?}", e);
+                return None;
+            }
+            info!("LINE 用のアイコン画像を保存しました: {}", file_name);
         }
-    };
+        Some(format!("{}/{}", self.base_url, file_name))
+    }
 
-    if config["LINE_ENABLED"].as_str().unwrap_or("false") != "true" {
-        info!("LINE notification is disabled. Skipping notification.");
-        return;
+    fn file_name(&self, event: &NotificationEvent) -> String {
+        let mut hasher = DefaultHasher::new();
+        (
+            self.salt,
+            event.app.hwnd,
+            event.app.process_id,
+            event.detected_at,
+        )
+            .hash(&mut hasher);
+        format!("{:016x}.png", hasher.finish())
     }
 
-    let channel_access_token = config["LINE_CHANNEL_ACCESS_TOKEN"].as_str().unwrap_or("");
-    let target = config["LINE_TARGET"].as_str().unwrap_or("");
+    /// 保存期間（`IMAGE_RETENTION`）を過ぎたアイコン画像を削除します。このアプリが保存した形式のファイル名だけを対象とします。
+    fn remove_expired_images(&self) {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries.flatten() {
+            if !is_published_image_name(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            let expired = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > IMAGE_RETENTION);
+            if !expired {
+                continue;
+            }
+            match fs::remove_file(entry.path()) {
+                Ok(_) => info!(
+                    "保存期間を過ぎた LINE 用のアイコン画像を削除しました: {:?}",
+                    entry.file_name()
+                ),
+                Err(e) => warn!(
+                    "LINE 用のアイコン画像の削除に失敗しました: {:?}: {:?}",
+                    entry.file_name(),
+                    e
+                ),
+            }
+        }
+    }
+}
+
+/// `ImageHosting` が保存したアイコン画像のファイル名（16 桁の 16 進数と `.png`）かどうかを返します。
+fn is_published_image_name(name: &str) -> bool {
+    name.strip_suffix(".png")
+        .is_some_and(|stem| stem.len() == 16 && stem.chars().all(|c| c.is_ascii_hexdigit()))
+}
 
-    if channel_access_token.trim().is_empty() || target.trim().is_empty() {
-        info!("LINE notification settings are incomplete. Skipping notification.");
-        return;
+impl LineNotifier {
+    pub fn from_config(config: &AppConfig, client: &Client) -> Option<Self> {
+        let line = &config.line;
+        if !line.enabled {
+            return None;
+        }
+        let targets: Vec<String> = line
+            .target
+            .split(',')
+            .map(str::trim)
+            .filter(|target| !target.is_empty())
+            .map(str::to_string)
+            .collect();
+        if targets.is_empty() {
+            return None;
+        }
+        let image_hosting = match (non_empty(&line.image_dir), non_empty(&line.image_base_url)) {
+            (Some(dir), Some(base_url)) => Some(ImageHosting {
+                dir: PathBuf::from(dir),
+                base_url: base_url.trim_end_matches('/').to_string(),
+                salt: fastrand::u64(..),
+            }),
+            _ => None,
+        };
+        Some(Self {
+            client: client.clone(),
+            api_base_url: LINE_MESSAGE_API_BASE_URL.to_string(),
+            channel_access_token: non_empty(&line.channel_access_token)?,
+            targets,
+            image_hosting,
+            message: MessageTemplate::from_config(config, "line"),
+        })
     }
 
-    let message_text = format!("Application \"{}\" taskbar icon changed.", app_name);
+    /// 送信するメッセージの一覧を作成します。
+    fn build_messages(&self, event: &NotificationEvent, image_url: Option<&str>) -> Vec<Value> {
+        let text = self.message.render(event);
+        let labels = self.message.labels();
+        let rows = vec![
+            flex_row(
+                labels.detected_at,
+                &event.detected_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            ),
+            flex_row(labels.trigger, self.message.trigger_label(event.trigger)),
+        ];
 
-    let payload = serde_json::json!({
-        "to": target,
-        "messages": [
-            {
-                "type": "text",
-                "text": message_text
+        let mut messages = vec![serde_json::json!({
+            "type": "flex",
+            "altText": text,
+            "contents": {
+                "type": "bubble",
+                "body": {
+                    "type": "box",
+                    "layout": "vertical",
+                    "spacing": "md",
+                    "contents": [
+                        {
+                            "type": "text",
+                            "text": event.app.name,
+                            "weight": "bold",
+                            "size": "lg",
+                            "wrap": true
+                        },
+                        {
+                            "type": "text",
+                            "text": text,
+                            "size": "sm",
+                            "wrap": true
+                        },
+                        {
+                            "type": "box",
+                            "layout": "vertical",
+                            "spacing": "sm",
+                            "contents": rows
+                        }
+                    ]
+                }
             }
-        ]
-    });
-
-    let client = Client::new();
-    let response = client
-        .post("https://api.line.me/v2/bot/message/push")
-        .header("Authorization", format!("Bearer {}", channel_access_token))
-        .header("Content-Type", "application/json")
-        .json(&payload)
-        .send()
+        })];
+        if let Some(image_url) = image_url {
+            messages.push(serde_json::json!({
+                "type": "image",
+                "originalContentUrl": image_url,
+                "previewImageUrl": image_url
+            }));
+        }
+        messages
+    }
+
+    async fn post(&self, endpoint: &str, payload: &Value) -> DeliveryOutcome {
+        let outcome = DeliveryOutcome::from_response(
+            self.client
+                .post(format!("{}/{}", self.api_base_url, endpoint))
+                .header(
+                    "Authorization",
+                    format!("Bearer {}", self.channel_access_token),
+                )
+                .header("Content-Type", "application/json")
+                .json(payload)
+                .send()
+                .await,
+        )
         .await;
+        match outcome {
+            DeliveryOutcome::RateLimited { message, .. } if is_monthly_limit(&message) => {
+                DeliveryOutcome::Failed {
+                    status: Some(429),
+                    message,
+                    permanent: true,
+                }
+            }
+            other => other,
+        }
+    }
+}
+
+/// Flex Message の「項目名: 値」の行を作成します。
+fn flex_row(label: &str, value: &str) -> Value {
+    serde_json::json!({
+        "type": "box",
+        "layout": "baseline",
+        "spacing": "sm",
+        "contents": [
+            { "type": "text", "text": label, "color": "#aaaaaa", "size": "sm", "flex": 2 },
+            { "type": "text", "text": value, "size": "sm", "flex": 5, "wrap": true }
+        ]
+    })
+}
 
-    match response {
-        Ok(response) => {
-            if response.status().is_success() {
-                info!("LINE notification sent.");
-            } else {
-                warn!(
-                    "LINE notification failed with status code: {}",
-                    response.status()
-                );
+#[async_trait]
+impl Notifier for LineNotifier {
+    fn name(&self) -> &str {
+        "line"
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> DeliveryOutcome {
+        let image_url = match (&self.image_hosting, &event.captured_icon_png) {
+            (Some(hosting), Some(png)) => hosting.publish(event, png),
+            _ => None,
+        };
+        let messages = self.build_messages(event, image_url.as_deref());
+
+        if let [target] = self.targets.as_slice() {
+            let payload = serde_json::json!({ "to": target, "messages": messages });
+            return self.post("push", &payload).await;
+        }
+        let mut delivered = 0;
+        for targets in self.targets.chunks(MULTICAST_MAX_TARGETS) {
+            let payload = serde_json::json!({ "to": targets, "messages": messages });
+            match self.post("multicast", &payload).await {
+                DeliveryOutcome::Sent => delivered += targets.len(),
+                outcome if delivered == 0 => return outcome,
+                outcome => return partial_failure(delivered, outcome),
             }
         }
-        Err(e) => {
-            error!("Failed to send LINE notification: {:?}", e);
+        DeliveryOutcome::Sent
+    }
+}
+
+/// マルチキャストの途中で送信に失敗した場合の送信結果を返します。
+///
+/// 再送すると送信済みの送信先へ重複して届くため、再送しない失敗として扱います。
+fn partial_failure(delivered: usize, outcome: DeliveryOutcome) -> DeliveryOutcome {
+    let (status, message) = match outcome {
+        DeliveryOutcome::Failed {
+            status, message, ..
+        } => (status, message),
+        DeliveryOutcome::RateLimited { message, .. } => (Some(429), message),
+        DeliveryOutcome::Sent => return DeliveryOutcome::Sent,
+    };
+    DeliveryOutcome::Failed {
+        status,
+        message: format!(
+            "{} 件の送信先へ送信した後、残りの送信先へ送信できませんでした。重複して届かないよう再送しません: {}",
+            delivered, message
+        ),
+        permanent: true,
+    }
+}
+
+/// 月間のメッセージ数の上限を超えたことを示す応答かどうかを返します。
+fn is_monthly_limit(body: &str) -> bool {
+    body.contains("monthly limit")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ImageHosting, LineNotifier};
+    use crate::notifier::{http_client, DeliveryOutcome, Notifier};
+    use crate::test_support::{app_config, sample_event, stand_in_server};
+    use serde_json::json;
+    use std::time::{Duration, SystemTime};
+
+    fn notifier(api_url: String) -> LineNotifier {
+        let mut notifier = LineNotifier::from_config(
+            &app_config(json!({
+                "LINE_ENABLED": "true",
+                "LINE_CHANNEL_ACCESS_TOKEN": "token",
+                "LINE_TARGET": "U123"
+            })),
+            &http_client(),
+        )
+        .unwrap();
+        notifier.api_base_url = api_url;
+        notifier
+    }
+
+    #[tokio::test]
+    async fn rate_limit_is_retryable() {
+        let (base_url, server) = stand_in_server(
+            "429 Too Many Requests",
+            r#"{"message":"The API rate limit has been exceeded. Try again later."}"#,
+        );
+
+        let outcome = notifier(base_url).send(&sample_event()).await;
+        server.join().unwrap();
+
+        assert!(matches!(outcome, DeliveryOutcome::RateLimited { .. }));
+        assert!(outcome.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn monthly_limit_is_reported_as_permanent_failure() {
+        let (base_url, server) = stand_in_server(
+            "429 Too Many Requests",
+            r#"{"message":"You have reached your monthly limit."}"#,
+        );
+
+        let outcome = notifier(base_url).send(&sample_event()).await;
+        server.join().unwrap();
+
+        assert!(matches!(
+            outcome,
+            DeliveryOutcome::Failed {
+                status: Some(429),
+                permanent: true,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn single_target_is_sent_as_flex_push_message() {
+        let (base_url, server) = stand_in_server("200 OK", "{}");
+
+        let outcome = notifier(base_url).send(&sample_event()).await;
+        let request = server.join().unwrap();
+
+        assert_eq!(outcome, DeliveryOutcome::Sent);
+        assert!(request.starts_with("POST /push "));
+        assert!(request.contains(r#""to":"U123""#));
+        assert!(request.contains(r#""type":"flex""#));
+        assert!(!request.contains(r#""type":"image""#));
+    }
+
+    #[tokio::test]
+    async fn multiple_targets_are_sent_as_multicast_with_image() {
+        let (base_url, server) = stand_in_server("200 OK", "{}");
+        let image_dir =
+            std::env::temp_dir().join(format!("flash-code-line-test-{}", std::process::id()));
+        let mut notifier = LineNotifier::from_config(
+            &app_config(json!({
+                "LINE_ENABLED": "true",
+                "LINE_CHANNEL_ACCESS_TOKEN": "token",
+                "LINE_TARGET": "U123, U456",
+                "LINE_IMAGE_DIR": image_dir.to_string_lossy(),
+                "LINE_IMAGE_BASE_URL": "https://example.com/icons/"
+            })),
+            &http_client(),
+        )
+        .unwrap();
+        notifier.api_base_url = base_url;
+        let mut event = sample_event();
+        event.captured_icon_png = Some(b"\x89PNG".to_vec());
+
+        let outcome = notifier.send(&event).await;
+        let request = server.join().unwrap();
+        let saved = std::fs::read_dir(&image_dir).unwrap().count();
+        std::fs::remove_dir_all(&image_dir).unwrap();
+
+        assert_eq!(outcome, DeliveryOutcome::Sent);
+        assert!(request.starts_with("POST /multicast "));
+        assert!(request.contains(r#""to":["U123","U456"]"#));
+        assert!(request.contains(r#""originalContentUrl":"https://example.com/icons/"#));
+        assert_eq!(saved, 1);
+    }
+
+    #[test]
+    fn image_is_saved_once_per_event_and_expired_images_are_removed() {
+        let dir =
+            std::env::temp_dir().join(format!("flash-code-line-image-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let expired = dir.join("0123456789abcdef.png");
+        std::fs::write(&expired, b"old").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&expired)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(8 * 24 * 60 * 60))
+            .unwrap();
+        std::fs::write(dir.join("index.html"), b"keep").unwrap();
+        let hosting = ImageHosting {
+            dir: dir.clone(),
+            base_url: "https://example.com/icons".to_string(),
+            salt: 1,
+        };
+        let event = sample_event();
+
+        let first = hosting.publish(&event, b"\x89PNG").unwrap();
+        let second = hosting.publish(&event, b"\x89PNG").unwrap();
+        let mut names: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"index.html".to_string()));
+        assert!(!names.contains(&"0123456789abcdef.png".to_string()));
+    }
+
+    #[tokio::test]
+    async fn failure_after_a_delivered_chunk_is_not_retried() {
+        let (base_url, server) = stand_in_server("200 OK", "{}");
+        let targets: Vec<String> = (0..501).map(|i| format!("U{}", i)).collect();
+        let mut notifier = LineNotifier::from_config(
+            &app_config(json!({
+                "LINE_ENABLED": "true",
+                "LINE_CHANNEL_ACCESS_TOKEN": "token",
+                "LINE_TARGET": targets.join(",")
+            })),
+            &http_client(),
+        )
+        .unwrap();
+        notifier.api_base_url = base_url;
+
+        let outcome = notifier.send(&sample_event()).await;
+        server.join().unwrap();
+
+        match outcome {
+            DeliveryOutcome::Failed {
+                message, permanent, ..
+            } => {
+                assert!(permanent);
+                assert!(message.starts_with("500 件の送信先へ送信した後"));
+            }
+            other => panic!("unexpected outcome: {:?}", other),
         }
     }
 }
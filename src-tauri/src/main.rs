@@ -1,20 +1,45 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod app_config;
+mod command_notifier;
 mod commands;
 mod config_manager;
+mod delivery_history;
+mod delivery_retry;
+mod desktop_notifier;
 mod discord_notifier;
+mod escalation;
+mod gotify_notifier;
 mod icon_position;
 mod image_comparison;
 mod line_notifier;
+mod message_template;
 mod monitor;
+mod monitor_persistence;
+mod notification_queue;
+mod notification_routing;
+mod notifier;
+mod notifier_instance;
+mod ntfy_notifier;
+mod pushover_notifier;
 mod screen_capture;
+mod slack_notifier;
+mod smtp_notifier;
+mod teams_notifier;
+mod telegram_notifier;
+#[cfg(test)]
+mod test_support;
+mod webhook_notifier;
 mod window_collection;
 mod window_utils;
-use std::env;
+use std::{env, fs};
 
+use delivery_history::{DeliveryHistory, DELIVERY_HISTORY_FILE_NAME};
 use log::warn;
-use tauri::Manager;
+use monitor_persistence::{resume_monitoring, ResumeReportState, MONITORED_TARGETS_FILE_NAME};
+use notification_queue::{run_queue_worker, NotificationQueue, NOTIFICATION_QUEUE_FILE_NAME};
+use tauri::{Manager, RunEvent};
 use tauri_plugin_log::LogTarget;
 use window_utils::{get_or_create_config_file_path, ConfigState, MonitorState};
 
@@ -22,16 +47,51 @@ fn main() {
     tauri::Builder::default()
         .setup(|app| {
             // アプリのシステム設定フォルダパスを取得
+            let mut targets_file = None;
             if let Some(config_dir) = app.path_resolver().app_config_dir() {
                 let config_file = get_or_create_config_file_path(&config_dir, "appsettings.json");
                 app.manage(ConfigState {
                     path: config_file.clone(),
                 });
+                targets_file = Some(config_dir.join(MONITORED_TARGETS_FILE_NAME));
             } else {
                 warn!("アプリのシステム設定フォルダパスが取得できませんでした。");
             }
 
-            app.manage(MonitorState::new());
+            app.manage(MonitorState::new(app.handle(), targets_file.clone()));
+            app.manage(ResumeReportState::new());
+
+            let data_dir = match app.path_resolver().app_data_dir() {
+                Some(data_dir) => match fs::create_dir_all(&data_dir) {
+                    Ok(_) => Some(data_dir),
+                    Err(e) => {
+                        warn!("アプリのデータフォルダの作成に失敗しました: {:?}", e);
+                        None
+                    }
+                },
+                None => {
+                    warn!("アプリのデータフォルダパスが取得できませんでした。");
+                    None
+                }
+            };
+            // 検知と通知の送信結果の配信履歴
+            app.manage(DeliveryHistory::new(
+                data_dir
+                    .as_ref()
+                    .map(|data_dir| data_dir.join(DELIVERY_HISTORY_FILE_NAME)),
+            ));
+            // オフライン時に送信できなかった通知の送信待ちキュー
+            app.manage(NotificationQueue::load(
+                data_dir
+                    .as_ref()
+                    .map(|data_dir| data_dir.join(NOTIFICATION_QUEUE_FILE_NAME)),
+            ));
+            tauri::async_runtime::spawn(run_queue_worker(app.handle()));
+
+            // 前回終了時の監視対象の監視を再開
+            if targets_file.is_some() {
+                tauri::async_runtime::spawn(resume_monitoring(app.handle()));
+            }
 
             Ok(())
         })
@@ -63,23 +123,39 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             commands::start_monitoring,
             commands::stop_monitoring,
+            commands::add_monitoring_target,
+            commands::remove_monitoring_target,
+            commands::pause_monitoring_target,
+            commands::resume_monitoring_target,
+            commands::acknowledge_alert,
+            commands::get_monitoring_status,
+            commands::get_notification_queue,
+            commands::get_delivery_history,
+            commands::clear_delivery_history,
+            commands::get_app_config,
+            commands::update_app_config,
+            commands::get_target_settings,
+            commands::update_target_settings,
             commands::get_taskbar_apps,
-            commands::update_webhook_url,
-            commands::update_threshold,
-            commands::update_interval,
-            commands::update_minimize_on_monitor_start,
-            commands::update_line_enabled,
-            commands::update_line_channel_access_token,
-            commands::update_line_target,
-            commands::get_webhook_url,
-            commands::get_threshold,
-            commands::get_interval,
-            commands::get_minimize_on_monitor_start,
-            commands::get_line_enabled,
-            commands::get_line_channel_access_token_configured,
-            commands::get_line_target,
+            commands::get_message_template_settings,
+            commands::preview_message_template,
+            commands::get_routing_rules,
+            commands::update_routing_rules,
+            commands::list_notifier_instances,
+            commands::add_notifier_instance,
+            commands::update_notifier_instance,
+            commands::remove_notifier_instance,
+            commands::send_test_notification,
+            commands::get_resume_report,
             commands::log_from_frontend
         ])
-        .run(tauri::generate_context!())
-        .expect("tauri::Builder::default() | run: error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("tauri::Builder::default() | build: error while building tauri application")
+        .run(|app_handle, event| {
+            // 終了時の監視対象は保存したまま監視を停止し、次回起動時に再開できるようにする
+            if let RunEvent::ExitRequested { .. } = event {
+                let monitor_state = app_handle.state::<MonitorState>();
+                tauri::async_runtime::block_on(monitor_state.shutdown());
+            }
+        });
 }
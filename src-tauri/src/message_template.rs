@@ -0,0 +1,358 @@
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::app_config::AppConfig;
+use crate::notifier::{
+    render_template_with, template_values, NotificationEvent, NotificationTrigger,
+};
+use crate::window_utils::AppInfo;
+
+/// 本文テンプレートを設定できる通知先の名前。
+///
+/// 汎用 Webhook とコマンド実行は、それぞれ専用のテンプレート（`HTTP_WEBHOOK_BODY_TEMPLATE` など）を使用します。
+pub const TEMPLATE_NOTIFIERS: [&str; 10] = [
+    "discord", "line", "slack", "teams", "telegram", "ntfy", "gotify", "pushover", "email",
+    "desktop",
+];
+
+/// 本文テンプレートで使用できるプレースホルダーの名前。`notifier::template_values` の一覧に加え、
+/// 言語に応じた検知内容の表示名（`{trigger_label}`）を使用できます。
+const PLACEHOLDERS: [&str; 8] = [
+    "app_name",
+    "pid",
+    "timestamp",
+    "time",
+    "diff_score",
+    "trigger",
+    "trigger_label",
+    "icon_base64",
+];
+
+/// 通知の言語。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    /// 日本語（既定）。
+    #[default]
+    Ja,
+    /// 英語。
+    En,
+}
+
+/// 通知本文の見出しなど、テンプレート以外の部分に使用する文言。
+pub struct MessageLabels {
+    pub application: &'static str,
+    pub trigger: &'static str,
+    pub detected_at: &'static str,
+    pub process_id: &'static str,
+    pub diff_score: &'static str,
+    pub before_icon: &'static str,
+    pub after_icon: &'static str,
+    /// 検知内容の表示名に続けて「〜を検知しました。」とする文言。
+    pub detected_suffix: &'static str,
+}
+
+const JA_LABELS: MessageLabels = MessageLabels {
+    application: "アプリケーション",
+    trigger: "検知内容",
+    detected_at: "検知時刻",
+    process_id: "プロセス ID",
+    diff_score: "差分値",
+    before_icon: "監視開始時のアイコン",
+    after_icon: "検知時のアイコン",
+    detected_suffix: "を検知しました。",
+};
+
+const EN_LABELS: MessageLabels = MessageLabels {
+    application: "Application",
+    trigger: "Status",
+    detected_at: "Time",
+    process_id: "Process ID",
+    diff_score: "Diff score",
+    before_icon: "Icon at start",
+    after_icon: "Icon at detection",
+    detected_suffix: " detected.",
+};
+
+impl Language {
+    /// `"ja"` または `"en"` を解析します。それ以外の場合は `None` を返します。
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "ja" => Some(Language::Ja),
+            "en" => Some(Language::En),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::Ja => "ja",
+            Language::En => "en",
+        }
+    }
+
+    /// 設定値から通知の言語を取得します。空または不正な場合は日本語を使用します。
+    pub fn labels(&self) -> &'static MessageLabels {
+        match self {
+            Language::Ja => &JA_LABELS,
+            Language::En => &EN_LABELS,
+        }
+    }
+
+    /// 検知内容の表示名を返します。
+    pub fn trigger_label(&self, trigger: NotificationTrigger) -> &'static str {
+        match (self, trigger) {
+            (Language::Ja, NotificationTrigger::IconChanged) => "アイコンの変化",
+            (Language::En, NotificationTrigger::IconChanged) => "Icon changed",
+        }
+    }
+
+    /// エスカレーションで送信する通知の場合、本文に添える経過時間の注記を返します。
+    ///
+    /// エスカレーションで送信する通知でない場合は空文字を返します。
+    pub fn escalation_note(&self, event: &NotificationEvent) -> String {
+        let minutes = match event.escalated_after_minutes {
+            Some(minutes) => minutes,
+            None => return String::new(),
+        };
+        match self {
+            Language::Ja => format!(
+                "\n※ 検知から {} 分経過しても確認されていないため、再度通知しています。",
+                minutes
+            ),
+            Language::En => format!(
+                "\n(Not acknowledged {} minutes after detection. Notifying again.)",
+                minutes
+            ),
+        }
+    }
+
+    /// 送信待ちキューから遅れて送信する通知の場合、本文に添える元の検知時刻の注記を返します。
+    ///
+    /// 遅れて送信する通知でない場合は空文字を返します。
+    pub fn delayed_note(&self, event: &NotificationEvent) -> String {
+        if !event.delayed {
+            return String::new();
+        }
+        let detected_at = event.detected_at.format("%Y-%m-%d %H:%M:%S");
+        match self {
+            Language::Ja => format!(
+                "\n※ {} に検知した変化です。送信できなかったため、遅れて送信しています。",
+                detected_at
+            ),
+            Language::En => format!(
+                "\n(Detected at {}. Delivered late because it could not be sent earlier.)",
+                detected_at
+            ),
+        }
+    }
+}
+
+/// 通知先の既定の本文テンプレートを返します。
+///
+/// Telegram は本文だけを送信するため、検知時刻も含めます。
+pub fn default_template(language: Language, notifier: &str) -> &'static str {
+    match (language, notifier) {
+        (Language::Ja, "telegram") => {
+            "アプリケーション「{app_name}」のアイコンに変化がありました。\n検知時刻: {time}"
+        }
+        (Language::Ja, "desktop") => "{trigger_label}を検知しました。",
+        (Language::Ja, _) => "アプリケーション「{app_name}」のアイコンに変化がありました。",
+        (Language::En, "telegram") => {
+            "Application \"{app_name}\" taskbar icon changed.\nTime: {time}"
+        }
+        (Language::En, "desktop") => "{trigger_label} detected.",
+        (Language::En, _) => "Application \"{app_name}\" taskbar icon changed.",
+    }
+}
+
+/// 通知先の本文テンプレート。
+///
+/// # 概要
+/// `MESSAGE_TEMPLATES` に通知先の名前をキーとしてテンプレートが保存されている場合はそのテンプレートを、
+/// ない場合は `NOTIFY_LANGUAGE` の言語の既定のテンプレートを使用します。
+///
+/// # プレースホルダー
+/// `notifier::template_values` のプレースホルダーに加え、次のプレースホルダーを使用できます。
+/// - `{time}`: 検知時刻（`%Y-%m-%d %H:%M:%S` 形式）。
+/// - `{trigger_label}`: 言語に応じた検知内容の表示名（例: `アイコンの変化`）。
+#[derive(Clone, Debug, PartialEq)]
+pub struct MessageTemplate {
+    pub language: Language,
+    pub template: String,
+}
+
+impl MessageTemplate {
+    /// 設定値から指定した通知先の本文テンプレートを取得します。
+    pub fn from_config(config: &AppConfig, notifier: &str) -> Self {
+        let language = config.notify_language;
+        let template = config
+            .message_templates
+            .get(notifier)
+            .map(String::as_str)
+            .filter(|template| !template.trim().is_empty())
+            .unwrap_or_else(|| default_template(language, notifier))
+            .to_string();
+        Self { language, template }
+    }
+
+    pub fn labels(&self) -> &'static MessageLabels {
+        self.language.labels()
+    }
+
+    pub fn trigger_label(&self, trigger: NotificationTrigger) -> &'static str {
+        self.language.trigger_label(trigger)
+    }
+
+    /// テンプレートを検知イベントの値で置き換え、エスカレーションや遅れて送信する通知の場合は注記を添えた本文を返します。
+    pub fn render(&self, event: &NotificationEvent) -> String {
+        let mut values = template_values(event);
+        values.push((
+            "trigger_label",
+            self.trigger_label(event.trigger).to_string(),
+        ));
+        let mut rendered = render_template_with(&self.template, &values, str::to_string);
+        rendered.push_str(&self.language.escalation_note(event));
+        rendered.push_str(&self.language.delayed_note(event));
+        rendered
+    }
+}
+
+/// テンプレートに含まれる、使用できないプレースホルダーの名前を返します。
+///
+/// `{` と `}` で囲まれた英小文字と `_` だけの文字列をプレースホルダーとみなします。
+pub fn unknown_placeholders(template: &str) -> Vec<String> {
+    let mut unknown = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        let end = match rest.find('}') {
+            Some(end) => end,
+            None => break,
+        };
+        let name = &rest[..end];
+        let is_placeholder =
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase() || c == '_');
+        if is_placeholder && !PLACEHOLDERS.contains(&name) && !unknown.iter().any(|n| n == name) {
+            unknown.push(name.to_string());
+        }
+    }
+    unknown
+}
+
+/// 通知先の名前と、本文テンプレートのプレースホルダーを検証します。
+pub fn validate_message_template(notifier: &str, template: &str) -> Result<(), String> {
+    if !TEMPLATE_NOTIFIERS.contains(&notifier) {
+        return Err(format!(
+            "本文テンプレートを設定できない通知先です: {}",
+            notifier
+        ));
+    }
+    let unknown = unknown_placeholders(template);
+    if !unknown.is_empty() {
+        return Err(format!(
+            "使用できないプレースホルダーが含まれています: {}",
+            unknown
+                .iter()
+                .map(|name| format!("{{{}}}", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// テンプレートのプレビューや通知のテスト送信に使用する検知イベントを返します。
+pub fn preview_event() -> NotificationEvent {
+    let app = AppInfo {
+        name: "Sample App".to_string(),
+        hwnd: 0,
+        process_id: 1234,
+        thread_id: 0,
+        icon: None,
+        executable: Some("sample.exe".to_string()),
+    };
+    let mut event = NotificationEvent::icon_changed(app, 0.1234);
+    if let Some(detected_at) = Local.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).single() {
+        event.detected_at = detected_at;
+    }
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{unknown_placeholders, Language, MessageTemplate};
+    use crate::test_support::{app_config, sample_event};
+    use chrono::{Local, TimeZone};
+    use serde_json::json;
+
+    #[test]
+    fn from_config_uses_language_default_unless_template_is_configured() {
+        let config = app_config(json!({
+            "NOTIFY_LANGUAGE": "en",
+            "MESSAGE_TEMPLATES": { "slack": "{app_name} ({trigger_label}) at {time}" }
+        }));
+
+        let discord = MessageTemplate::from_config(&config, "discord");
+        let slack = MessageTemplate::from_config(&config, "slack");
+        let mut event = sample_event();
+        event.detected_at = Local.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).unwrap();
+
+        assert_eq!(
+            discord.render(&event),
+            "Application \"Chat\" taskbar icon changed."
+        );
+        assert_eq!(
+            slack.render(&event),
+            "Chat (Icon changed) at 2024-01-01 09:00:00"
+        );
+    }
+
+    #[test]
+    fn render_appends_delayed_note_in_language() {
+        let mut event = sample_event();
+        event.delayed = true;
+
+        let ja = MessageTemplate::from_config(&app_config(json!({})), "discord").render(&event);
+        let en = MessageTemplate::from_config(
+            &app_config(json!({ "NOTIFY_LANGUAGE": "en" })),
+            "discord",
+        )
+        .render(&event);
+
+        assert!(ja.starts_with("アプリケーション「Chat」"));
+        assert!(ja.contains("遅れて送信しています"));
+        assert!(en.contains("Delivered late"));
+    }
+
+    #[test]
+    fn render_appends_escalation_note() {
+        let mut event = sample_event();
+        event.escalated_after_minutes = Some(10);
+
+        let rendered =
+            MessageTemplate::from_config(&app_config(json!({ "NOTIFY_LANGUAGE": "en" })), "slack")
+                .render(&event);
+
+        assert!(
+            rendered.ends_with("(Not acknowledged 10 minutes after detection. Notifying again.)")
+        );
+    }
+
+    #[test]
+    fn invalid_language_falls_back_to_japanese() {
+        assert_eq!(
+            app_config(json!({ "NOTIFY_LANGUAGE": "fr" })).notify_language,
+            Language::Ja
+        );
+        assert_eq!(Language::parse(" EN "), Some(Language::En));
+    }
+
+    #[test]
+    fn unknown_placeholders_ignores_json_braces() {
+        assert_eq!(
+            unknown_placeholders(r#"{app_name} {title} {"a":1} {title} {time}"#),
+            vec!["title".to_string()]
+        );
+    }
+}
@@ -1,30 +1,99 @@
 // monitor.rs
 use crate::{
-    discord_notifier::send_discord_notification, image_comparison::has_significant_difference,
-    line_notifier::send_line_notification, screen_capture::capture_icon_image,
-    window_utils::AppInfo,
+    app_config::AppConfig,
+    delivery_history::{DeliveryHistory, HistoryEntry, HistoryKind},
+    desktop_notifier::DesktopNotifier,
+    escalation::{
+        AcknowledgeSource, EscalationAction, EscalationPolicy, EscalationStep, WindowActivity,
+    },
+    image_comparison::{compare_images, DetectorConfig},
+    message_template::MessageTemplate,
+    notification_queue::NotificationQueue,
+    notification_routing::NotificationRouter,
+    notifier::{DeliveryOutcome, DeliveryResult, NotificationEvent, NotifierRegistry},
+    screen_capture::capture_icon_image,
+    window_utils::{restore_window, AppInfo, StatusReporter, TargetState},
+};
+use chrono::Local;
+use log::{error, info, warn};
+use serde::Serialize;
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
 };
-use log::{error, info};
-use std::{path::PathBuf, time::Duration};
 use tauri::Manager;
 use tokio::time::sleep;
 use windows::Win32::{
     Foundation::{HWND, LPARAM, LRESULT, WPARAM},
-    UI::WindowsAndMessaging::{SendMessageW, SC_MINIMIZE, SC_RESTORE, WM_SYSCOMMAND},
+    UI::WindowsAndMessaging::{
+        GetForegroundWindow, IsIconic, IsWindow, SendMessageW, SC_MINIMIZE, WM_SYSCOMMAND,
+    },
 };
 
+/// 確認待ちの間、アイコンが監視開始時の状態へ戻ったとみなすまでに、その状態が続く必要がある時間。
+///
+/// 点滅中のアイコンは一時的に監視開始時の状態へ戻るため、1 回の比較では判定しません。
+const ICON_RESTORED_DURATION: Duration = Duration::from_secs(5);
+
+/// 監視タスクの起動に使用する設定値。
+///
+/// 全体設定と監視対象ごとの設定（`TargetSettings`）を統合し、検証した後の値です。
+///
+/// # フィールド
+/// - `interval`: チェック間隔（ミリ秒）。
+/// - `threshold`: 画像比較のしきい値（`0.0〜1.0`）。
+/// - `minimize_on_start`: 監視開始時に対象ウィンドウを最小化するかどうか。
+/// - `detector`: アイコンの変化の判定方式。
+/// - `desktop_notification`: 変化を検知した際にデスクトップ通知を表示するかどうか。
+/// - `escalation`: 検知した変化が確認されない場合のエスカレーションポリシー。`None` の場合は検知後すぐに監視を終了します。
+///
+#[derive(Clone, Debug)]
+pub struct MonitorSettings {
+    pub interval: u64,
+    pub threshold: f32,
+    pub minimize_on_start: bool,
+    pub detector: DetectorConfig,
+    pub desktop_notification: bool,
+    pub escalation: Option<EscalationPolicy>,
+}
+
+/// 確認待ちの終了理由。
+enum AcknowledgeWait {
+    /// 確認された。
+    Acknowledged(AcknowledgeSource),
+    /// 確認されないまま、すべての段階のエスカレーションを行った。
+    Exhausted,
+    /// 監視対象のウィンドウが見つからなくなった。
+    WindowLost,
+}
+
+/// 恒久的な失敗（認証エラーや送信先が存在しないなど）となった通知先の一覧。
+///
+/// 再送しても成功しないため、`notification_delivery_failed` イベントでフロントエンドへ通知し、設定の見直しを促します。
+///
+/// # フィールド
+/// - `app`: 変化が検知されたアプリケーションの情報。
+/// - `failures`: 恒久的な失敗となった通知先ごとの送信結果。
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct DeliveryFailureReport {
+    pub app: AppInfo,
+    pub failures: Vec<DeliveryResult>,
+}
+
 /// 監視対象のアプリケーションアイコンを定期的にチェックする非同期関数。
 ///
 /// # 概要
 /// 指定したアプリケーションのアイコンを一定間隔でキャプチャし、
 /// 初期状態のアイコンと比較して変化があった場合に通知を送信します。
+/// エスカレーションポリシーが設定されている場合は、変化が確認されるまで監視を続けます。
 ///
 /// # 引数
 /// - `app_info`: 監視対象アプリケーションの情報（`AppInfo`）。
-/// - `interval`: チェック間隔（ミリ秒）。
-/// - `threshold`: 画像比較のしきい値（`0.0〜1.0`）。
-/// - `config_path`: 通知送信に必要な設定ファイルのパス。
-/// - `app_handle`: Tauri の AppHandle。`emit_all` を用いて全ウィンドウへ "monitoring_stopped" イベントを発行するために利用。
+/// - `settings`: 監視間隔・しきい値・最小化設定・判定方式・デスクトップ通知の有無。
+/// - `config_path`: 通知送信に必要な設定ファイルのパス。監視開始時に 1 度だけ読み込み、通知先を構築します。
+/// - `app_handle`: Tauri の AppHandle。`emit_all` を用いて全ウィンドウへ "monitor_target_stopped" イベントを発行するために利用。
+/// - `reporter`: 監視状況（状態、最終取得時刻、差分値、連続失敗回数、通知の送信結果）を `MonitorState` へ報告するためのハンドル。
 ///
 /// # 使用例
 /// ```rust
@@ -32,26 +101,52 @@ use windows::Win32::{
 /// use std::path::PathBuf;
 ///
 /// let app_info = AppInfo { /* 初期化 */ };
-/// let interval = 3000;
-/// let threshold = 0.050;
+/// let settings = MonitorSettings {
+///     interval: 3000,
+///     threshold: 0.050,
+///     minimize_on_start: true,
+///     detector: DetectorConfig::default(),
+///     desktop_notification: false,
+///     escalation: None,
+/// };
 /// let config_path = PathBuf::from("path/to/config.json");
 /// // Tauri 側で AppHandle を取得する必要があります。
 /// // ここでは仮に `app_handle` として取得したものを渡す例です。
 /// let app_handle = tauri::AppHandle::current();
 ///
-/// monitor_app_icon(app_info, interval, threshold, config_path, app_handle).await;
+/// monitor_app_icon(app_info, settings, config_path, app_handle, reporter).await;
 /// ```
 ///
 pub async fn monitor_app_icon(
     app_info: AppInfo,
-    interval: u64,
-    threshold: f32,
-    minimize_on_start: bool,
+    settings: MonitorSettings,
     config_path: PathBuf,
     app_handle: tauri::AppHandle,
+    reporter: StatusReporter,
 ) {
     info!("monitor_app_iconを呼び出しました。");
-    if minimize_on_start {
+    // 設定ファイルを読み込めない場合も、通知先を登録せずに監視を続けます。
+    let config = match AppConfig::load(&config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            error!(
+                "通知先の読み込みに失敗したため、通知先なしで監視を続けます: {}",
+                e
+            );
+            AppConfig::default()
+        }
+    };
+    let mut notifiers = NotifierRegistry::from_config(&config);
+    let router = NotificationRouter::from_config(&config);
+    if settings.desktop_notification {
+        notifiers.register(Box::new(DesktopNotifier::new(
+            &app_handle.config().tauri.bundle.identifier,
+            MessageTemplate::from_config(&config, "desktop"),
+        )));
+    }
+    info!("通知先: {:?}", notifiers.names());
+
+    if settings.minimize_on_start {
         unsafe {
             let result = SendMessageW(
                 HWND(app_info.hwnd as *mut _),
@@ -63,6 +158,7 @@ pub async fn monitor_app_icon(
                 info!("ウィンドウを最小化しました。(返り値: {:?})", result);
             } else {
                 error!("ウィンドウの最小化に失敗しました。: {:?}", result);
+                report_error(&reporter, "ウィンドウの最小化に失敗しました。").await;
                 return;
             }
         }
@@ -75,55 +171,373 @@ pub async fn monitor_app_icon(
         Some(img) => img,
         None => {
             error!("初期画像の取得に失敗しました。");
+            if is_window_lost(app_info.hwnd) {
+                report_lost(&reporter).await;
+            } else {
+                report_error(&reporter, "初期画像の取得に失敗しました。").await;
+            }
             return;
         }
     };
+    reporter
+        .update(|status| {
+            status.state = TargetState::Watching;
+            status.last_capture_at = Some(Local::now());
+        })
+        .await;
 
     info!("アイコンの監視ループを開始します。");
     loop {
         // 一定時間待機
-        sleep(Duration::from_millis(interval)).await;
+        sleep(Duration::from_millis(settings.interval)).await;
 
         // 現在のアイコン画像を取得
         let current_image = match capture_icon_image(HWND(app_info.hwnd as *mut _)) {
             Some(img) => img,
             None => {
                 error!("アイコンの取得に失敗しました。");
+                if is_window_lost(app_info.hwnd) {
+                    warn!("監視対象のウィンドウが見つかりません。監視を終了します。");
+                    report_lost(&reporter).await;
+                    return;
+                }
+                reporter
+                    .update(|status| status.consecutive_capture_failures += 1)
+                    .await;
                 continue;
             }
         };
         info!("アイコンの取得に成功しました。");
 
         // 画像比較
-        if has_significant_difference(&initial_image, &current_image, threshold) {
+        let comparison = compare_images(
+            &initial_image,
+            &current_image,
+            settings.threshold,
+            &settings.detector,
+        );
+        reporter
+            .update(|status| {
+                status.last_capture_at = Some(Local::now());
+                status.last_diff_score = Some(comparison.diff_score);
+                status.consecutive_capture_failures = 0;
+                if comparison.is_significant {
+                    status.state = TargetState::Triggered;
+                }
+            })
+            .await;
+        if comparison.is_significant {
             info!("アイコンに変化がありました。");
             // 変化が検知された場合の処理
-            send_discord_notification(&app_info.name, config_path.clone()).await;
-            send_line_notification(&app_info.name, config_path.clone()).await;
-
-            if minimize_on_start {
-                unsafe {
-                    let result = SendMessageW(
-                        HWND(app_info.hwnd as *mut _),
-                        WM_SYSCOMMAND,
-                        WPARAM(SC_RESTORE as usize),
-                        LPARAM(0),
-                    );
-                    if result == LRESULT(0) {
-                        info!("ウィンドウを復元しました。 (返り値: {:?})", result);
-                    } else {
-                        error!("ウィンドウの復元に失敗しました。: {:?}", result);
+            let detected = Instant::now();
+            let event = NotificationEvent::icon_changed(app_info.clone(), comparison.diff_score)
+                .with_captured_icon(&current_image)
+                .with_baseline_icon(&initial_image);
+            let names = router.route(&app_info).map(|(rule, names)| {
+                info!(
+                    "ルーティングルール「{}」により通知先を選択しました: {:?}",
+                    rule.name, names
+                );
+                names
+            });
+            deliver(
+                &app_handle,
+                &reporter,
+                &notifiers,
+                &event,
+                names.as_deref(),
+                settings.threshold,
+            )
+            .await;
+
+            if let Some(policy) = &settings.escalation {
+                let context = EscalationContext {
+                    app_handle: &app_handle,
+                    reporter: &reporter,
+                    notifiers: &notifiers,
+                    event: &event,
+                    names: names.as_deref(),
+                    threshold: settings.threshold,
+                };
+                let wait =
+                    await_acknowledgement(&context, &settings, policy, &initial_image, detected)
+                        .await;
+                let acknowledged_by = match wait {
+                    AcknowledgeWait::Acknowledged(source) => {
+                        info!("検知した変化が確認されました: {:?}", source);
+                        Some(source)
+                    }
+                    AcknowledgeWait::Exhausted => {
+                        info!("すべてのエスカレーションを行いました。");
+                        None
+                    }
+                    AcknowledgeWait::WindowLost => {
+                        warn!("監視対象のウィンドウが見つかりません。監視を終了します。");
+                        report_lost(&reporter).await;
                         return;
                     }
-                }
+                };
+                reporter
+                    .update(|status| {
+                        status.state = TargetState::Triggered;
+                        status.next_escalation_at = None;
+                        status.acknowledged_by = acknowledged_by;
+                    })
+                    .await;
+            }
+
+            if settings.minimize_on_start && !restore_window(app_info.hwnd) {
+                return;
             }
 
-            match app_handle.emit_all("monitoring_stopped", ()) {
-                Ok(_) => info!("monitoring_stoppedイベントを送信しました。"),
-                Err(e) => error!("monitoring_stoppedイベントの送信に失敗しました: {:?}", e),
+            // 他の監視対象の監視は続けるため、この監視対象の情報だけを通知します。
+            match app_handle.emit_all("monitor_target_stopped", &app_info) {
+                Ok(_) => info!("monitor_target_stoppedイベントを送信しました。"),
+                Err(e) => error!(
+                    "monitor_target_stoppedイベントの送信に失敗しました: {:?}",
+                    e
+                ),
             }
             break;
         }
         info!("アイコンに変化はありませんでした。");
     }
 }
+
+/// 検知イベントを通知先へ送信し、送信結果を報告して配信履歴へ記録します。
+///
+/// `names` を指定した場合は、その通知先とルーティングの対象外の通知先だけへ送信します。
+async fn deliver(
+    app_handle: &tauri::AppHandle,
+    reporter: &StatusReporter,
+    notifiers: &NotifierRegistry,
+    event: &NotificationEvent,
+    names: Option<&[String]>,
+    threshold: f32,
+) {
+    let results = match names {
+        Some(names) => notifiers.notify_routed(event, names).await,
+        None => notifiers.notify_all(event).await,
+    };
+    report_permanent_failures(app_handle, &event.app, &results);
+    app_handle
+        .state::<NotificationQueue>()
+        .enqueue_undelivered(
+            notifiers,
+            &app_handle.state::<DeliveryHistory>(),
+            event,
+            &results,
+        )
+        .await;
+    let kind = match event.escalated_after_minutes {
+        Some(_) => HistoryKind::Escalation,
+        None => HistoryKind::Detection,
+    };
+    app_handle
+        .state::<DeliveryHistory>()
+        .record(HistoryEntry::new(
+            kind,
+            event,
+            Some(threshold),
+            results.clone(),
+        ))
+        .await;
+    reporter
+        .update(|status| status.last_delivery = results)
+        .await;
+}
+
+/// エスカレーションで通知を送信するために必要な値。
+///
+/// # フィールド
+/// - `event`: 最初に送信した検知イベント。
+/// - `names`: 最初の通知でルーティングルールにより選択した通知先。`None` の場合はすべての通知先。
+/// - `threshold`: 配信履歴へ記録する画像差分しきい値。
+///
+struct EscalationContext<'a> {
+    app_handle: &'a tauri::AppHandle,
+    reporter: &'a StatusReporter,
+    notifiers: &'a NotifierRegistry,
+    event: &'a NotificationEvent,
+    names: Option<&'a [String]>,
+    threshold: f32,
+}
+
+/// 検知した変化が確認されるまで待機し、確認されないまま時間が経過した段階のエスカレーションを行います。
+///
+/// 監視間隔ごとに、確認ボタンが押されたか、ウィンドウが元のサイズへ戻された・前面に表示されたか、
+/// アイコンが監視開始時の状態へ戻ったかを確認します。
+/// 監視開始時に最小化したウィンドウは、ウィンドウの操作を確認として判定できるよう、確認されるまで復元しません。
+async fn await_acknowledgement(
+    context: &EscalationContext<'_>,
+    settings: &MonitorSettings,
+    policy: &EscalationPolicy,
+    initial_image: &image::DynamicImage,
+    detected: Instant,
+) -> AcknowledgeWait {
+    let hwnd = context.event.app.hwnd;
+    let steps = policy.sorted_steps();
+    let mut next_step = 0;
+    let mut activity = window_activity(hwnd);
+    let mut icon_restored_since: Option<Instant> = None;
+    let next_escalation_at = |step: Option<&EscalationStep>| {
+        step.map(|step| {
+            context.event.detected_at + chrono::Duration::minutes(step.after_minutes as i64)
+        })
+    };
+    context
+        .reporter
+        .update(|status| {
+            status.state = TargetState::Unacknowledged;
+            status.next_escalation_at = next_escalation_at(steps.first());
+        })
+        .await;
+
+    loop {
+        sleep(Duration::from_millis(settings.interval)).await;
+
+        if context.reporter.is_acknowledged() {
+            return AcknowledgeWait::Acknowledged(AcknowledgeSource::Button);
+        }
+        if is_window_lost(hwnd) {
+            return AcknowledgeWait::WindowLost;
+        }
+        let current = window_activity(hwnd);
+        if current.activated_since(&activity) {
+            return AcknowledgeWait::Acknowledged(AcknowledgeSource::WindowActivated);
+        }
+        activity = current;
+        if let Some(image) = capture_icon_image(HWND(hwnd as *mut _)) {
+            let comparison = compare_images(
+                initial_image,
+                &image,
+                settings.threshold,
+                &settings.detector,
+            );
+            if comparison.is_significant {
+                icon_restored_since = None;
+            } else {
+                let since = *icon_restored_since.get_or_insert_with(Instant::now);
+                if since.elapsed() >= ICON_RESTORED_DURATION {
+                    return AcknowledgeWait::Acknowledged(AcknowledgeSource::IconRestored);
+                }
+            }
+        }
+
+        let mut escalated = false;
+        while let Some(step) = steps.get(next_step) {
+            if detected.elapsed() < Duration::from_secs(step.after_minutes * 60) {
+                break;
+            }
+            escalate(context, step).await;
+            next_step += 1;
+            escalated = true;
+        }
+        if next_step >= steps.len() {
+            return AcknowledgeWait::Exhausted;
+        }
+        if escalated {
+            context
+                .reporter
+                .update(|status| {
+                    status.next_escalation_at = next_escalation_at(steps.get(next_step))
+                })
+                .await;
+        }
+    }
+}
+
+/// エスカレーションの 1 段階の操作を行います。
+async fn escalate(context: &EscalationContext<'_>, step: &EscalationStep) {
+    info!(
+        "検知から{}分経過しても確認されていないため、エスカレーションします: {:?}",
+        step.after_minutes, step.action
+    );
+    let event = step.escalated_event(context.event);
+    let names: Option<Vec<String>> = match &step.action {
+        EscalationAction::Notify { notifiers } => Some(
+            notifiers
+                .iter()
+                .map(|notifier| notifier.trim().to_string())
+                .filter(|notifier| !notifier.is_empty())
+                .collect(),
+        ),
+        EscalationAction::Resend | EscalationAction::RaisePriority => {
+            context.names.map(<[String]>::to_vec)
+        }
+    };
+    deliver(
+        context.app_handle,
+        context.reporter,
+        context.notifiers,
+        &event,
+        names.as_deref(),
+        context.threshold,
+    )
+    .await;
+}
+
+/// 監視対象のウィンドウの表示状態を取得します。
+fn window_activity(hwnd: isize) -> WindowActivity {
+    unsafe {
+        WindowActivity {
+            minimized: IsIconic(HWND(hwnd as *mut _)).as_bool(),
+            foreground: GetForegroundWindow() == HWND(hwnd as *mut _),
+        }
+    }
+}
+
+/// 恒久的な失敗となった通知先がある場合、`notification_delivery_failed` イベントを発行します。
+fn report_permanent_failures(
+    app_handle: &tauri::AppHandle,
+    app_info: &AppInfo,
+    results: &[DeliveryResult],
+) {
+    let failures: Vec<DeliveryResult> = results
+        .iter()
+        .filter(|result| {
+            matches!(
+                result.result,
+                DeliveryOutcome::Failed {
+                    permanent: true,
+                    ..
+                }
+            )
+        })
+        .cloned()
+        .collect();
+    if failures.is_empty() {
+        return;
+    }
+    let report = DeliveryFailureReport {
+        app: app_info.clone(),
+        failures,
+    };
+    match app_handle.emit_all("notification_delivery_failed", report) {
+        Ok(_) => info!("notification_delivery_failedイベントを送信しました。"),
+        Err(e) => error!(
+            "notification_delivery_failedイベントの送信に失敗しました: {:?}",
+            e
+        ),
+    }
+}
+
+/// 監視対象のウィンドウが存在しなくなったかどうかを返します。
+fn is_window_lost(hwnd: isize) -> bool {
+    unsafe { !IsWindow(HWND(hwnd as *mut _)).as_bool() }
+}
+
+async fn report_lost(reporter: &StatusReporter) {
+    reporter
+        .update(|status| status.state = TargetState::Lost)
+        .await;
+}
+
+async fn report_error(reporter: &StatusReporter, message: &str) {
+    reporter
+        .update(|status| {
+            status.state = TargetState::Error;
+            status.error = Some(message.to_string());
+        })
+        .await;
+}
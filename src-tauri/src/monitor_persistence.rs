@@ -0,0 +1,298 @@
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+use tauri::{async_runtime::Mutex, Manager};
+
+use crate::window_utils::{AppInfo, ConfigState, MonitorState};
+
+/// 監視対象の一覧を保存するファイル名。設定ファイルと同じフォルダに作成されます。
+pub const MONITORED_TARGETS_FILE_NAME: &str = "monitored_targets.json";
+
+/// 再起動後に監視を再開するために保存する監視対象の情報。
+///
+/// # フィールド
+/// - `identity`: 監視対象の識別キー（`AppInfo::identity`）。
+/// - `name`: 保存時のウィンドウタイトル。
+/// - `executable`: 保存時の実行ファイルのフルパス。
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PersistedTarget {
+    pub identity: String,
+    pub name: String,
+    pub executable: Option<String>,
+}
+
+impl PersistedTarget {
+    pub fn from_app_info(app: &AppInfo) -> Self {
+        Self {
+            identity: app.identity(),
+            name: app.name.clone(),
+            executable: app.executable.clone(),
+        }
+    }
+}
+
+/// 起動時の監視再開の結果。
+///
+/// # フィールド
+/// - `resumed`: 監視を再開したアプリケーション。
+/// - `missing`: ウィンドウが見つからず、監視を再開できなかった監視対象。
+/// - `failed`: ウィンドウは見つかったが、設定値の検証などに失敗して監視を再開できなかった監視対象。
+///
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ResumeReport {
+    pub resumed: Vec<AppInfo>,
+    pub missing: Vec<PersistedTarget>,
+    pub failed: Vec<PersistedTarget>,
+}
+
+/// 直近の起動時の監視再開の結果を保持する構造体。
+///
+/// 起動時の監視再開はフロントエンドの初期化前に完了することがあるため、
+/// `monitoring_resumed` イベントに加えて `get_resume_report` コマンドでも取得できるように保持します。
+///
+pub struct ResumeReportState {
+    pub report: Mutex<Option<ResumeReport>>,
+}
+
+impl ResumeReportState {
+    pub fn new() -> Self {
+        Self {
+            report: Mutex::new(None),
+        }
+    }
+}
+
+/// 監視対象の一覧をファイルへ保存します。
+///
+/// 監視対象が空の場合も空の一覧を保存し、次回起動時に監視が再開されないようにします。
+pub fn save_monitored_targets(path: &Path, apps: &[AppInfo]) {
+    let targets: Vec<PersistedTarget> = apps.iter().map(PersistedTarget::from_app_info).collect();
+    let content = match serde_json::to_string_pretty(&targets) {
+        Ok(content) => content,
+        Err(e) => {
+            error!("監視対象の一覧のシリアライズに失敗しました: {:?}", e);
+            return;
+        }
+    };
+    match fs::write(path, content) {
+        Ok(_) => info!("監視対象の一覧を保存しました。({}件)", targets.len()),
+        Err(e) => error!("監視対象の一覧の保存に失敗しました: {:?}", e),
+    }
+}
+
+/// 保存されている監視対象の一覧を読み込みます。
+///
+/// # 戻り値
+/// * `Ok(Vec<PersistedTarget>)` - 保存されている監視対象。ファイルが存在しない場合は空。
+/// * `Err(String)` - ファイルの読み込み、または JSON 解析に失敗した場合。
+pub fn load_monitored_targets(path: &Path) -> Result<Vec<PersistedTarget>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("read error: {:?}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("parse error: {:?}", e))
+}
+
+/// 保存されている監視対象を、現在のタスクバー上のウィンドウと照合します。
+///
+/// # 概要
+/// 1. 識別キー（実行ファイル名とウィンドウタイトル）が一致するウィンドウを探します。
+/// 2. 見つからない場合、同じ実行ファイルのウィンドウが 1 つだけであればそのウィンドウを使用します。
+///    未読件数などでウィンドウタイトルが変わるアプリケーションに対応するためです。
+///
+/// 1 つのウィンドウが複数の監視対象に割り当てられることはありません。
+///
+/// # 戻り値
+/// 照合できたアプリケーションと、照合できなかった監視対象のタプル。
+pub fn resolve_targets(
+    persisted: &[PersistedTarget],
+    available: &[AppInfo],
+) -> (Vec<AppInfo>, Vec<PersistedTarget>) {
+    let mut used = vec![false; available.len()];
+    let mut resolved = Vec::new();
+    let mut missing = Vec::new();
+
+    for target in persisted {
+        let exact = available
+            .iter()
+            .enumerate()
+            .find(|(i, app)| !used[*i] && app.identity() == target.identity)
+            .map(|(i, _)| i);
+        let found = exact.or_else(|| {
+            let executable = target.executable.as_deref()?;
+            let candidates: Vec<usize> = available
+                .iter()
+                .enumerate()
+                .filter(|(i, app)| !used[*i] && app.executable.as_deref() == Some(executable))
+                .map(|(i, _)| i)
+                .collect();
+            match candidates.as_slice() {
+                [only] => Some(*only),
+                _ => None,
+            }
+        });
+
+        match found {
+            Some(i) => {
+                used[i] = true;
+                resolved.push(available[i].clone());
+            }
+            None => missing.push(target.clone()),
+        }
+    }
+
+    (resolved, missing)
+}
+
+/// 前回終了時の監視対象の監視を再開します。
+///
+/// # 概要
+/// `RESUME_MONITORING_ON_STARTUP` が有効な場合に、起動時のセットアップから呼び出されます。
+/// 保存されている監視対象を現在のウィンドウと照合し、見つかったものの監視を開始します。
+/// 結果は `ResumeReportState` に保持し、`monitoring_resumed` イベントでフロントエンドへ通知します。
+pub async fn resume_monitoring(app_handle: tauri::AppHandle) {
+    let config_state = app_handle.state::<ConfigState>();
+    let monitor_state = app_handle.state::<MonitorState>();
+
+    match crate::config_manager::get_app_config(config_state.clone()).await {
+        Ok(config) if config.resume_monitoring_on_startup => {}
+        Ok(_) => {
+            info!("起動時の監視再開は無効です。");
+            return;
+        }
+        Err(e) => {
+            error!("起動時の監視再開設定の取得に失敗しました: {}", e);
+            return;
+        }
+    }
+
+    let targets_file = match monitor_state.targets_file() {
+        Some(path) => path,
+        None => {
+            warn!("監視対象の一覧の保存先が見つからないため、監視を再開できません。");
+            return;
+        }
+    };
+    let persisted = match load_monitored_targets(&targets_file) {
+        Ok(persisted) => persisted,
+        Err(e) => {
+            error!("監視対象の一覧の読み込みに失敗しました: {}", e);
+            return;
+        }
+    };
+    if persisted.is_empty() {
+        info!("再開する監視対象はありません。");
+        return;
+    }
+
+    let available = crate::window_collection::get_taskbar_apps();
+    let (resolved, missing) = resolve_targets(&persisted, &available);
+    for target in &missing {
+        warn!(
+            "監視対象：{:?}のウィンドウが見つからないため、監視を再開できませんでした。",
+            target.name
+        );
+    }
+
+    let mut report = ResumeReport {
+        missing,
+        ..ResumeReport::default()
+    };
+    for app in resolved {
+        match crate::commands::load_monitor_settings(config_state.clone(), &app).await {
+            Ok(settings) => {
+                monitor_state
+                    .monitor_target(app.clone(), settings, config_state.clone())
+                    .await;
+                report.resumed.push(app);
+            }
+            Err(e) => {
+                error!(
+                    "監視対象：{:?}の監視設定の読み込みに失敗しました: {}",
+                    app.name, e
+                );
+                report.failed.push(PersistedTarget::from_app_info(&app));
+            }
+        }
+    }
+    info!(
+        "監視を再開しました。(再開: {}件、未検出: {}件、失敗: {}件)",
+        report.resumed.len(),
+        report.missing.len(),
+        report.failed.len()
+    );
+
+    *app_handle.state::<ResumeReportState>().report.lock().await = Some(report.clone());
+    if let Err(e) = app_handle.emit_all("monitoring_resumed", report) {
+        error!("monitoring_resumedイベントの送信に失敗しました: {:?}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resolve_targets, PersistedTarget};
+    use crate::window_utils::AppInfo;
+
+    fn app(name: &str, hwnd: isize, executable: &str) -> AppInfo {
+        AppInfo {
+            name: name.to_string(),
+            hwnd,
+            process_id: 0,
+            thread_id: 0,
+            icon: None,
+            executable: Some(executable.to_string()),
+        }
+    }
+
+    #[test]
+    fn resolve_targets_matches_by_identity() {
+        let available = vec![
+            app("Build", 1, "C:\\tools\\ci.exe"),
+            app("Chat", 2, "C:\\apps\\chat.exe"),
+        ];
+        let persisted = vec![PersistedTarget::from_app_info(&app(
+            "Chat",
+            99,
+            "C:\\apps\\chat.exe",
+        ))];
+
+        let (resolved, missing) = resolve_targets(&persisted, &available);
+
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].hwnd, 2);
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn resolve_targets_falls_back_to_unique_executable() {
+        let available = vec![app("Chat (3)", 2, "C:\\apps\\chat.exe")];
+        let persisted = vec![PersistedTarget::from_app_info(&app(
+            "Chat",
+            99,
+            "C:\\apps\\chat.exe",
+        ))];
+
+        let (resolved, missing) = resolve_targets(&persisted, &available);
+
+        assert_eq!(resolved.len(), 1);
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn resolve_targets_reports_ambiguous_and_missing_targets() {
+        let available = vec![
+            app("Editor - a.txt", 1, "C:\\apps\\editor.exe"),
+            app("Editor - b.txt", 2, "C:\\apps\\editor.exe"),
+        ];
+        let persisted = vec![
+            PersistedTarget::from_app_info(&app("Editor - c.txt", 9, "C:\\apps\\editor.exe")),
+            PersistedTarget::from_app_info(&app("Mail", 8, "C:\\apps\\mail.exe")),
+        ];
+
+        let (resolved, missing) = resolve_targets(&persisted, &available);
+
+        assert!(resolved.is_empty());
+        assert_eq!(missing.len(), 2);
+    }
+}
@@ -0,0 +1,562 @@
+use base64::{engine::general_purpose, Engine};
+use chrono::{DateTime, Duration as ChronoDuration, Local};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::PathBuf,
+    time::Duration,
+};
+use tauri::{async_runtime::Mutex, Manager};
+use tokio::time::sleep;
+
+use crate::delivery_history::{DeliveryHistory, HistoryEntry, HistoryKind};
+use crate::notifier::{DeliveryOutcome, DeliveryResult, NotificationEvent, NotifierRegistry};
+use crate::window_utils::ConfigState;
+
+/// 送信待ちキューを保存するファイル名。アプリのデータフォルダに作成されます。
+pub const NOTIFICATION_QUEUE_FILE_NAME: &str = "notification_queue.json";
+
+/// 送信待ちキューの通知を再送する間隔。
+const FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 通知先ごとに保存する送信待ちの通知の上限。超えた場合は古い通知から破棄します。
+const MAX_QUEUED_PER_NOTIFIER: usize = 100;
+
+/// 送信待ちの通知を保持する時間（時間単位）。キューへ保存してからこれを過ぎた通知は送信せずに破棄します。
+const QUEUE_TTL_HOURS: i64 = 24;
+
+/// 送信待ちキューに保存した通知。
+///
+/// # フィールド
+/// - `id`: キュー内で通知を識別する ID。保存した順に大きくなります。
+/// - `notifier`: 送信先の通知先の名前（`Notifier::name`）。
+/// - `event`: 検知イベント。`detected_at` は元の検知時刻のまま保存します。
+/// - `captured_icon_base64`: 検知時にキャプチャしたアイコン画像（PNG）の Base64。
+/// - `baseline_icon_base64`: 監視開始時にキャプチャした基準のアイコン画像（PNG）の Base64。
+/// - `queued_at`: キューへ保存した時刻。
+/// - `attempts`: これまでに送信を試みた回数。
+/// - `last_error`: 最後に送信に失敗した際のエラー内容。
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QueuedNotification {
+    pub id: u64,
+    pub notifier: String,
+    pub event: NotificationEvent,
+    #[serde(default)]
+    pub captured_icon_base64: Option<String>,
+    #[serde(default)]
+    pub baseline_icon_base64: Option<String>,
+    pub queued_at: DateTime<Local>,
+    pub attempts: u32,
+    pub last_error: String,
+}
+
+impl QueuedNotification {
+    /// 送信に使用する検知イベントを返します。遅れて送信する通知として扱います。
+    fn delayed_event(&self) -> NotificationEvent {
+        let mut event = self.event.clone();
+        event.delayed = true;
+        event.captured_icon_png = decode_icon(&self.captured_icon_base64);
+        event.baseline_icon_png = decode_icon(&self.baseline_icon_base64);
+        event
+    }
+}
+
+/// オフラインなどで送信できなかった通知を保存する送信待ちキュー。
+///
+/// # 概要
+/// 一時的な失敗（通信エラー、サーバーエラー、レート制限）で再送しても送信できなかった通知を、
+/// 通知先ごとに保存した順でファイルへ保存します。`run_queue_worker` が定期的に送信を試み、
+/// 送信できた通知、または恒久的な失敗となった通知をキューから取り除きます。
+///
+/// 長時間オフラインが続いた場合にキューが際限なく大きくならないよう、通知先ごとの件数の上限
+/// （`MAX_QUEUED_PER_NOTIFIER`）を超えた古い通知と、保持する時間（`QUEUE_TTL_HOURS`）を過ぎた通知は
+/// 送信せずに破棄し、配信履歴へ記録します。
+///
+/// # フィールド
+/// - `path`: キューを保存するファイルのパス。`None` の場合はメモリ上にのみ保持します。
+/// - `entries`: 送信待ちの通知。保存した順に並びます。
+///
+pub struct NotificationQueue {
+    path: Option<PathBuf>,
+    entries: Mutex<Vec<QueuedNotification>>,
+}
+
+impl NotificationQueue {
+    /// ファイルから送信待ちキューを読み込みます。ファイルが存在しない、または読み込めない場合は空のキューを返します。
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let entries = match &path {
+            Some(path) if path.exists() => match fs::read_to_string(path)
+                .map_err(|e| format!("{:?}", e))
+                .and_then(|content| {
+                    serde_json::from_str::<Vec<QueuedNotification>>(&content)
+                        .map_err(|e| format!("{:?}", e))
+                }) {
+                Ok(entries) => {
+                    info!("送信待ちキューを読み込みました。({}件)", entries.len());
+                    entries
+                }
+                Err(e) => {
+                    warn!("送信待ちキューの読み込みに失敗しました: {}", e);
+                    Vec::new()
+                }
+            },
+            _ => Vec::new(),
+        };
+        Self {
+            path,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// 送信待ちの通知の一覧を返します。
+    pub async fn entries(&self) -> Vec<QueuedNotification> {
+        self.entries.lock().await.clone()
+    }
+
+    /// 一時的な失敗で送信できなかった通知をキューへ保存します。
+    ///
+    /// 恒久的な失敗や、`Notifier::queues_when_undelivered` が `false` の通知先の結果は保存しません。
+    /// 通知先ごとの件数が上限を超えた場合は、古い通知から破棄して配信履歴へ記録します。
+    pub async fn enqueue_undelivered(
+        &self,
+        registry: &NotifierRegistry,
+        history: &DeliveryHistory,
+        event: &NotificationEvent,
+        results: &[DeliveryResult],
+    ) {
+        let undelivered: Vec<&DeliveryResult> = results
+            .iter()
+            .filter(|result| result.result.is_retryable())
+            .filter(|result| {
+                registry
+                    .get(&result.notifier)
+                    .map(|notifier| notifier.queues_when_undelivered())
+                    .unwrap_or(false)
+            })
+            .collect();
+        if undelivered.is_empty() {
+            return;
+        }
+
+        let mut entries = self.entries.lock().await;
+        let first_id = entries.last().map(|entry| entry.id + 1).unwrap_or(0);
+        for (id, result) in (first_id..).zip(undelivered) {
+            info!(
+                "{}への通知を送信待ちキューへ保存しました。",
+                result.notifier
+            );
+            entries.push(QueuedNotification {
+                id,
+                notifier: result.notifier.clone(),
+                event: event.clone(),
+                captured_icon_base64: encode_icon(&event.captured_icon_png),
+                baseline_icon_base64: encode_icon(&event.baseline_icon_png),
+                queued_at: Local::now(),
+                attempts: result.attempts,
+                last_error: error_message(&result.result),
+            });
+        }
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for entry in entries.iter() {
+            *counts.entry(entry.notifier.clone()).or_default() += 1;
+        }
+        let mut overflowed = Vec::new();
+        entries.retain(|entry| {
+            let count = counts.get_mut(&entry.notifier).unwrap();
+            if *count <= MAX_QUEUED_PER_NOTIFIER {
+                return true;
+            }
+            *count -= 1;
+            overflowed.push(entry.clone());
+            false
+        });
+        self.save(&entries);
+        drop(entries);
+        record_discarded(history, overflowed, "送信待ちの通知が上限を超えました").await;
+    }
+
+    /// 保持する時間（`QUEUE_TTL_HOURS`）を過ぎた通知をキューから取り除き、配信履歴へ記録します。
+    async fn remove_expired(&self, history: &DeliveryHistory) {
+        let cutoff = Local::now() - ChronoDuration::hours(QUEUE_TTL_HOURS);
+        let mut entries = self.entries.lock().await;
+        let (expired, kept): (Vec<_>, Vec<_>) = entries
+            .drain(..)
+            .partition(|entry| entry.queued_at < cutoff);
+        *entries = kept;
+        if expired.is_empty() {
+            return;
+        }
+        self.save(&entries);
+        drop(entries);
+        record_discarded(history, expired, "送信待ちの期限を過ぎました").await;
+    }
+
+    /// 送信待ちの通知を保存した順に送信し、送信できた件数を返します。
+    ///
+    /// 通知先ごとの順序を保つため、一時的な失敗となった通知先の以降の通知は次回まで送信しません。
+    /// 恒久的な失敗となった通知と、通知先の設定が削除された通知はキューから取り除きます。
+    /// 送信中も監視タスクがキューへ保存できるよう、送信中はキューをロックしません。
+    ///
+    /// キューから取り除いた通知の送信結果は配信履歴へ記録します。
+    /// 一時的な失敗でキューに残る通知は、再送のたびに記録しないよう、送信待ちキューの `last_error` にのみ残します。
+    /// 送信の前に、保持する時間を過ぎた通知を破棄します。
+    pub async fn flush(&self, registry: &NotifierRegistry, history: &DeliveryHistory) -> usize {
+        self.remove_expired(history).await;
+        let pending = self.entries().await;
+        if pending.is_empty() {
+            return 0;
+        }
+
+        let mut blocked: HashSet<String> = HashSet::new();
+        let mut removed: HashSet<u64> = HashSet::new();
+        let mut failed: HashMap<u64, String> = HashMap::new();
+        let mut delivered = 0;
+        for entry in pending {
+            if blocked.contains(&entry.notifier) {
+                continue;
+            }
+            let notifier = match registry.get(&entry.notifier) {
+                Some(notifier) => notifier,
+                None => {
+                    warn!(
+                        "通知先 {} が設定されていないため、送信待ちの通知を破棄します。",
+                        entry.notifier
+                    );
+                    removed.insert(entry.id);
+                    continue;
+                }
+            };
+            let event = entry.delayed_event();
+            let outcome = notifier.send(&event).await;
+            match &outcome {
+                DeliveryOutcome::Sent => {
+                    info!("{}へ送信待ちの通知を送信しました。", entry.notifier);
+                    removed.insert(entry.id);
+                    delivered += 1;
+                }
+                outcome if outcome.is_retryable() => {
+                    failed.insert(entry.id, error_message(outcome));
+                    blocked.insert(entry.notifier);
+                    continue;
+                }
+                outcome => {
+                    error!(
+                        "{}への送信待ちの通知を送信できないため破棄します: {}",
+                        entry.notifier,
+                        error_message(outcome)
+                    );
+                    removed.insert(entry.id);
+                }
+            }
+            let result = DeliveryResult {
+                notifier: entry.notifier.clone(),
+                delivered_at: Local::now(),
+                attempts: entry.attempts + 1,
+                result: outcome,
+            };
+            history
+                .record(HistoryEntry::new(
+                    HistoryKind::Queued,
+                    &event,
+                    None,
+                    vec![result],
+                ))
+                .await;
+        }
+
+        let mut entries = self.entries.lock().await;
+        entries.retain(|entry| !removed.contains(&entry.id));
+        for entry in entries.iter_mut() {
+            if let Some(last_error) = failed.remove(&entry.id) {
+                entry.attempts += 1;
+                entry.last_error = last_error;
+            }
+        }
+        self.save(&entries);
+        delivered
+    }
+
+    fn save(&self, entries: &[QueuedNotification]) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+        let content = match serde_json::to_string_pretty(entries) {
+            Ok(content) => content,
+            Err(e) => {
+                error!("送信待ちキューのシリアライズに失敗しました: {:?}", e);
+                return;
+            }
+        };
+        if let Err(e) = fs::write(path, content) {
+            error!("送信待ちキューの保存に失敗しました: {:?}", e);
+        }
+    }
+}
+
+fn encode_icon(png: &Option<Vec<u8>>) -> Option<String> {
+    png.as_ref()
+        .map(|png| general_purpose::STANDARD.encode(png))
+}
+
+fn decode_icon(icon: &Option<String>) -> Option<Vec<u8>> {
+    icon.as_deref()
+        .and_then(|icon| general_purpose::STANDARD.decode(icon).ok())
+}
+
+/// 送信せずに破棄した送信待ちの通知を、恒久的な失敗として配信履歴へ記録します。
+async fn record_discarded(
+    history: &DeliveryHistory,
+    discarded: Vec<QueuedNotification>,
+    message: &str,
+) {
+    for entry in discarded {
+        warn!(
+            "{}への送信待ちの通知を破棄しました（{}）: {}",
+            entry.notifier, message, entry.event.app.name
+        );
+        let result = DeliveryResult {
+            notifier: entry.notifier,
+            delivered_at: Local::now(),
+            attempts: entry.attempts,
+            result: DeliveryOutcome::Failed {
+                status: None,
+                message: message.to_string(),
+                permanent: true,
+            },
+        };
+        history
+            .record(HistoryEntry::new(
+                HistoryKind::Queued,
+                &entry.event,
+                None,
+                vec![result],
+            ))
+            .await;
+    }
+}
+
+/// 送信結果からエラー内容を取得します。
+fn error_message(outcome: &DeliveryOutcome) -> String {
+    match outcome {
+        DeliveryOutcome::Sent => String::new(),
+        DeliveryOutcome::Failed {
+            status: Some(status),
+            message,
+            ..
+        } => format!("{} {}", status, message),
+        DeliveryOutcome::Failed { message, .. } | DeliveryOutcome::RateLimited { message, .. } => {
+            message.clone()
+        }
+    }
+}
+
+/// 送信待ちキューの通知を定期的に送信する非同期関数。
+///
+/// アプリの起動時に開始し、`FLUSH_INTERVAL` ごとに設定ファイルから通知先を構築して送信を試みます。
+/// 送信待ちの通知がない場合は設定ファイルを読み込みません。
+pub async fn run_queue_worker(app_handle: tauri::AppHandle) {
+    loop {
+        sleep(FLUSH_INTERVAL).await;
+        let queue = app_handle.state::<NotificationQueue>();
+        if queue.entries.lock().await.is_empty() {
+            continue;
+        }
+        let config_path = match app_handle.try_state::<ConfigState>() {
+            Some(config_state) => config_state.path.clone(),
+            None => continue,
+        };
+        let registry = match NotifierRegistry::load(&config_path) {
+            Ok(registry) => registry,
+            Err(e) => {
+                error!("通知先の読み込みに失敗しました: {}", e);
+                continue;
+            }
+        };
+        let delivered = queue
+            .flush(&registry, &app_handle.state::<DeliveryHistory>())
+            .await;
+        if delivered > 0 {
+            info!("送信待ちの通知を{}件送信しました。", delivered);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NotificationQueue, MAX_QUEUED_PER_NOTIFIER, QUEUE_TTL_HOURS};
+    use crate::delivery_history::{DeliveryHistory, HistoryFilter, HistoryKind};
+    use crate::notifier::{DeliveryOutcome, DeliveryResult, NotificationEvent, NotifierRegistry};
+    use crate::test_support::{sample_app, ScriptedNotifier};
+    use crate::window_utils::AppInfo;
+    use chrono::{Duration, Local};
+    use std::sync::{Arc, Mutex};
+
+    fn registry(
+        name: &'static str,
+        outcomes: Vec<DeliveryOutcome>,
+    ) -> (NotifierRegistry, Arc<Mutex<Vec<NotificationEvent>>>) {
+        let notifier = ScriptedNotifier::new(name, outcomes);
+        let received = notifier.received.clone();
+        let mut registry = NotifierRegistry::default();
+        registry.register(Box::new(notifier));
+        (registry, received)
+    }
+
+    fn event(name: &str) -> NotificationEvent {
+        let app = AppInfo {
+            name: name.to_string(),
+            ..sample_app()
+        };
+        NotificationEvent::icon_changed(app, 0.2)
+    }
+
+    fn result(notifier: &str, outcome: DeliveryOutcome) -> DeliveryResult {
+        DeliveryResult {
+            notifier: notifier.to_string(),
+            delivered_at: Local::now(),
+            attempts: 3,
+            result: outcome,
+        }
+    }
+
+    #[tokio::test]
+    async fn only_transient_failures_are_queued() {
+        let (registry, _) = registry("discord", Vec::new());
+        let queue = NotificationQueue::load(None);
+
+        queue
+            .enqueue_undelivered(
+                &registry,
+                &DeliveryHistory::new(None),
+                &event("Chat"),
+                &[
+                    result("discord", DeliveryOutcome::failed(None, "offline")),
+                    result(
+                        "discord",
+                        DeliveryOutcome::failed(Some(401), "Unauthorized"),
+                    ),
+                    result("discord", DeliveryOutcome::Sent),
+                ],
+            )
+            .await;
+
+        let entries = queue.entries().await;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].last_error, "offline");
+        assert_eq!(entries[0].attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn flush_delivers_in_order_with_original_detection_time() {
+        let (registry, received) = registry(
+            "discord",
+            vec![
+                DeliveryOutcome::Sent,
+                DeliveryOutcome::failed(None, "offline"),
+            ],
+        );
+        let queue = NotificationQueue::load(None);
+        for name in ["First", "Second", "Third"] {
+            queue
+                .enqueue_undelivered(
+                    &registry,
+                    &DeliveryHistory::new(None),
+                    &event(name),
+                    &[result("discord", DeliveryOutcome::failed(None, "offline"))],
+                )
+                .await;
+        }
+        let first_detected_at = queue.entries().await[0].event.detected_at;
+
+        assert_eq!(queue.flush(&registry, &DeliveryHistory::new(None)).await, 1);
+
+        let received = received.lock().unwrap().clone();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].app.name, "First");
+        assert!(received[0].delayed);
+        assert_eq!(received[0].detected_at, first_detected_at);
+        assert_eq!(received[1].app.name, "Second");
+        let remaining: Vec<String> = queue
+            .entries()
+            .await
+            .into_iter()
+            .map(|entry| entry.event.app.name)
+            .collect();
+        assert_eq!(remaining, vec!["Second", "Third"]);
+    }
+
+    #[tokio::test]
+    async fn queue_is_restored_from_file() {
+        let path =
+            std::env::temp_dir().join(format!("flash-code-queue-test-{}.json", std::process::id()));
+        let (registry, _) = registry("discord", Vec::new());
+        let mut event = event("Chat");
+        event.captured_icon_png = Some(b"\x89PNG".to_vec());
+
+        NotificationQueue::load(Some(path.clone()))
+            .enqueue_undelivered(
+                &registry,
+                &DeliveryHistory::new(None),
+                &event,
+                &[result(
+                    "discord",
+                    DeliveryOutcome::failed(Some(503), "Service Unavailable"),
+                )],
+            )
+            .await;
+        let entries = NotificationQueue::load(Some(path.clone())).entries().await;
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].event.detected_at, event.detected_at);
+        assert_eq!(
+            entries[0].delayed_event().captured_icon_png,
+            Some(b"\x89PNG".to_vec())
+        );
+    }
+
+    #[tokio::test]
+    async fn overflowed_and_expired_notifications_are_discarded_and_recorded() {
+        let path = std::env::temp_dir().join(format!(
+            "flash-code-queue-history-test-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let history = DeliveryHistory::new(Some(path.clone()));
+        let (registry, received) = registry("discord", Vec::new());
+        let queue = NotificationQueue::load(None);
+        for i in 0..=MAX_QUEUED_PER_NOTIFIER {
+            queue
+                .enqueue_undelivered(
+                    &registry,
+                    &history,
+                    &event(&format!("App{}", i)),
+                    &[result("discord", DeliveryOutcome::failed(None, "offline"))],
+                )
+                .await;
+        }
+        let queued = queue.entries().await;
+        for entry in queue.entries.lock().await.iter_mut() {
+            entry.queued_at = Local::now() - Duration::hours(QUEUE_TTL_HOURS + 1);
+        }
+
+        assert_eq!(queue.flush(&registry, &history).await, 0);
+
+        let recorded = history.query(&HistoryFilter::default()).await.unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(queued.len(), MAX_QUEUED_PER_NOTIFIER);
+        assert_eq!(queued[0].event.app.name, "App1");
+        assert!(queue.entries().await.is_empty());
+        assert!(received.lock().unwrap().is_empty());
+        assert_eq!(recorded.len(), MAX_QUEUED_PER_NOTIFIER + 1);
+        assert_eq!(recorded.last().unwrap().app_name, "App0");
+        assert!(recorded
+            .iter()
+            .all(|entry| entry.kind == HistoryKind::Queued
+                && !entry.deliveries[0].result.is_retryable()));
+    }
+}
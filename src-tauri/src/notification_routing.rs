@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use crate::app_config::AppConfig;
+use crate::window_utils::AppInfo;
+
+/// ルーティングルールを保存する設定キー。
+pub const ROUTING_RULES_KEY: &str = "ROUTING_RULES";
+
+/// 監視対象ごとに通知先を振り分けるルール。
+///
+/// # 概要
+/// ウィンドウタイトルと実行ファイル名が条件に一致した監視対象の通知を、`notifiers` の通知先だけへ送信します。
+/// 条件はワイルドカード（`*` は任意の文字列、`?` は任意の 1 文字）で指定でき、大文字と小文字は区別しません。
+///
+/// # フィールド
+/// - `name`: ルールの表示名。
+/// - `title_pattern`: ウィンドウタイトルの条件。空の場合はすべてのタイトルに一致します。
+/// - `executable`: 実行ファイル名（例: `chrome.exe`）の条件。空の場合はすべての実行ファイルに一致します。
+/// - `notifiers`: 送信する通知先の名前（`Notifier::name`）。
+///
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RoutingRule {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub title_pattern: String,
+    #[serde(default)]
+    pub executable: String,
+    #[serde(default)]
+    pub notifiers: Vec<String>,
+}
+
+impl RoutingRule {
+    /// 監視対象がルールの条件に一致するかどうかを返します。
+    ///
+    /// タイトルと実行ファイル名のどちらの条件も空のルールは、どの監視対象にも一致しません。
+    pub fn matches(&self, app: &AppInfo) -> bool {
+        let title_pattern = self.title_pattern.trim();
+        let executable = self.executable.trim();
+        if title_pattern.is_empty() && executable.is_empty() {
+            return false;
+        }
+        let title_matches = title_pattern.is_empty() || wildcard_match(title_pattern, &app.name);
+        let executable_matches = executable.is_empty()
+            || app
+                .executable
+                .as_deref()
+                .and_then(|path| path.rsplit(['\\', '/']).next())
+                .map(|name| wildcard_match(executable, name))
+                .unwrap_or(false);
+        title_matches && executable_matches
+    }
+
+    /// ルールの設定値を検証します。
+    pub fn validate(&self) -> Result<(), String> {
+        let label = if self.name.trim().is_empty() {
+            "名前のないルール"
+        } else {
+            self.name.trim()
+        };
+        if self.title_pattern.trim().is_empty() && self.executable.trim().is_empty() {
+            return Err(format!(
+                "{}: ウィンドウタイトルまたは実行ファイル名の条件を指定してください。",
+                label
+            ));
+        }
+        if self
+            .notifiers
+            .iter()
+            .all(|notifier| notifier.trim().is_empty())
+        {
+            return Err(format!("{}: 通知先を 1 つ以上指定してください。", label));
+        }
+        Ok(())
+    }
+}
+
+/// 設定ファイルのルーティングルールの一覧。
+///
+/// ルールは上から順に評価し、最初に一致したルールの通知先へ送信します。
+/// どのルールにも一致しない監視対象は、従来どおり設定されているすべての通知先へ送信します。
+#[derive(Clone, Debug, Default)]
+pub struct NotificationRouter {
+    rules: Vec<RoutingRule>,
+}
+
+impl NotificationRouter {
+    /// 設定値からルーティングルールを読み込みます。形式が不正なルールや条件が不足しているルールは `AppConfig` の読み込み時に除かれます。
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            rules: config.routing_rules.clone(),
+        }
+    }
+
+    /// 監視対象の通知を送信する通知先の名前を返します。
+    ///
+    /// # 戻り値
+    /// * `Some((rule, notifiers))` - 最初に一致したルールと、その通知先。
+    /// * `None` - どのルールにも一致しない場合。すべての通知先へ送信します。
+    pub fn route(&self, app: &AppInfo) -> Option<(&RoutingRule, Vec<String>)> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(app))
+            .map(|rule| {
+                let notifiers = rule
+                    .notifiers
+                    .iter()
+                    .map(|notifier| notifier.trim().to_string())
+                    .filter(|notifier| !notifier.is_empty())
+                    .collect();
+                (rule, notifiers)
+            })
+    }
+}
+
+/// ワイルドカード（`*` と `?`）を含むパターンが文字列全体に一致するかどうかを、大文字と小文字を区別せずに返します。
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // 直前の `*` の位置と、その `*` に対応させ始めた文字列の位置。
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{wildcard_match, NotificationRouter};
+    use crate::test_support::{app_config, sample_app};
+    use crate::window_utils::AppInfo;
+    use serde_json::json;
+
+    fn app(name: &str, executable: &str) -> AppInfo {
+        AppInfo {
+            name: name.to_string(),
+            executable: Some(format!("C:\\Program Files\\App\\{}", executable)),
+            ..sample_app()
+        }
+    }
+
+    #[test]
+    fn wildcard_match_supports_star_and_question_mark() {
+        assert!(wildcard_match("*Jenkins*", "Build #12 - jenkins"));
+        assert!(wildcard_match("chat?.exe", "Chat2.exe"));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("*Jenkins", "Jenkins - Build"));
+        assert!(!wildcard_match("chat?.exe", "chat.exe"));
+    }
+
+    #[test]
+    fn first_matching_rule_selects_notifiers() {
+        let router = NotificationRouter::from_config(&app_config(json!({
+            "ROUTING_RULES": [
+                { "name": "Build", "title_pattern": "*Jenkins*", "notifiers": ["discord"] },
+                { "name": "Chat", "executable": "line.exe", "notifiers": ["line", " "] },
+                { "name": "Invalid", "notifiers": ["slack"] }
+            ]
+        })));
+
+        let (rule, notifiers) = router.route(&app("Jenkins - Build", "chrome.exe")).unwrap();
+        assert_eq!(rule.name, "Build");
+        assert_eq!(notifiers, vec!["discord"]);
+
+        let (_, notifiers) = router.route(&app("LINE", "LINE.EXE")).unwrap();
+        assert_eq!(notifiers, vec!["line"]);
+
+        assert!(router.route(&app("Editor", "code.exe")).is_none());
+    }
+
+    #[test]
+    fn title_and_executable_must_both_match() {
+        let router = NotificationRouter::from_config(&app_config(json!({
+            "ROUTING_RULES": [
+                { "title_pattern": "Inbox*", "executable": "outlook.exe", "notifiers": ["email"] }
+            ]
+        })));
+
+        assert!(router
+            .route(&app("Inbox - Outlook", "OUTLOOK.EXE"))
+            .is_some());
+        assert!(router
+            .route(&app("Inbox - Outlook", "chrome.exe"))
+            .is_none());
+    }
+}
@@ -0,0 +1,884 @@
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine};
+use chrono::{DateTime, Local};
+use futures::future::join_all;
+use image::{DynamicImage, ImageFormat};
+use log::{error, info, warn};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{io::Cursor, path::Path, time::Duration};
+
+use crate::app_config::AppConfig;
+use crate::command_notifier::CommandNotifier;
+use crate::delivery_retry::{send_with_retry, RetryPolicy};
+use crate::discord_notifier::DiscordNotifier;
+use crate::gotify_notifier::GotifyNotifier;
+use crate::line_notifier::LineNotifier;
+use crate::notifier_instance::{InstanceNotifier, NOTIFIER_KINDS};
+use crate::ntfy_notifier::NtfyNotifier;
+use crate::pushover_notifier::PushoverNotifier;
+use crate::slack_notifier::SlackNotifier;
+use crate::smtp_notifier::SmtpNotifier;
+use crate::teams_notifier::TeamsNotifier;
+use crate::telegram_notifier::TelegramNotifier;
+use crate::webhook_notifier::WebhookNotifier;
+use crate::window_utils::AppInfo;
+
+/// 通知のきっかけとなった事象の種類。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationTrigger {
+    /// タスクバーアイコンの変化を検知した。
+    IconChanged,
+}
+
+/// 各通知先へ送信する検知イベント。
+///
+/// # フィールド
+/// - `app`: 変化が検知されたアプリケーションの情報。
+/// - `trigger`: 通知のきっかけとなった事象の種類。
+/// - `detected_at`: 変化を検知した時刻。
+/// - `diff_score`: 初期画像との差分値（`0.0〜1.0`）。
+/// - `delayed`: オフラインなどで送信できず、送信待ちキューから遅れて送信する通知かどうか。
+/// - `escalated_after_minutes`: 確認されないまま経過した時間（分）。エスカレーションで送信する通知の場合のみ設定されます。
+/// - `raised_priority`: 優先度を上げて送信する通知かどうか。優先度を指定できる通知先（ntfy、Gotify、Pushover）で使用します。
+/// - `captured_icon_png`: 検知時にキャプチャしたタスクバーアイコンの PNG データ。
+/// - `baseline_icon_png`: 監視開始時にキャプチャした、比較の基準となるタスクバーアイコンの PNG データ。
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NotificationEvent {
+    pub app: AppInfo,
+    pub trigger: NotificationTrigger,
+    pub detected_at: DateTime<Local>,
+    pub diff_score: f32,
+    #[serde(default)]
+    pub delayed: bool,
+    #[serde(default)]
+    pub escalated_after_minutes: Option<u64>,
+    #[serde(default)]
+    pub raised_priority: bool,
+    #[serde(skip)]
+    pub captured_icon_png: Option<Vec<u8>>,
+    #[serde(skip)]
+    pub baseline_icon_png: Option<Vec<u8>>,
+}
+
+impl NotificationEvent {
+    pub fn icon_changed(app: AppInfo, diff_score: f32) -> Self {
+        Self {
+            app,
+            trigger: NotificationTrigger::IconChanged,
+            detected_at: Local::now(),
+            diff_score,
+            delayed: false,
+            escalated_after_minutes: None,
+            raised_priority: false,
+            captured_icon_png: None,
+            baseline_icon_png: None,
+        }
+    }
+
+    /// 検知時にキャプチャしたアイコン画像を PNG として添付します。
+    pub fn with_captured_icon(mut self, image: &DynamicImage) -> Self {
+        self.captured_icon_png = encode_png(image);
+        self
+    }
+
+    /// 監視開始時にキャプチャした基準のアイコン画像を PNG として添付します。
+    pub fn with_baseline_icon(mut self, image: &DynamicImage) -> Self {
+        self.baseline_icon_png = encode_png(image);
+        self
+    }
+}
+
+/// 画像を PNG 形式にエンコードします。失敗した場合は `None` を返します。
+pub fn encode_png(image: &DynamicImage) -> Option<Vec<u8>> {
+    let mut png_buffer = Vec::new();
+    match image.write_to(&mut Cursor::new(&mut png_buffer), ImageFormat::Png) {
+        Ok(_) => Some(png_buffer),
+        Err(e) => {
+            warn!("PNGのエンコードに失敗しました: {:?}", e);
+            None
+        }
+    }
+}
+
+/// 1 つの通知先への送信結果。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum DeliveryOutcome {
+    /// 送信に成功した。
+    Sent,
+    /// 送信に失敗した。
+    ///
+    /// - `status`: HTTP ステータスコード。通信エラーなど応答がない場合は `None`。
+    /// - `message`: エラー内容。
+    /// - `permanent`: 認証エラーや送信先が存在しないなど、再送しても成功しない失敗かどうか。
+    Failed {
+        status: Option<u16>,
+        message: String,
+        permanent: bool,
+    },
+    /// レート制限（HTTP 429）により送信できなかった。
+    ///
+    /// - `retry_after_ms`: 送信先が指定した再送までの待ち時間（ミリ秒）。指定がない場合は `None`。
+    /// - `message`: エラー内容。
+    RateLimited {
+        retry_after_ms: Option<u64>,
+        message: String,
+    },
+}
+
+impl DeliveryOutcome {
+    pub fn failed(status: Option<u16>, message: impl Into<String>) -> Self {
+        let permanent = matches!(status, Some(400 | 401 | 403 | 404));
+        DeliveryOutcome::Failed {
+            status,
+            message: message.into(),
+            permanent,
+        }
+    }
+
+    /// 再送すれば成功する可能性がある失敗かどうかを返します。
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            DeliveryOutcome::Sent => false,
+            DeliveryOutcome::Failed { permanent, .. } => !permanent,
+            DeliveryOutcome::RateLimited { .. } => true,
+        }
+    }
+
+    /// HTTP リクエストの結果を送信結果へ変換します。
+    ///
+    /// 2xx を成功とし、429 はレート制限、それ以外はステータスコードと応答本文を失敗として返します。
+    pub async fn from_response(result: Result<reqwest::Response, reqwest::Error>) -> Self {
+        match result {
+            Ok(response) => {
+                let status = response.status();
+                if status.is_success() {
+                    return DeliveryOutcome::Sent;
+                }
+                let retry_after_header = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string);
+                let body = response.text().await.unwrap_or_default();
+                if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    DeliveryOutcome::RateLimited {
+                        retry_after_ms: parse_retry_after(retry_after_header.as_deref(), &body),
+                        message: body,
+                    }
+                } else {
+                    DeliveryOutcome::failed(Some(status.as_u16()), body)
+                }
+            }
+            Err(e) => DeliveryOutcome::failed(
+                e.status().map(|status| status.as_u16()),
+                // URL にトークンが含まれる通知先があるため、エラー内容から URL を除きます。
+                e.without_url().to_string(),
+            ),
+        }
+    }
+}
+
+/// レート制限の応答から再送までの待ち時間（ミリ秒）を求めます。
+///
+/// Discord は応答本文の `retry_after`（秒、小数あり）で、その他の多くのサービスは `Retry-After` ヘッダー（秒）で指定します。
+/// より精度の高い応答本文の値を優先します。
+fn parse_retry_after(header: Option<&str>, body: &str) -> Option<u64> {
+    let seconds = serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|value| value["retry_after"].as_f64())
+        .or_else(|| header.and_then(|value| value.trim().parse::<f64>().ok()))?;
+    if seconds.is_finite() && seconds >= 0.0 {
+        Some((seconds * 1000.0).ceil() as u64)
+    } else {
+        None
+    }
+}
+
+/// 通知先ごとの送信結果。監視状況（`TargetStatus::last_delivery`）としてフロントエンドへ通知し、配信履歴にも記録します。
+///
+/// # フィールド
+/// - `notifier`: 通知先の名前（`Notifier::name`）。
+/// - `delivered_at`: 最後に送信を試みた時刻。
+/// - `attempts`: 送信を試みた回数（再送を含む）。
+/// - `result`: 最後の送信結果。
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeliveryResult {
+    pub notifier: String,
+    pub delivered_at: DateTime<Local>,
+    pub attempts: u32,
+    #[serde(flatten)]
+    pub result: DeliveryOutcome,
+}
+
+/// テスト通知の通知先ごとの送信結果。`send_test_notification` コマンドの戻り値として使用します。
+///
+/// # フィールド
+/// - `notifier`: 通知先の名前（`Notifier::name`）。
+/// - `success`: 送信に成功したかどうか。
+/// - `status`: HTTP ステータスコード。成功した場合や、通信エラーなど応答がない場合は `None`。
+/// - `message`: エラー内容。成功した場合は空。
+///
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TestNotificationResult {
+    pub notifier: String,
+    pub success: bool,
+    pub status: Option<u16>,
+    pub message: String,
+}
+
+impl TestNotificationResult {
+    pub fn new(notifier: &str, outcome: DeliveryOutcome) -> Self {
+        let (success, status, message) = match outcome {
+            DeliveryOutcome::Sent => (true, None, String::new()),
+            DeliveryOutcome::Failed {
+                status, message, ..
+            } => (false, status, message),
+            DeliveryOutcome::RateLimited { message, .. } => (false, Some(429), message),
+        };
+        Self {
+            notifier: notifier.to_string(),
+            success,
+            status,
+            message,
+        }
+    }
+}
+
+/// 通知先の共通インターフェース。
+///
+/// 通知先を追加する場合は、このトレイトを実装し `build_notifier` と `NOTIFIER_KINDS` へ登録します。
+/// 監視ループは通知先の種類を意識せず、登録されたすべての通知先へ送信します。
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// ログや送信結果に表示する通知先の名前。
+    fn name(&self) -> &str;
+
+    /// 検知イベントを送信します。
+    async fn send(&self, event: &NotificationEvent) -> DeliveryOutcome;
+
+    /// 一時的な失敗で送信できなかった場合に、送信待ちキューへ保存して後で送信するかどうか。
+    ///
+    /// ローカルで完結する通知先など、遅れて送信しても意味がない通知先は `false` を返します。
+    fn queues_when_undelivered(&self) -> bool {
+        true
+    }
+
+    /// 一時的な失敗で送信できなかった場合に、`RetryPolicy` に従って再送するかどうか。
+    ///
+    /// 再送すると同じ処理が重複して実行されるおそれのある通知先などは `false` を返し、1 回だけ送信します。
+    fn retries_when_failed(&self) -> bool {
+        true
+    }
+
+    /// ルーティングルール（`NotificationRouter`）で送信先を絞り込む対象かどうか。
+    ///
+    /// 監視対象ごとの設定で有効にする通知先など、ルールに関係なく送信する通知先は `false` を返します。
+    fn routable(&self) -> bool {
+        true
+    }
+}
+
+/// 通知先への HTTP リクエストの接続のタイムアウト。
+const HTTP_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// 通知先への HTTP リクエスト全体のタイムアウト。
+///
+/// 通知先へは並行して送信し、すべての結果を待つため、応答しない通知先があっても監視や送信待ちキューの処理が止まらないようにします。
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 通知先で共有する、タイムアウトを設定した HTTP クライアントを作成します。
+pub fn http_client() -> Client {
+    Client::builder()
+        .connect_timeout(HTTP_CONNECT_TIMEOUT)
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .unwrap_or_else(|e| {
+            warn!("HTTP クライアントの作成に失敗しました: {:?}", e);
+            Client::new()
+        })
+}
+
+/// 設定ファイルから構築した通知先の一覧。
+///
+/// 監視開始時に 1 度だけ設定ファイルを読み込んで構築し、検知のたびに設定ファイルを読み直さないようにします。
+#[derive(Default)]
+pub struct NotifierRegistry {
+    notifiers: Vec<Box<dyn Notifier>>,
+    retry_policy: RetryPolicy,
+}
+
+impl NotifierRegistry {
+    /// 設定値から有効な通知先を構築します。設定が不足している通知先は登録しません。
+    ///
+    /// 従来の設定キー（`DISCORD_WEBHOOK_URL` など）で構築する通知先の後に、
+    /// `NOTIFIER_INSTANCES` の有効なインスタンスを登録します。
+    /// HTTP で送信する通知先は、`http_client` で作成した 1 つのクライアントを共有します。
+    pub fn from_config(config: &AppConfig) -> Self {
+        let mut registry = Self {
+            notifiers: Vec::new(),
+            retry_policy: RetryPolicy::from_config(config),
+        };
+        let client = http_client();
+        for (kind, _) in NOTIFIER_KINDS {
+            if let Some(notifier) = build_notifier(kind, config, &client) {
+                registry.register(notifier);
+            }
+        }
+        for instance in &config.notifier_instances {
+            if !instance.enabled {
+                continue;
+            }
+            match build_notifier(&instance.kind, &instance.notifier_config(config), &client) {
+                Some(notifier) => registry.register(Box::new(InstanceNotifier::new(
+                    instance.id.clone(),
+                    notifier,
+                ))),
+                None => warn!(
+                    "通知先「{}」は設定が不足しているため登録しません。",
+                    instance.id
+                ),
+            }
+        }
+        registry
+    }
+
+    /// 設定ファイルを読み込み、通知先を構築します。
+    ///
+    /// # 戻り値
+    /// * `Ok(NotifierRegistry)` - 構築した通知先の一覧。
+    /// * `Err(String)` - 設定ファイルの読み込み、または JSON 解析に失敗した場合。
+    pub fn load(config_path: &Path) -> Result<Self, String> {
+        Ok(Self::from_config(&AppConfig::load(config_path)?))
+    }
+
+    pub fn register(&mut self, notifier: Box<dyn Notifier>) {
+        self.notifiers.push(notifier);
+    }
+
+    /// 指定した名前の通知先を返します。
+    pub fn get(&self, name: &str) -> Option<&dyn Notifier> {
+        self.notifiers
+            .iter()
+            .find(|notifier| notifier.name() == name)
+            .map(|notifier| notifier.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.notifiers
+            .iter()
+            .map(|notifier| notifier.name())
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notifiers.is_empty()
+    }
+
+    /// 登録されているすべての通知先へ検知イベントを送信し、通知先ごとの結果を返します。
+    ///
+    /// 通知先へは並行して送信し、一時的な失敗は `RetryPolicy` に従って再送します。
+    /// 1 つの通知先で失敗しても、残りの通知先への送信は継続します。結果は登録順に並びます。
+    pub async fn notify_all(&self, event: &NotificationEvent) -> Vec<DeliveryResult> {
+        if self.is_empty() {
+            info!("通知先が設定されていないため、通知は送信しません。");
+        }
+        self.notify_each(
+            self.notifiers.iter().map(|notifier| notifier.as_ref()),
+            event,
+        )
+        .await
+    }
+
+    /// ルーティングルールやエスカレーションで選択された `names` の通知先と、ルーティングの対象外の通知先へ検知イベントを送信します。
+    ///
+    /// 設定されていない通知先の名前は警告を出力して無視します。送信の方法は `notify_all` と同じです。
+    pub async fn notify_routed(
+        &self,
+        event: &NotificationEvent,
+        names: &[String],
+    ) -> Vec<DeliveryResult> {
+        for name in names {
+            if self.get(name).is_none() {
+                warn!("通知先「{}」は設定されていないため、送信しません。", name);
+            }
+        }
+        let selected: Vec<&dyn Notifier> = self
+            .notifiers
+            .iter()
+            .map(|notifier| notifier.as_ref())
+            .filter(|notifier| !notifier.routable() || names.iter().any(|n| n == notifier.name()))
+            .collect();
+        if selected.is_empty() {
+            info!("選択された通知先がないため、通知は送信しません。");
+        }
+        self.notify_each(selected.into_iter(), event).await
+    }
+
+    /// 指定した通知先へテスト通知を送信し、通知先ごとの結果を返します。`name` が `None` の場合はすべての通知先へ送信します。
+    ///
+    /// 設定をすぐに確認できるよう再送は行わず、送信できなかった場合も送信待ちキューへ保存しません。
+    ///
+    /// # 戻り値
+    /// * `Ok(Vec<TestNotificationResult>)` - 通知先ごとの送信結果。登録順に並びます。
+    /// * `Err(String)` - 指定した通知先が登録されていない場合、または通知先が 1 つも登録されていない場合。
+    pub async fn send_test(
+        &self,
+        name: Option<&str>,
+        event: &NotificationEvent,
+    ) -> Result<Vec<TestNotificationResult>, String> {
+        let targets: Vec<&dyn Notifier> = match name {
+            Some(name) => vec![self.get(name).ok_or_else(|| {
+                format!(
+                    "通知先「{}」は設定されていないか、設定が不足しています。",
+                    name
+                )
+            })?],
+            None => self
+                .notifiers
+                .iter()
+                .map(|notifier| notifier.as_ref())
+                .collect(),
+        };
+        if targets.is_empty() {
+            return Err("通知先が設定されていません。".to_string());
+        }
+        let results = join_all(targets.into_iter().map(|notifier| async move {
+            let result = TestNotificationResult::new(notifier.name(), notifier.send(event).await);
+            match (result.success, result.status) {
+                (true, _) => info!("{}へテスト通知を送信しました。", result.notifier),
+                (false, Some(status)) => warn!(
+                    "{}へのテスト通知の送信に失敗しました。ステータスコード: {} {}",
+                    result.notifier, status, result.message
+                ),
+                (false, None) => warn!(
+                    "{}へのテスト通知の送信中にエラーが発生しました: {}",
+                    result.notifier, result.message
+                ),
+            }
+            result
+        }))
+        .await;
+        Ok(results)
+    }
+
+    async fn notify_each<'a>(
+        &'a self,
+        notifiers: impl Iterator<Item = &'a dyn Notifier>,
+        event: &NotificationEvent,
+    ) -> Vec<DeliveryResult> {
+        join_all(notifiers.map(|notifier| self.deliver(notifier, event))).await
+    }
+
+    async fn deliver(&self, notifier: &dyn Notifier, event: &NotificationEvent) -> DeliveryResult {
+        let (outcome, attempts) = send_with_retry(notifier, event, &self.retry_policy).await;
+        match &outcome {
+            DeliveryOutcome::Sent => info!("{}へ通知を送信しました。", notifier.name()),
+            DeliveryOutcome::Failed {
+                status, message, ..
+            } => match status {
+                Some(status) => warn!(
+                    "{}への通知の送信に失敗しました。ステータスコード: {} {}",
+                    notifier.name(),
+                    status,
+                    message
+                ),
+                None => error!(
+                    "{}への通知の送信中にエラーが発生しました: {}",
+                    notifier.name(),
+                    message
+                ),
+            },
+            DeliveryOutcome::RateLimited { message, .. } => warn!(
+                "{}への通知はレート制限により送信できませんでした: {}",
+                notifier.name(),
+                message
+            ),
+        }
+        DeliveryResult {
+            notifier: notifier.name().to_string(),
+            delivered_at: Local::now(),
+            attempts,
+            result: outcome,
+        }
+    }
+}
+
+/// 通知先の種類（`NOTIFIER_KINDS`）と設定値から通知先を構築します。設定が不足している場合は `None` を返します。
+///
+/// HTTP で送信する通知先は `client` を使用します。
+pub fn build_notifier(
+    kind: &str,
+    config: &AppConfig,
+    client: &Client,
+) -> Option<Box<dyn Notifier>> {
+    fn boxed<N: Notifier + 'static>(notifier: Option<N>) -> Option<Box<dyn Notifier>> {
+        notifier.map(|notifier| Box::new(notifier) as Box<dyn Notifier>)
+    }
+    match kind {
+        "discord" => boxed(DiscordNotifier::from_config(config, client)),
+        "line" => boxed(LineNotifier::from_config(config, client)),
+        "slack" => boxed(SlackNotifier::from_config(config, client)),
+        "teams" => boxed(TeamsNotifier::from_config(config, client)),
+        "telegram" => boxed(TelegramNotifier::from_config(config, client)),
+        "ntfy" => boxed(NtfyNotifier::from_config(config, client)),
+        "gotify" => boxed(GotifyNotifier::from_config(config, client)),
+        "pushover" => boxed(PushoverNotifier::from_config(config, client)),
+        "email" => boxed(SmtpNotifier::from_config(config)),
+        "webhook" => boxed(WebhookNotifier::from_config(config, client)),
+        "command" => boxed(CommandNotifier::from_config(config)),
+        _ => None,
+    }
+}
+
+/// 設定値から空白を除いた文字列を返します。空の場合は `None` を返します。
+pub fn non_empty(value: &str) -> Option<String> {
+    Some(value.trim())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// 本文テンプレートで使用できるプレースホルダーと、検知イベントから求めた値の一覧を返します。
+///
+/// # プレースホルダー
+/// - `{app_name}`: アプリケーション名。
+/// - `{pid}`: プロセス ID。
+/// - `{timestamp}`: 検知時刻（RFC 3339 形式）。
+/// - `{time}`: 検知時刻（`%Y-%m-%d %H:%M:%S` 形式）。
+/// - `{diff_score}`: 初期画像との差分値。
+/// - `{trigger}`: 検知内容（例: `icon_changed`）。
+/// - `{icon_base64}`: 検知時のアイコン画像（PNG）の Base64。ない場合はウィンドウのアイコン、どちらもない場合は空文字。
+pub fn template_values(event: &NotificationEvent) -> Vec<(&'static str, String)> {
+    let icon_base64 = match &event.captured_icon_png {
+        Some(png) => general_purpose::STANDARD.encode(png),
+        None => event.app.icon.clone().unwrap_or_default(),
+    };
+    let trigger = serde_json::to_value(event.trigger)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default();
+    vec![
+        ("app_name", event.app.name.clone()),
+        ("pid", event.app.process_id.to_string()),
+        ("timestamp", event.detected_at.to_rfc3339()),
+        (
+            "time",
+            event.detected_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        ),
+        ("diff_score", format!("{:.4}", event.diff_score)),
+        ("trigger", trigger),
+        ("icon_base64", icon_base64),
+    ]
+}
+
+/// テンプレートの `{name}` 形式のプレースホルダーを検知イベントの値で置き換えます。
+///
+/// `escape` は置き換える値に適用されます。JSON の文字列内に埋め込む場合などに使用します。
+/// 未知のプレースホルダーはそのまま残します。
+pub fn render_template(
+    template: &str,
+    event: &NotificationEvent,
+    escape: impl Fn(&str) -> String,
+) -> String {
+    render_template_with(template, &template_values(event), escape)
+}
+
+/// テンプレートの `{name}` 形式のプレースホルダーを `values` の値で置き換えます。
+///
+/// 未知のプレースホルダーはそのまま残します。
+pub fn render_template_with(
+    template: &str,
+    values: &[(&'static str, String)],
+    escape: impl Fn(&str) -> String,
+) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let candidate = &rest[start + 1..];
+        let replaced = candidate.find('}').and_then(|end| {
+            let name = &candidate[..end];
+            values
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (escape(value), end))
+        });
+        match replaced {
+            Some((value, end)) => {
+                rendered.push_str(&value);
+                rest = &candidate[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = candidate;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// JSON の文字列リテラル内に埋め込めるよう、値をエスケープします（前後の `"` は含みません）。
+pub fn escape_json_string(value: &str) -> String {
+    let quoted = Value::String(value.to_string()).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        escape_json_string, parse_retry_after, render_template, DeliveryOutcome, NotificationEvent,
+        Notifier, NotifierRegistry, TestNotificationResult,
+    };
+    use crate::test_support::{app_config, sample_app, sample_event};
+    use crate::window_utils::AppInfo;
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    /// 常に送信に成功する通知先。
+    struct StubNotifier {
+        name: &'static str,
+        routable: bool,
+    }
+
+    #[async_trait]
+    impl Notifier for StubNotifier {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn send(&self, _event: &NotificationEvent) -> DeliveryOutcome {
+            DeliveryOutcome::Sent
+        }
+
+        fn routable(&self) -> bool {
+            self.routable
+        }
+    }
+
+    #[test]
+    fn render_template_replaces_known_placeholders() {
+        let app = AppInfo {
+            name: "Chat \"team\"".to_string(),
+            process_id: 42,
+            icon: Some("aWNvbg==".to_string()),
+            ..sample_app()
+        };
+        let event = NotificationEvent::icon_changed(app, 0.25);
+
+        let rendered = render_template(
+            r#"{"app":"{app_name}","pid":{pid},"score":{diff_score},"trigger":"{trigger}","icon":"{icon_base64}","keep":"{unknown}"}"#,
+            &event,
+            escape_json_string,
+        );
+        let value: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+
+        assert_eq!(value["app"], "Chat \"team\"");
+        assert_eq!(value["pid"], 42);
+        assert_eq!(value["score"], 0.25);
+        assert_eq!(value["trigger"], "icon_changed");
+        assert_eq!(value["icon"], "aWNvbg==");
+        assert_eq!(value["keep"], "{unknown}");
+    }
+
+    #[test]
+    fn registry_skips_unconfigured_notifiers() {
+        let config = json!({
+            "DISCORD_WEBHOOK_URL": "",
+            "LINE_ENABLED": "true",
+            "LINE_CHANNEL_ACCESS_TOKEN": "token",
+            "LINE_TARGET": ""
+        });
+
+        let registry = NotifierRegistry::from_config(&app_config(config));
+
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn registry_builds_configured_notifiers() {
+        let config = json!({
+            "DISCORD_WEBHOOK_URL": "https://discord.com/api/webhooks/1/abc",
+            "LINE_ENABLED": "true",
+            "LINE_CHANNEL_ACCESS_TOKEN": "token",
+            "LINE_TARGET": "U123"
+        });
+
+        let registry = NotifierRegistry::from_config(&app_config(config));
+
+        assert_eq!(registry.names(), vec!["discord", "line"]);
+    }
+
+    #[test]
+    fn registry_skips_disabled_line() {
+        let config = json!({
+            "LINE_ENABLED": "false",
+            "LINE_CHANNEL_ACCESS_TOKEN": "token",
+            "LINE_TARGET": "U123"
+        });
+
+        let registry = NotifierRegistry::from_config(&app_config(config));
+
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn failed_outcome_marks_auth_and_not_found_as_permanent() {
+        assert!(matches!(
+            DeliveryOutcome::failed(Some(401), ""),
+            DeliveryOutcome::Failed {
+                permanent: true,
+                ..
+            }
+        ));
+        assert!(matches!(
+            DeliveryOutcome::failed(Some(404), ""),
+            DeliveryOutcome::Failed {
+                permanent: true,
+                ..
+            }
+        ));
+        assert!(matches!(
+            DeliveryOutcome::failed(Some(500), ""),
+            DeliveryOutcome::Failed {
+                permanent: false,
+                ..
+            }
+        ));
+        assert!(matches!(
+            DeliveryOutcome::failed(None, "timeout"),
+            DeliveryOutcome::Failed {
+                permanent: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn retry_after_prefers_body_over_header() {
+        assert_eq!(
+            parse_retry_after(
+                Some("3"),
+                r#"{"message":"You are being rate limited.","retry_after":0.25,"global":false}"#
+            ),
+            Some(250)
+        );
+        assert_eq!(
+            parse_retry_after(Some("3"), "Too Many Requests"),
+            Some(3000)
+        );
+        assert_eq!(
+            parse_retry_after(
+                None,
+                r#"{"message":"The API rate limit has been exceeded."}"#
+            ),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn notify_routed_sends_to_selected_and_unroutable_notifiers() {
+        let mut registry = NotifierRegistry::default();
+        for (name, routable) in [("discord", true), ("line", true), ("desktop", false)] {
+            registry.register(Box::new(StubNotifier { name, routable }));
+        }
+        let event = sample_event();
+
+        let results = registry
+            .notify_routed(&event, &["line".to_string(), "slack".to_string()])
+            .await;
+        let notified: Vec<&str> = results
+            .iter()
+            .map(|result| result.notifier.as_str())
+            .collect();
+
+        assert_eq!(notified, vec!["line", "desktop"]);
+    }
+
+    /// 常に指定した結果を返す通知先。
+    struct FixedNotifier {
+        name: &'static str,
+        outcome: DeliveryOutcome,
+    }
+
+    #[async_trait]
+    impl Notifier for FixedNotifier {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn send(&self, _event: &NotificationEvent) -> DeliveryOutcome {
+            self.outcome.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn send_test_reports_each_notifier_without_retrying() {
+        let mut registry = NotifierRegistry::default();
+        registry.register(Box::new(StubNotifier {
+            name: "discord",
+            routable: true,
+        }));
+        registry.register(Box::new(FixedNotifier {
+            name: "slack",
+            outcome: DeliveryOutcome::failed(Some(404), "no_team"),
+        }));
+        let event = sample_event();
+
+        let results = registry.send_test(None, &event).await.unwrap();
+        assert_eq!(
+            results,
+            vec![
+                TestNotificationResult {
+                    notifier: "discord".to_string(),
+                    success: true,
+                    status: None,
+                    message: String::new(),
+                },
+                TestNotificationResult {
+                    notifier: "slack".to_string(),
+                    success: false,
+                    status: Some(404),
+                    message: "no_team".to_string(),
+                },
+            ]
+        );
+
+        let results = registry.send_test(Some("discord"), &event).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(registry.send_test(Some("line"), &event).await.is_err());
+        assert!(NotifierRegistry::default()
+            .send_test(None, &event)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn registry_builds_enabled_instances_after_legacy_notifiers() {
+        let config = json!({
+            "DISCORD_WEBHOOK_URL": "https://discord.com/api/webhooks/1/abc",
+            "NOTIFIER_INSTANCES": [
+                {
+                    "id": "team-discord",
+                    "kind": "discord",
+                    "settings": { "DISCORD_WEBHOOK_URL": "https://discord.com/api/webhooks/2/def" }
+                },
+                { "id": "my-line", "kind": "line", "settings": { "LINE_TARGET": "U123" } },
+                {
+                    "id": "old-slack",
+                    "kind": "slack",
+                    "enabled": false,
+                    "settings": { "SLACK_WEBHOOK_URL": "https://hooks.slack.com/services/x" }
+                }
+            ]
+        });
+
+        let registry = NotifierRegistry::from_config(&app_config(config));
+
+        assert_eq!(registry.names(), vec!["discord", "team-discord"]);
+    }
+}
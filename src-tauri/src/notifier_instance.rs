@@ -0,0 +1,223 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::app_config::AppConfig;
+use crate::notifier::{DeliveryOutcome, NotificationEvent, Notifier};
+
+/// 通知先のインスタンスを保存する設定キー。
+pub const NOTIFIER_INSTANCES_KEY: &str = "NOTIFIER_INSTANCES";
+
+/// インスタンスとして追加できる通知先の種類と、その種類の設定キーの接頭辞。
+///
+/// 種類の名前は、従来の設定キーで構築する通知先の名前（`Notifier::name`）と同じです。
+pub const NOTIFIER_KINDS: [(&str, &str); 11] = [
+    ("discord", "DISCORD_"),
+    ("line", "LINE_"),
+    ("slack", "SLACK_"),
+    ("teams", "TEAMS_"),
+    ("telegram", "TELEGRAM_"),
+    ("ntfy", "NTFY_"),
+    ("gotify", "GOTIFY_"),
+    ("pushover", "PUSHOVER_"),
+    ("email", "SMTP_"),
+    ("webhook", "HTTP_WEBHOOK_"),
+    ("command", "COMMAND_"),
+];
+
+/// 名前を付けて追加した通知先。
+///
+/// # 概要
+/// 同じ種類の通知先を複数登録できるよう、`appsettings.json` の `NOTIFIER_INSTANCES` に一覧として保存します。
+/// 従来の `DISCORD_WEBHOOK_URL` などの設定キーで構築する通知先とは別に登録されます。
+///
+/// # フィールド
+/// - `id`: 通知先の名前。送信結果やルーティングルール（`RoutingRule::notifiers`）で使用します。英数字、`-`、`_` のみ使用できます。
+/// - `kind`: 通知先の種類（`NOTIFIER_KINDS`）。
+/// - `label`: 画面に表示する名前。
+/// - `enabled`: 通知先を有効にするかどうか。
+/// - `settings`: 通知先の設定。従来の設定キーと同じキー（例: `DISCORD_WEBHOOK_URL`）で指定します。
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NotifierInstance {
+    pub id: String,
+    pub kind: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub settings: Map<String, Value>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl NotifierInstance {
+    /// 設定値を検証します。
+    ///
+    /// 通知先の名前が不正な場合、種類が不明な場合、種類に対応しない設定キーを含む場合はエラーを返します。
+    pub fn validate(&self) -> Result<(), String> {
+        let id = self.id.trim();
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err("通知先の名前は英数字、-、_ で指定してください。".to_string());
+        }
+        if id == "desktop" || NOTIFIER_KINDS.iter().any(|(kind, _)| *kind == id) {
+            return Err(format!(
+                "通知先の名前「{}」は組み込みの通知先と重複するため使用できません。",
+                id
+            ));
+        }
+        let prefix = kind_prefix(&self.kind)
+            .ok_or_else(|| format!("不明な通知先の種類です: {}", self.kind))?;
+        if let Some(key) = self.settings.keys().find(|key| !key.starts_with(prefix)) {
+            return Err(format!(
+                "{}の通知先には {} で始まる設定キーのみ指定できます: {}",
+                self.kind, prefix, key
+            ));
+        }
+        Ok(())
+    }
+
+    /// インスタンスの設定値を、種類に対応する通知先の設定として検証します。保存時に呼び出されます。
+    pub fn validate_settings(&self) -> Result<(), String> {
+        let config: AppConfig = serde_json::from_value(Value::Object(self.settings.clone()))
+            .map_err(|e| format!("通知先「{}」の設定が不正です: {}", self.id, e))?;
+        config
+            .validate_notifier(&self.kind)
+            .map_err(|e| format!("通知先「{}」: {}", self.id, e))
+    }
+
+    /// 通知先を構築するための設定値を作成します。
+    ///
+    /// インスタンスの設定に、言語や本文テンプレートなどの共有の設定を加えます。
+    /// LINE は `LINE_ENABLED` が有効な場合のみ構築されるため、有効なインスタンスでは常に有効とします。
+    pub fn notifier_config(&self, config: &AppConfig) -> AppConfig {
+        let mut instance_config =
+            AppConfig::from_value(Value::Object(self.settings.clone())).unwrap_or_default();
+        instance_config.notify_language = config.notify_language;
+        instance_config.message_templates = config.message_templates.clone();
+        if self.kind == "line" {
+            instance_config.line.enabled = true;
+        }
+        instance_config
+    }
+}
+
+/// 通知先の種類に対応する設定キーの接頭辞を返します。
+pub fn kind_prefix(kind: &str) -> Option<&'static str> {
+    NOTIFIER_KINDS
+        .iter()
+        .find(|(name, _)| *name == kind)
+        .map(|(_, prefix)| *prefix)
+}
+
+/// インスタンスの名前で送信結果を記録する通知先。
+///
+/// 通知先の実装（`DiscordNotifier` など）は種類の名前を返すため、インスタンスの名前に置き換えます。
+pub struct InstanceNotifier {
+    id: String,
+    inner: Box<dyn Notifier>,
+}
+
+impl InstanceNotifier {
+    pub fn new(id: String, inner: Box<dyn Notifier>) -> Self {
+        Self { id, inner }
+    }
+}
+
+#[async_trait]
+impl Notifier for InstanceNotifier {
+    fn name(&self) -> &str {
+        &self.id
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> DeliveryOutcome {
+        self.inner.send(event).await
+    }
+
+    fn queues_when_undelivered(&self) -> bool {
+        self.inner.queues_when_undelivered()
+    }
+
+    fn retries_when_failed(&self) -> bool {
+        self.inner.retries_when_failed()
+    }
+
+    fn routable(&self) -> bool {
+        self.inner.routable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NotifierInstance;
+    use crate::message_template::Language;
+    use crate::test_support::app_config;
+    use serde_json::json;
+
+    #[test]
+    fn loading_skips_invalid_and_duplicate_instances() {
+        let instances = app_config(json!({
+            "NOTIFIER_INSTANCES": [
+                { "id": "team", "kind": "discord", "settings": { "DISCORD_WEBHOOK_URL": "https://a" } },
+                { "id": "team", "kind": "line" },
+                { "id": "discord", "kind": "discord" },
+                { "id": "mine", "kind": "line", "settings": { "SLACK_WEBHOOK_URL": "https://b" } },
+                { "id": "unknown", "kind": "fax" },
+                { "kind": "line" }
+            ]
+        }))
+        .notifier_instances;
+
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].id, "team");
+        assert!(instances[0].enabled);
+    }
+
+    #[test]
+    fn validate_settings_checks_the_settings_of_the_kind() {
+        let instance = |settings| -> NotifierInstance {
+            serde_json::from_value(
+                json!({ "id": "mine", "kind": "pushover", "settings": settings }),
+            )
+            .unwrap()
+        };
+
+        assert!(instance(json!({ "PUSHOVER_PRIORITY": "1" }))
+            .validate_settings()
+            .is_ok());
+        assert!(instance(json!({ "PUSHOVER_PRIORITY": "9" }))
+            .validate_settings()
+            .is_err());
+        assert!(instance(json!({ "PUSHOVER_RETRY": "soon" }))
+            .validate_settings()
+            .is_err());
+    }
+
+    #[test]
+    fn notifier_config_adds_shared_keys_and_enables_line() {
+        let instance: NotifierInstance = serde_json::from_value(json!({
+            "id": "mine",
+            "kind": "line",
+            "settings": { "LINE_TARGET": "U123" }
+        }))
+        .unwrap();
+
+        let config = instance.notifier_config(&app_config(json!({
+            "NOTIFY_LANGUAGE": "en",
+            "LINE_TARGET": "U999",
+            "DISCORD_WEBHOOK_URL": "https://a"
+        })));
+
+        assert_eq!(config.line.target, "U123");
+        assert!(config.line.enabled);
+        assert_eq!(config.notify_language, Language::En);
+        assert_eq!(config.discord.webhook_url, "");
+    }
+}
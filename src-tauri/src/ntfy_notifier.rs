@@ -0,0 +1,167 @@
+use async_trait::async_trait;
+use log::warn;
+use reqwest::{Client, Url};
+
+use crate::app_config::AppConfig;
+use crate::message_template::MessageTemplate;
+use crate::notifier::{non_empty, DeliveryOutcome, NotificationEvent, Notifier};
+
+const DEFAULT_NTFY_PRIORITY: u8 = 3;
+/// エスカレーションで優先度を上げて送信する場合の優先度（最大）。
+const RAISED_NTFY_PRIORITY: u8 = 5;
+
+/// ntfy のトピックへ通知を送信する通知先。
+///
+/// # 概要
+/// ウィンドウタイトルなどの日本語をヘッダーに含めずに送信できるよう、
+/// トピック URL をサーバーとトピック名に分けて JSON 形式で送信します。
+///
+/// # 設定
+/// - `NTFY_TOPIC_URL`: トピックの URL（例: `https://ntfy.sh/my-topic`）。空の場合、通知先として登録されません。
+/// - `NTFY_PRIORITY`: 優先度（`1〜5`）。既定値は `3`。優先度を上げて送信する通知では `5` を使用します。
+/// - `NTFY_TAGS`: カンマ区切りのタグ（例: `warning,computer`）。
+/// - `NTFY_ACCESS_TOKEN`: アクセストークン。空の場合は認証なしで送信します。
+/// - `MESSAGE_TEMPLATES.ntfy`: 本文のテンプレート（`MessageTemplate`）。
+pub struct NtfyNotifier {
+    client: Client,
+    server_url: String,
+    topic: String,
+    priority: u8,
+    tags: Vec<String>,
+    access_token: Option<String>,
+    message: MessageTemplate,
+}
+
+impl NtfyNotifier {
+    pub fn from_config(config: &AppConfig, client: &Client) -> Option<Self> {
+        let ntfy = &config.ntfy;
+        let topic_url = non_empty(&ntfy.topic_url)?;
+        let (server_url, topic) = match split_topic_url(&topic_url) {
+            Some(parts) => parts,
+            None => {
+                warn!("NTFY_TOPIC_URLの形式が不正なため、ntfy 通知は送信しません。");
+                return None;
+            }
+        };
+        Some(Self {
+            client: client.clone(),
+            server_url,
+            topic,
+            priority: parse_priority(ntfy.priority),
+            tags: ntfy
+                .tags
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_string)
+                .collect(),
+            access_token: non_empty(&ntfy.access_token),
+            message: MessageTemplate::from_config(config, "ntfy"),
+        })
+    }
+}
+
+/// トピック URL をサーバーの URL とトピック名に分割します。
+fn split_topic_url(topic_url: &str) -> Option<(String, String)> {
+    let mut url = Url::parse(topic_url).ok()?;
+    let topic = url
+        .path_segments()?
+        .rfind(|segment| !segment.is_empty())?
+        .to_string();
+    url.path_segments_mut().ok()?.pop_if_empty().pop();
+    Some((url.as_str().trim_end_matches('/').to_string(), topic))
+}
+
+/// 優先度の設定値を確認します。`1〜5` 以外の場合は既定値を使用します。
+fn parse_priority(value: Option<u8>) -> u8 {
+    match value {
+        None => DEFAULT_NTFY_PRIORITY,
+        Some(priority) if (1..=5).contains(&priority) => priority,
+        Some(_) => {
+            warn!(
+                "NTFY_PRIORITYは1から5の範囲で指定してください。既定値({})を使用します。",
+                DEFAULT_NTFY_PRIORITY
+            );
+            DEFAULT_NTFY_PRIORITY
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for NtfyNotifier {
+    fn name(&self) -> &str {
+        "ntfy"
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> DeliveryOutcome {
+        let payload = serde_json::json!({
+            "topic": self.topic,
+            "title": event.app.name,
+            "message": self.message.render(event),
+            "priority": if event.raised_priority {
+                RAISED_NTFY_PRIORITY
+            } else {
+                self.priority
+            },
+            "tags": self.tags,
+        });
+        let mut request = self.client.post(&self.server_url).json(&payload);
+        if let Some(token) = &self.access_token {
+            request = request.bearer_auth(token);
+        }
+        DeliveryOutcome::from_response(request.send().await).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_priority, split_topic_url, NtfyNotifier};
+    use crate::notifier::{http_client, DeliveryOutcome, Notifier};
+    use crate::test_support::{app_config, sample_event, stand_in_server};
+    use serde_json::json;
+
+    #[test]
+    fn split_topic_url_separates_server_and_topic() {
+        assert_eq!(
+            split_topic_url("https://ntfy.example.com/alerts"),
+            Some(("https://ntfy.example.com".to_string(), "alerts".to_string()))
+        );
+        assert_eq!(
+            split_topic_url("https://example.com/ntfy/alerts/"),
+            Some(("https://example.com/ntfy".to_string(), "alerts".to_string()))
+        );
+        assert_eq!(split_topic_url("https://ntfy.example.com/"), None);
+    }
+
+    #[test]
+    fn parse_priority_falls_back_to_default() {
+        assert_eq!(parse_priority(Some(5)), 5);
+        assert_eq!(parse_priority(Some(9)), 3);
+        assert_eq!(parse_priority(Some(0)), 3);
+        assert_eq!(parse_priority(None), 3);
+    }
+
+    #[tokio::test]
+    async fn send_publishes_json_with_auth() {
+        let (base_url, server) = stand_in_server("200 OK", "{}");
+        let notifier = NtfyNotifier::from_config(
+            &app_config(json!({
+                "NTFY_TOPIC_URL": format!("{}/alerts", base_url),
+                "NTFY_PRIORITY": "4",
+                "NTFY_TAGS": "warning, computer",
+                "NTFY_ACCESS_TOKEN": "tk_secret"
+            })),
+            &http_client(),
+        )
+        .unwrap();
+        let outcome = notifier.send(&sample_event()).await;
+        let request = server.join().unwrap();
+
+        assert_eq!(outcome, DeliveryOutcome::Sent);
+        assert!(request.starts_with("POST / "));
+        assert!(request.contains("authorization: Bearer tk_secret"));
+        assert!(request.contains(r#""topic":"alerts""#));
+        assert!(request.contains(r#""priority":4"#));
+        assert!(request.contains(r#""tags":["warning","computer"]"#));
+    }
+}
@@ -0,0 +1,221 @@
+use async_trait::async_trait;
+use log::warn;
+use reqwest::Client;
+
+use crate::app_config::AppConfig;
+use crate::message_template::MessageTemplate;
+use crate::notifier::{non_empty, DeliveryOutcome, NotificationEvent, Notifier};
+
+const PUSHOVER_MESSAGES_URL: &str = "https://api.pushover.net/1/messages.json";
+const DEFAULT_PUSHOVER_RETRY: u32 = 60;
+const DEFAULT_PUSHOVER_EXPIRE: u32 = 3600;
+/// 緊急通知（優先度 `2`）の再通知間隔の下限（秒）。Pushover API の制限です。
+const MIN_PUSHOVER_RETRY: u32 = 30;
+/// 緊急通知（優先度 `2`）の再通知を続ける時間の上限（秒）。Pushover API の制限です。
+const MAX_PUSHOVER_EXPIRE: u32 = 10800;
+
+/// Pushover で通知を送信する通知先。
+///
+/// # 概要
+/// 優先度 `2`（緊急）の場合、受信者が確認するまで `retry` 秒ごとに最大 `expire` 秒間再通知されます。
+///
+/// # 設定
+/// - `PUSHOVER_USER_KEY`: 送信先のユーザーキー（またはグループキー）。
+/// - `PUSHOVER_APP_TOKEN`: アプリケーションの API トークン。
+/// - `PUSHOVER_DEVICE`: 送信先のデバイス名。空の場合はすべてのデバイスへ送信します。
+/// - `PUSHOVER_SOUND`: 通知音。空の場合はユーザーの既定の通知音を使用します。
+/// - `PUSHOVER_PRIORITY`: 優先度（`-2〜2`）。既定値は `0`。優先度を上げて送信する通知では 1 段階上の優先度を使用します。
+/// - `PUSHOVER_RETRY`: 緊急通知の再通知間隔（秒、`30` 以上）。既定値は `60`。
+/// - `PUSHOVER_EXPIRE`: 緊急通知の再通知を続ける時間（秒、`10800` 以下）。既定値は `3600`。
+/// - `MESSAGE_TEMPLATES.pushover`: 本文のテンプレート（`MessageTemplate`）。
+///
+/// ユーザーキーまたは API トークンが空の場合、通知先として登録されません。
+pub struct PushoverNotifier {
+    client: Client,
+    api_url: String,
+    user_key: String,
+    app_token: String,
+    device: Option<String>,
+    sound: Option<String>,
+    priority: i8,
+    retry: u32,
+    expire: u32,
+    message: MessageTemplate,
+}
+
+impl PushoverNotifier {
+    pub fn from_config(config: &AppConfig, client: &Client) -> Option<Self> {
+        let pushover = &config.pushover;
+        Some(Self {
+            client: client.clone(),
+            api_url: PUSHOVER_MESSAGES_URL.to_string(),
+            user_key: non_empty(&pushover.user_key)?,
+            app_token: non_empty(&pushover.app_token)?,
+            device: non_empty(&pushover.device),
+            sound: non_empty(&pushover.sound),
+            priority: parse_priority(pushover.priority),
+            retry: pushover
+                .retry
+                .unwrap_or(DEFAULT_PUSHOVER_RETRY)
+                .max(MIN_PUSHOVER_RETRY),
+            expire: pushover
+                .expire
+                .unwrap_or(DEFAULT_PUSHOVER_EXPIRE)
+                .min(MAX_PUSHOVER_EXPIRE),
+            message: MessageTemplate::from_config(config, "pushover"),
+        })
+    }
+
+    /// Pushover API へ送信するフォームの項目を作成します。
+    ///
+    /// `retry` と `expire` は緊急通知（優先度 `2`）の場合だけ送信します。
+    fn build_form(&self, event: &NotificationEvent) -> Vec<(&'static str, String)> {
+        let priority = if event.raised_priority {
+            (self.priority + 1).min(2)
+        } else {
+            self.priority
+        };
+        let mut form = vec![
+            ("token", self.app_token.clone()),
+            ("user", self.user_key.clone()),
+            ("title", event.app.name.clone()),
+            ("message", self.message.render(event)),
+            ("timestamp", event.detected_at.timestamp().to_string()),
+            ("priority", priority.to_string()),
+        ];
+        if let Some(device) = &self.device {
+            form.push(("device", device.clone()));
+        }
+        if let Some(sound) = &self.sound {
+            form.push(("sound", sound.clone()));
+        }
+        if priority == 2 {
+            form.push(("retry", self.retry.to_string()));
+            form.push(("expire", self.expire.to_string()));
+        }
+        form
+    }
+}
+
+/// 優先度の設定値を確認します。`-2〜2` 以外の場合は `0` を使用します。
+fn parse_priority(value: Option<i8>) -> i8 {
+    match value {
+        None => 0,
+        Some(priority) if (-2..=2).contains(&priority) => priority,
+        Some(_) => {
+            warn!("PUSHOVER_PRIORITYは-2から2の範囲で指定してください。既定値(0)を使用します。");
+            0
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for PushoverNotifier {
+    fn name(&self) -> &str {
+        "pushover"
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> DeliveryOutcome {
+        DeliveryOutcome::from_response(
+            self.client
+                .post(&self.api_url)
+                .form(&self.build_form(event))
+                .send()
+                .await,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PushoverNotifier;
+    use crate::notifier::{http_client, DeliveryOutcome, Notifier};
+    use crate::test_support::{app_config, sample_event, stand_in_server};
+    use serde_json::json;
+
+    #[test]
+    fn emergency_priority_includes_clamped_retry_and_expire() {
+        let notifier = PushoverNotifier::from_config(
+            &app_config(json!({
+                "PUSHOVER_USER_KEY": "user",
+                "PUSHOVER_APP_TOKEN": "token",
+                "PUSHOVER_PRIORITY": "2",
+                "PUSHOVER_RETRY": "10",
+                "PUSHOVER_EXPIRE": "99999"
+            })),
+            &http_client(),
+        )
+        .unwrap();
+
+        let form = notifier.build_form(&sample_event());
+
+        assert!(form.contains(&("priority", "2".to_string())));
+        assert!(form.contains(&("retry", "30".to_string())));
+        assert!(form.contains(&("expire", "10800".to_string())));
+    }
+
+    #[test]
+    fn normal_priority_omits_retry_and_expire() {
+        let notifier = PushoverNotifier::from_config(
+            &app_config(json!({
+                "PUSHOVER_USER_KEY": "user",
+                "PUSHOVER_APP_TOKEN": "token",
+                "PUSHOVER_PRIORITY": "1",
+                "PUSHOVER_DEVICE": "phone",
+                "PUSHOVER_SOUND": "siren"
+            })),
+            &http_client(),
+        )
+        .unwrap();
+
+        let form = notifier.build_form(&sample_event());
+
+        assert!(form.contains(&("device", "phone".to_string())));
+        assert!(form.contains(&("sound", "siren".to_string())));
+        assert!(!form
+            .iter()
+            .any(|(key, _)| *key == "retry" || *key == "expire"));
+    }
+
+    #[test]
+    fn raised_priority_escalates_one_level() {
+        let notifier = PushoverNotifier::from_config(
+            &app_config(json!({
+                "PUSHOVER_USER_KEY": "user",
+                "PUSHOVER_APP_TOKEN": "token",
+                "PUSHOVER_PRIORITY": "1"
+            })),
+            &http_client(),
+        )
+        .unwrap();
+        let mut event = sample_event();
+        event.raised_priority = true;
+
+        let form = notifier.build_form(&event);
+
+        assert!(form.contains(&("priority", "2".to_string())));
+        assert!(form.contains(&("retry", "60".to_string())));
+    }
+
+    #[tokio::test]
+    async fn send_posts_form_to_messages_api() {
+        let (base_url, server) = stand_in_server("200 OK", r#"{"status":1}"#);
+        let mut notifier = PushoverNotifier::from_config(
+            &app_config(json!({
+                "PUSHOVER_USER_KEY": "user",
+                "PUSHOVER_APP_TOKEN": "token"
+            })),
+            &http_client(),
+        )
+        .unwrap();
+        notifier.api_url = format!("{}/1/messages.json", base_url);
+
+        let outcome = notifier.send(&sample_event()).await;
+        let request = server.join().unwrap();
+
+        assert_eq!(outcome, DeliveryOutcome::Sent);
+        assert!(request.starts_with("POST /1/messages.json "));
+        assert!(request.contains("token=token&user=user"));
+    }
+}
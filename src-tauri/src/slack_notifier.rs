@@ -0,0 +1,152 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::app_config::AppConfig;
+use crate::message_template::MessageTemplate;
+use crate::notifier::{non_empty, DeliveryOutcome, NotificationEvent, Notifier};
+
+/// Slack の Incoming Webhook で通知を送信する通知先。
+///
+/// # 概要
+/// Block Kit 形式で、アプリケーション名・検知内容・検知時刻を通知します。
+///
+/// # 設定
+/// - `SLACK_WEBHOOK_URL`: Slack Incoming Webhook URL。空の場合、通知先として登録されません。
+/// - `MESSAGE_TEMPLATES.slack`: 本文のテンプレート（`MessageTemplate`）。
+pub struct SlackNotifier {
+    client: Client,
+    webhook_url: String,
+    message: MessageTemplate,
+}
+
+impl SlackNotifier {
+    pub fn from_config(config: &AppConfig, client: &Client) -> Option<Self> {
+        let webhook_url = non_empty(&config.slack.webhook_url)?;
+        Some(Self {
+            client: client.clone(),
+            webhook_url,
+            message: MessageTemplate::from_config(config, "slack"),
+        })
+    }
+}
+
+/// Slack へ送信する Block Kit 形式のペイロードを作成します。
+///
+/// `text` は通知のプレビューや Block Kit 非対応のクライアントで表示されます。
+fn build_payload(message: &MessageTemplate, event: &NotificationEvent) -> Value {
+    let text = escape_mrkdwn(&message.render(event));
+    let labels = message.labels();
+    serde_json::json!({
+        "text": text,
+        "blocks": [
+            {
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": text }
+            },
+            {
+                "type": "section",
+                "fields": [
+                    { "type": "mrkdwn", "text": format!("*{}*\n{}", labels.application, escape_mrkdwn(&event.app.name)) },
+                    {
+                        "type": "mrkdwn",
+                        "text": format!("*{}*\n{}", labels.trigger, message.trigger_label(event.trigger))
+                    },
+                    {
+                        "type": "mrkdwn",
+                        "text": format!(
+                            "*{}*\n{}",
+                            labels.detected_at,
+                            event.detected_at.format("%Y-%m-%d %H:%M:%S")
+                        )
+                    }
+                ]
+            }
+        ]
+    })
+}
+
+/// Slack の mrkdwn へ埋め込む文字列の制御文字（`&`、`<`、`>`）をエスケープします。
+///
+/// ウィンドウタイトルに含まれる `<!channel>` などがメンションやリンクとして解釈されないようにします。
+fn escape_mrkdwn(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[async_trait]
+impl Notifier for SlackNotifier {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> DeliveryOutcome {
+        DeliveryOutcome::from_response(
+            self.client
+                .post(&self.webhook_url)
+                .json(&build_payload(&self.message, event))
+                .send()
+                .await,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::build_payload;
+    use crate::message_template::MessageTemplate;
+    use crate::notifier::NotificationEvent;
+    use crate::test_support::{app_config, sample_app, sample_event};
+    use crate::window_utils::AppInfo;
+    use serde_json::json;
+
+    #[test]
+    fn payload_contains_app_name_trigger_and_time() {
+        let event = sample_event();
+
+        let payload = build_payload(
+            &MessageTemplate::from_config(&app_config(json!({})), "slack"),
+            &event,
+        );
+        let fields = payload["blocks"][1]["fields"].as_array().unwrap();
+
+        assert!(payload["text"].as_str().unwrap().contains("Chat"));
+        assert_eq!(fields.len(), 3);
+        assert!(fields[1]["text"]
+            .as_str()
+            .unwrap()
+            .contains("アイコンの変化"));
+        assert!(fields[2]["text"]
+            .as_str()
+            .unwrap()
+            .contains(&event.detected_at.format("%Y-%m-%d").to_string()));
+    }
+
+    #[test]
+    fn payload_escapes_mrkdwn_control_characters() {
+        let app = AppInfo {
+            name: "<!channel> R&D <https://example.com|link>".to_string(),
+            ..sample_app()
+        };
+        let event = NotificationEvent::icon_changed(app, 0.2);
+
+        let payload = build_payload(
+            &MessageTemplate::from_config(&app_config(json!({})), "slack"),
+            &event,
+        );
+
+        let escaped = "&lt;!channel&gt; R&amp;D &lt;https://example.com|link&gt;";
+        assert!(payload["text"].as_str().unwrap().contains(escaped));
+        assert!(payload["blocks"][0]["text"]["text"]
+            .as_str()
+            .unwrap()
+            .contains(escaped));
+        assert!(payload["blocks"][1]["fields"][0]["text"]
+            .as_str()
+            .unwrap()
+            .ends_with(escaped));
+    }
+}
@@ -0,0 +1,290 @@
+use async_trait::async_trait;
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use log::warn;
+
+use crate::app_config::AppConfig;
+use crate::message_template::{Language, MessageTemplate};
+use crate::notifier::{non_empty, DeliveryOutcome, NotificationEvent, Notifier};
+
+/// HTML 本文からアイコン画像を参照する Content-ID。
+const ICON_CONTENT_ID: &str = "icon";
+
+/// SMTP サーバーとの接続方式。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SmtpSecurity {
+    /// 平文で接続した後、STARTTLS で暗号化します。
+    StartTls,
+    /// 接続時から TLS で暗号化します（SMTPS）。
+    Tls,
+    /// 暗号化しません。社内のリレーサーバーや動作確認用です。
+    None,
+}
+
+impl SmtpSecurity {
+    fn parse(value: Option<&str>) -> Option<Self> {
+        match value.map(str::to_ascii_lowercase).as_deref() {
+            None | Some("starttls") => Some(SmtpSecurity::StartTls),
+            Some("tls") => Some(SmtpSecurity::Tls),
+            Some("none") => Some(SmtpSecurity::None),
+            Some(_) => None,
+        }
+    }
+
+    fn default_port(&self) -> u16 {
+        match self {
+            SmtpSecurity::StartTls => 587,
+            SmtpSecurity::Tls => 465,
+            SmtpSecurity::None => 25,
+        }
+    }
+}
+
+/// SMTP でメール通知を送信する通知先。
+///
+/// # 概要
+/// テキストと HTML の本文を含むメールを送信します。
+/// 検知時のアイコン画像がある場合は、HTML 本文に埋め込む画像として添付します。
+///
+/// # 設定
+/// - `SMTP_HOST`: SMTP サーバーのホスト名。
+/// - `SMTP_PORT`: ポート番号。空の場合は接続方式に応じて `587`、`465`、`25` を使用します。
+/// - `SMTP_SECURITY`: 接続方式。`starttls`（既定）、`tls`、`none` のいずれか。
+/// - `SMTP_USERNAME` / `SMTP_PASSWORD`: 認証情報。ユーザー名が空の場合は認証しません。
+/// - `SMTP_FROM`: 送信元アドレス（例: `Flash Code <alert@example.com>`）。
+/// - `SMTP_TO`: カンマ区切りの送信先アドレス。
+/// - `MESSAGE_TEMPLATES.email`: 本文のテンプレート（`MessageTemplate`）。
+///
+/// ホスト名・送信元・送信先のいずれかが空、または不正な場合、通知先として登録されません。
+pub struct SmtpNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    message: MessageTemplate,
+}
+
+impl SmtpNotifier {
+    pub fn from_config(config: &AppConfig) -> Option<Self> {
+        let smtp = &config.smtp;
+        let host = non_empty(&smtp.host)?;
+        let from_value = non_empty(&smtp.from)?;
+        let to_value = non_empty(&smtp.to)?;
+
+        let security = match SmtpSecurity::parse(non_empty(&smtp.security).as_deref()) {
+            Some(security) => security,
+            None => {
+                warn!("SMTP_SECURITYはstarttls、tls、noneのいずれかを指定してください。メール通知は送信しません。");
+                return None;
+            }
+        };
+        let port = smtp.port.unwrap_or_else(|| security.default_port());
+        let from = match from_value.parse::<Mailbox>() {
+            Ok(from) => from,
+            Err(e) => {
+                warn!(
+                    "SMTP_FROMの形式が不正なため、メール通知は送信しません: {}",
+                    e
+                );
+                return None;
+            }
+        };
+        let mut to = Vec::new();
+        for address in to_value.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+            match address.parse::<Mailbox>() {
+                Ok(mailbox) => to.push(mailbox),
+                Err(e) => {
+                    warn!("SMTP_TOの形式が不正なため、メール通知は送信しません: {}", e);
+                    return None;
+                }
+            }
+        }
+        if to.is_empty() {
+            return None;
+        }
+
+        let builder = match security {
+            SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host),
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&host),
+            SmtpSecurity::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                &host,
+            )),
+        };
+        let mut builder = match builder {
+            Ok(builder) => builder.port(port),
+            Err(e) => {
+                warn!(
+                    "SMTP の接続設定の作成に失敗したため、メール通知は送信しません: {}",
+                    e
+                );
+                return None;
+            }
+        };
+        if let Some(username) = non_empty(&smtp.username) {
+            builder = builder.credentials(Credentials::new(username, smtp.password.clone()));
+        }
+
+        Some(Self {
+            transport: builder.build(),
+            from,
+            to,
+            message: MessageTemplate::from_config(config, "email"),
+        })
+    }
+
+    fn build_message(&self, event: &NotificationEvent) -> Result<Message, String> {
+        let subject = match self.message.language {
+            Language::Ja => format!(
+                "[Flash Code] {} のアイコンに変化がありました",
+                event.app.name
+            ),
+            Language::En => format!("[Flash Code] {} taskbar icon changed", event.app.name),
+        };
+        let labels = self.message.labels();
+        let body = self.message.render(event);
+        let trigger = self.message.trigger_label(event.trigger);
+        let detected_at = event.detected_at.format("%Y-%m-%d %H:%M:%S").to_string();
+        let text = format!(
+            "{}\n\n{}: {}\n{}: {}\n",
+            body, labels.trigger, trigger, labels.detected_at, detected_at
+        );
+        let icon_html = if event.captured_icon_png.is_some() {
+            format!(
+                r#"<p><img src="cid:{}" alt="icon" width="32" height="32"></p>"#,
+                ICON_CONTENT_ID
+            )
+        } else {
+            String::new()
+        };
+        let html = format!(
+            "<html><body>{}<p>{}</p>\
+             <table><tr><th align=\"left\">{}</th><td>{}</td></tr>\
+             <tr><th align=\"left\">{}</th><td>{}</td></tr></table></body></html>",
+            icon_html,
+            escape_html(&body).replace('\n', "<br>"),
+            labels.trigger,
+            escape_html(trigger),
+            labels.detected_at,
+            detected_at
+        );
+
+        let html_part = match &event.captured_icon_png {
+            Some(png) => MultiPart::related()
+                .singlepart(SinglePart::html(html))
+                .singlepart(
+                    Attachment::new_inline(ICON_CONTENT_ID.to_string())
+                        .body(png.clone(), ContentType::parse("image/png").unwrap()),
+                ),
+            None => MultiPart::related().singlepart(SinglePart::html(html)),
+        };
+
+        let mut builder = Message::builder().from(self.from.clone()).subject(subject);
+        for mailbox in &self.to {
+            builder = builder.to(mailbox.clone());
+        }
+        builder
+            .multipart(
+                MultiPart::alternative()
+                    .singlepart(SinglePart::plain(text))
+                    .multipart(html_part),
+            )
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// HTML 本文へ埋め込む文字列をエスケープします。
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[async_trait]
+impl Notifier for SmtpNotifier {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> DeliveryOutcome {
+        let message = match self.build_message(event) {
+            Ok(message) => message,
+            Err(e) => return DeliveryOutcome::failed(None, e),
+        };
+        match self.transport.send(message).await {
+            Ok(_) => DeliveryOutcome::Sent,
+            Err(e) => DeliveryOutcome::Failed {
+                status: None,
+                message: e.to_string(),
+                permanent: e.is_permanent(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_html, SmtpNotifier};
+    use crate::notifier::{DeliveryOutcome, NotificationEvent, Notifier};
+    use crate::test_support::{app_config, sample_app, stand_in_smtp_server};
+    use crate::window_utils::AppInfo;
+    use serde_json::json;
+
+    fn event() -> NotificationEvent {
+        let app = AppInfo {
+            name: "Chat <1>".to_string(),
+            ..sample_app()
+        };
+        NotificationEvent::icon_changed(app, 0.2)
+    }
+
+    #[test]
+    fn from_config_requires_valid_addresses() {
+        assert!(SmtpNotifier::from_config(&app_config(json!({
+            "SMTP_HOST": "localhost",
+            "SMTP_FROM": "not an address",
+            "SMTP_TO": "ops@example.com"
+        })))
+        .is_none());
+        assert!(SmtpNotifier::from_config(&app_config(json!({
+            "SMTP_HOST": "localhost",
+            "SMTP_FROM": "alert@example.com",
+            "SMTP_TO": " , "
+        })))
+        .is_none());
+    }
+
+    #[test]
+    fn escape_html_escapes_markup() {
+        assert_eq!(escape_html("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+
+    #[tokio::test]
+    async fn send_delivers_multipart_mail_with_inline_icon() {
+        let (port, server) = stand_in_smtp_server();
+        let notifier = SmtpNotifier::from_config(&app_config(json!({
+            "SMTP_HOST": "127.0.0.1",
+            "SMTP_PORT": port.to_string(),
+            "SMTP_SECURITY": "none",
+            "SMTP_FROM": "Flash Code <alert@example.com>",
+            "SMTP_TO": "ops@example.com, oncall@example.com"
+        })))
+        .unwrap();
+        let mut event = event();
+        event.captured_icon_png = Some(b"\x89PNG".to_vec());
+
+        let outcome = notifier.send(&event).await;
+        let session = server.join().unwrap();
+
+        assert_eq!(outcome, DeliveryOutcome::Sent);
+        assert!(session.contains("RCPT TO:<ops@example.com>"));
+        assert!(session.contains("RCPT TO:<oncall@example.com>"));
+        assert!(session.contains("multipart/alternative"));
+        assert!(session.contains("text/plain"));
+        assert!(session.contains("text/html"));
+        assert!(session.contains("Content-ID: <icon>"));
+        assert!(session.contains("Content-Disposition: inline"));
+    }
+}
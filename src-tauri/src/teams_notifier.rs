@@ -0,0 +1,175 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::app_config::AppConfig;
+use crate::message_template::MessageTemplate;
+use crate::notifier::{non_empty, DeliveryOutcome, NotificationEvent, Notifier};
+
+/// Microsoft Teams のワークフロー（Power Automate）の Webhook で通知を送信する通知先。
+///
+/// # 概要
+/// Adaptive Card 形式で、アプリケーションのアイコン・アプリケーション名・検知内容・検知時刻を通知します。
+///
+/// # 設定
+/// - `TEAMS_WEBHOOK_URL`: Teams ワークフローの Webhook URL。空の場合、通知先として登録されません。
+/// - `MESSAGE_TEMPLATES.teams`: タイトルのテンプレート（`MessageTemplate`）。
+pub struct TeamsNotifier {
+    client: Client,
+    webhook_url: String,
+    message: MessageTemplate,
+}
+
+impl TeamsNotifier {
+    pub fn from_config(config: &AppConfig, client: &Client) -> Option<Self> {
+        let webhook_url = non_empty(&config.teams.webhook_url)?;
+        Some(Self {
+            client: client.clone(),
+            webhook_url,
+            message: MessageTemplate::from_config(config, "teams"),
+        })
+    }
+}
+
+/// Teams へ送信する Adaptive Card 形式のペイロードを作成します。
+///
+/// アイコン（`AppInfo::icon`）が取得できている場合は、data URI の画像としてタイトルの左に表示します。
+fn build_payload(message: &MessageTemplate, event: &NotificationEvent) -> Value {
+    let title = message.render(event);
+    let labels = message.labels();
+
+    let mut header_columns = Vec::new();
+    if let Some(icon) = &event.app.icon {
+        header_columns.push(serde_json::json!({
+            "type": "Column",
+            "width": "auto",
+            "items": [
+                {
+                    "type": "Image",
+                    "url": format!("data:image/png;base64,{}", icon),
+                    "size": "Small",
+                    "altText": event.app.name
+                }
+            ]
+        }));
+    }
+    header_columns.push(serde_json::json!({
+        "type": "Column",
+        "width": "stretch",
+        "verticalContentAlignment": "Center",
+        "items": [
+            { "type": "TextBlock", "text": title, "weight": "Bolder", "wrap": true }
+        ]
+    }));
+
+    serde_json::json!({
+        "type": "message",
+        "attachments": [
+            {
+                "contentType": "application/vnd.microsoft.card.adaptive",
+                "contentUrl": null,
+                "content": {
+                    "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                    "type": "AdaptiveCard",
+                    "version": "1.4",
+                    "body": [
+                        { "type": "ColumnSet", "columns": header_columns },
+                        {
+                            "type": "FactSet",
+                            "facts": [
+                                { "title": labels.application, "value": event.app.name },
+                                { "title": labels.trigger, "value": message.trigger_label(event.trigger) },
+                                {
+                                    "title": labels.detected_at,
+                                    "value": event.detected_at.format("%Y-%m-%d %H:%M:%S").to_string()
+                                }
+                            ]
+                        }
+                    ]
+                }
+            }
+        ]
+    })
+}
+
+#[async_trait]
+impl Notifier for TeamsNotifier {
+    fn name(&self) -> &str {
+        "teams"
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> DeliveryOutcome {
+        DeliveryOutcome::from_response(
+            self.client
+                .post(&self.webhook_url)
+                .json(&build_payload(&self.message, event))
+                .send()
+                .await,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::build_payload;
+    use crate::message_template::MessageTemplate;
+    use crate::notifier::NotificationEvent;
+    use crate::test_support::{app_config, sample_app};
+    use crate::window_utils::AppInfo;
+    use serde_json::{json, Value};
+
+    fn event(icon: Option<&str>) -> NotificationEvent {
+        let app = AppInfo {
+            icon: icon.map(str::to_string),
+            ..sample_app()
+        };
+        NotificationEvent::icon_changed(app, 0.2)
+    }
+
+    fn payload(config: Value, event: &NotificationEvent) -> Value {
+        build_payload(
+            &MessageTemplate::from_config(&app_config(config), "teams"),
+            event,
+        )
+    }
+
+    #[test]
+    fn payload_embeds_icon_as_data_uri() {
+        let payload = payload(json!({}), &event(Some("iVBORw0KGgo=")));
+        let card = &payload["attachments"][0]["content"];
+        let columns = card["body"][0]["columns"].as_array().unwrap();
+
+        assert_eq!(card["type"], "AdaptiveCard");
+        assert_eq!(columns.len(), 2);
+        assert_eq!(
+            columns[0]["items"][0]["url"],
+            "data:image/png;base64,iVBORw0KGgo="
+        );
+    }
+
+    #[test]
+    fn payload_omits_icon_when_not_available() {
+        let payload = payload(json!({}), &event(None));
+        let card = &payload["attachments"][0]["content"];
+        let columns = card["body"][0]["columns"].as_array().unwrap();
+        let facts = card["body"][1]["facts"].as_array().unwrap();
+
+        assert_eq!(columns.len(), 1);
+        assert_eq!(facts[2]["title"], "検知時刻");
+    }
+
+    #[test]
+    fn payload_uses_configured_language() {
+        let payload = payload(json!({ "NOTIFY_LANGUAGE": "en" }), &event(None));
+        let card = &payload["attachments"][0]["content"];
+        let facts = card["body"][1]["facts"].as_array().unwrap();
+
+        assert_eq!(
+            card["body"][0]["columns"][0]["items"][0]["text"],
+            "Application \"Chat\" taskbar icon changed."
+        );
+        assert_eq!(facts[1]["title"], "Status");
+        assert_eq!(facts[1]["value"], "Icon changed");
+    }
+}
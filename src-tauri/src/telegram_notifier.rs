@@ -0,0 +1,188 @@
+use async_trait::async_trait;
+use reqwest::{multipart, Client};
+
+use crate::app_config::AppConfig;
+use crate::message_template::MessageTemplate;
+use crate::notifier::{non_empty, DeliveryOutcome, NotificationEvent, Notifier};
+
+const DEFAULT_TELEGRAM_API_BASE_URL: &str = "https://api.telegram.org";
+
+/// Telegram Bot API で通知を送信する通知先。
+///
+/// # 概要
+/// `sendMessage` でアプリケーション名と検知時刻を通知します。
+/// `TELEGRAM_SEND_PHOTO` が `"true"` で検知時のアイコン画像がある場合は、`sendPhoto` で画像付きで通知します。
+///
+/// # 設定
+/// - `TELEGRAM_BOT_TOKEN`: Bot のトークン。
+/// - `TELEGRAM_CHAT_ID`: 送信先のチャット ID。
+/// - `TELEGRAM_SEND_PHOTO`: 検知時のアイコン画像を送信するかどうか。既定値は `"false"`。
+/// - `TELEGRAM_API_BASE_URL`: Bot API のベース URL。既定値は `https://api.telegram.org`。
+/// - `MESSAGE_TEMPLATES.telegram`: 本文のテンプレート（`MessageTemplate`）。
+///
+/// トークンまたはチャット ID が空の場合、通知先として登録されません。
+///
+/// # 注意
+/// トークンはリクエスト URL に含まれるため、URL をログやエラー内容に出力しないでください。
+pub struct TelegramNotifier {
+    client: Client,
+    api_base_url: String,
+    bot_token: String,
+    chat_id: String,
+    send_photo: bool,
+    message: MessageTemplate,
+}
+
+impl TelegramNotifier {
+    pub fn from_config(config: &AppConfig, client: &Client) -> Option<Self> {
+        let telegram = &config.telegram;
+        Some(Self {
+            client: client.clone(),
+            api_base_url: non_empty(&telegram.api_base_url)
+                .unwrap_or_else(|| DEFAULT_TELEGRAM_API_BASE_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            bot_token: non_empty(&telegram.bot_token)?,
+            chat_id: non_empty(&telegram.chat_id)?,
+            send_photo: telegram.send_photo,
+            message: MessageTemplate::from_config(config, "telegram"),
+        })
+    }
+
+    fn method_url(&self, method: &str) -> String {
+        format!("{}/bot{}/{}", self.api_base_url, self.bot_token, method)
+    }
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> DeliveryOutcome {
+        let text = self.message.render(event);
+
+        if let (true, Some(png)) = (self.send_photo, &event.captured_icon_png) {
+            let photo = match multipart::Part::bytes(png.clone())
+                .file_name("icon.png")
+                .mime_str("image/png")
+            {
+                Ok(part) => part,
+                Err(e) => return DeliveryOutcome::failed(None, e.without_url().to_string()),
+            };
+            let form = multipart::Form::new()
+                .text("chat_id", self.chat_id.clone())
+                .text("caption", text)
+                .part("photo", photo);
+            return DeliveryOutcome::from_response(
+                self.client
+                    .post(self.method_url("sendPhoto"))
+                    .multipart(form)
+                    .send()
+                    .await,
+            )
+            .await;
+        }
+
+        let payload = serde_json::json!({
+            "chat_id": self.chat_id,
+            "text": text
+        });
+        DeliveryOutcome::from_response(
+            self.client
+                .post(self.method_url("sendMessage"))
+                .json(&payload)
+                .send()
+                .await,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TelegramNotifier;
+    use crate::notifier::{http_client, DeliveryOutcome, Notifier};
+    use crate::test_support::{app_config, sample_event, stand_in_server};
+    use serde_json::json;
+
+    fn notifier(base_url: &str) -> TelegramNotifier {
+        TelegramNotifier::from_config(
+            &app_config(json!({
+                "TELEGRAM_BOT_TOKEN": "123:secret",
+                "TELEGRAM_CHAT_ID": "-100",
+                "TELEGRAM_API_BASE_URL": base_url
+            })),
+            &http_client(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn from_config_requires_token_and_chat_id() {
+        assert!(TelegramNotifier::from_config(
+            &app_config(json!({ "TELEGRAM_BOT_TOKEN": "123:secret" })),
+            &http_client()
+        )
+        .is_none());
+        assert!(TelegramNotifier::from_config(
+            &app_config(json!({ "TELEGRAM_CHAT_ID": "-100" })),
+            &http_client()
+        )
+        .is_none());
+    }
+
+    #[tokio::test]
+    async fn send_message_posts_to_bot_api() {
+        let (base_url, server) = stand_in_server("200 OK", r#"{"ok":true}"#);
+
+        let outcome = notifier(&base_url).send(&sample_event()).await;
+        let request = server.join().unwrap();
+
+        assert_eq!(outcome, DeliveryOutcome::Sent);
+        assert!(request.starts_with("POST /bot123:secret/sendMessage "));
+        assert!(request.contains(r#""chat_id":"-100""#));
+        assert!(request.contains("Chat"));
+    }
+
+    #[tokio::test]
+    async fn send_photo_uploads_captured_icon() {
+        let (base_url, server) = stand_in_server("200 OK", r#"{"ok":true}"#);
+        let mut notifier = notifier(&base_url);
+        notifier.send_photo = true;
+        let mut event = sample_event();
+        event.captured_icon_png = Some(b"\x89PNG".to_vec());
+
+        let outcome = notifier.send(&event).await;
+        let request = server.join().unwrap();
+
+        assert_eq!(outcome, DeliveryOutcome::Sent);
+        assert!(request.starts_with("POST /bot123:secret/sendPhoto "));
+        assert!(request.contains("filename=\"icon.png\""));
+    }
+
+    #[tokio::test]
+    async fn unauthorized_is_reported_as_permanent_failure() {
+        let (base_url, server) = stand_in_server(
+            "401 Unauthorized",
+            r#"{"ok":false,"error_code":401,"description":"Unauthorized"}"#,
+        );
+
+        let outcome = notifier(&base_url).send(&sample_event()).await;
+        server.join().unwrap();
+
+        match outcome {
+            DeliveryOutcome::Failed {
+                status,
+                message,
+                permanent,
+            } => {
+                assert_eq!(status, Some(401));
+                assert!(permanent);
+                assert!(!message.contains("secret"));
+            }
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! テストで共通して使用するヘルパー。
+
+use crate::app_config::AppConfig;
+use crate::notifier::{DeliveryOutcome, NotificationEvent, Notifier};
+use crate::window_utils::AppInfo;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// 1 件だけリクエストを受け付け、指定した応答を返すローカルの HTTP サーバーを起動します。
+///
+/// 受信したリクエスト（ヘッダーと本文）をスレッドの戻り値として返します。
+pub fn stand_in_server(
+    status_line: &'static str,
+    body: &'static str,
+) -> (String, JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut received = Vec::new();
+        let mut buffer = [0u8; 4096];
+        loop {
+            let read = stream.read(&mut buffer).unwrap();
+            received.extend_from_slice(&buffer[..read]);
+            let request = String::from_utf8_lossy(&received).to_string();
+            if let Some(header_end) = request.find("\r\n\r\n") {
+                let content_length = request[..header_end]
+                    .lines()
+                    .find_map(|line| {
+                        line.to_ascii_lowercase()
+                            .strip_prefix("content-length:")
+                            .map(|value| value.trim().parse::<usize>().unwrap())
+                    })
+                    .unwrap_or(0);
+                if received.len() >= header_end + 4 + content_length {
+                    break;
+                }
+            }
+            if read == 0 {
+                break;
+            }
+        }
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status_line,
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).unwrap();
+        String::from_utf8_lossy(&received).to_string()
+    });
+    (base_url, handle)
+}
+
+/// 1 通だけメールを受け付けるローカルの SMTP サーバーを起動します。
+///
+/// 受信したコマンドとメール本文をスレッドの戻り値として返します。
+pub fn stand_in_smtp_server() -> (u16, JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        let mut session = String::new();
+        writer.write_all(b"220 stand-in ESMTP\r\n").unwrap();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap() == 0 {
+                break;
+            }
+            session.push_str(&line);
+            let command = line.trim_end().to_ascii_uppercase();
+            if command.starts_with("EHLO") || command.starts_with("HELO") {
+                writer
+                    .write_all(b"250-stand-in\r\n250 8BITMIME\r\n")
+                    .unwrap();
+            } else if command == "DATA" {
+                writer.write_all(b"354 end with .\r\n").unwrap();
+                loop {
+                    let mut data_line = String::new();
+                    if reader.read_line(&mut data_line).unwrap() == 0 {
+                        break;
+                    }
+                    if data_line == ".\r\n" {
+                        break;
+                    }
+                    session.push_str(&data_line);
+                }
+                writer.write_all(b"250 queued\r\n").unwrap();
+            } else if command == "QUIT" {
+                writer.write_all(b"221 bye\r\n").unwrap();
+                break;
+            } else {
+                writer.write_all(b"250 ok\r\n").unwrap();
+            }
+        }
+        session
+    });
+    (port, handle)
+}
+
+/// JSON の設定値から `AppConfig` を作成します。
+pub fn app_config(value: Value) -> AppConfig {
+    AppConfig::from_value(value).unwrap()
+}
+
+/// テストで使用する監視対象（`Chat`）を返します。
+pub fn sample_app() -> AppInfo {
+    AppInfo {
+        name: "Chat".to_string(),
+        hwnd: 1,
+        process_id: 0,
+        thread_id: 0,
+        icon: None,
+        executable: None,
+    }
+}
+
+/// `sample_app` のアイコンの変化（差分値 `0.2`）を検知したイベントを返します。
+pub fn sample_event() -> NotificationEvent {
+    NotificationEvent::icon_changed(sample_app(), 0.2)
+}
+
+/// 指定した送信結果を順に返し、受け取った検知イベントを記録する通知先。
+pub struct ScriptedNotifier {
+    pub name: &'static str,
+    pub outcomes: Mutex<Vec<DeliveryOutcome>>,
+    pub received: Arc<Mutex<Vec<NotificationEvent>>>,
+    pub retries: bool,
+}
+
+impl ScriptedNotifier {
+    pub fn new(name: &'static str, outcomes: Vec<DeliveryOutcome>) -> Self {
+        Self {
+            name,
+            outcomes: Mutex::new(outcomes),
+            received: Arc::new(Mutex::new(Vec::new())),
+            retries: true,
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for ScriptedNotifier {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> DeliveryOutcome {
+        self.received.lock().unwrap().push(event.clone());
+        self.outcomes.lock().unwrap().remove(0)
+    }
+
+    fn retries_when_failed(&self) -> bool {
+        self.retries
+    }
+}
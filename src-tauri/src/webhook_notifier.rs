@@ -0,0 +1,184 @@
+use async_trait::async_trait;
+use log::warn;
+use reqwest::{Client, Method};
+use serde_json::{Map, Value};
+
+use crate::app_config::AppConfig;
+use crate::notifier::{
+    escape_json_string, non_empty, render_template, DeliveryOutcome, NotificationEvent, Notifier,
+};
+
+/// 本文テンプレートが未設定の場合に送信する本文。
+const DEFAULT_BODY_TEMPLATE: &str = r#"{"app_name":"{app_name}","pid":{pid},"timestamp":"{timestamp}","diff_score":{diff_score},"trigger":"{trigger}"}"#;
+
+/// 任意の HTTP エンドポイントへ通知を送信する汎用 Webhook の通知先。
+///
+/// # 概要
+/// メソッド・URL・ヘッダー・本文テンプレートを設定することで、コードを変更せずに社内システムなどと連携できます。
+/// 本文テンプレートのプレースホルダーは `notifier::template_values` を参照してください。
+///
+/// `Content-Type` が JSON の場合（未指定の場合は `application/json`）、
+/// プレースホルダーの値は JSON の文字列内に埋め込めるようエスケープされます。
+///
+/// # 設定
+/// - `HTTP_WEBHOOK_URL`: 送信先の URL。空の場合、通知先として登録されません。
+/// - `HTTP_WEBHOOK_METHOD`: HTTP メソッド。既定値は `POST`。
+/// - `HTTP_WEBHOOK_HEADERS`: ヘッダー名と値の JSON オブジェクト。
+/// - `HTTP_WEBHOOK_BODY_TEMPLATE`: 本文テンプレート。空の場合は主な項目を含む JSON を送信します。
+pub struct WebhookNotifier {
+    client: Client,
+    method: Method,
+    url: String,
+    headers: Vec<(String, String)>,
+    body_template: String,
+}
+
+impl WebhookNotifier {
+    pub fn from_config(config: &AppConfig, client: &Client) -> Option<Self> {
+        let webhook = &config.http_webhook;
+        let url = non_empty(&webhook.url)?;
+        let method = non_empty(&webhook.method)
+            .unwrap_or_else(|| "POST".to_string())
+            .to_ascii_uppercase();
+        let method = match Method::from_bytes(method.as_bytes()) {
+            Ok(method) => method,
+            Err(_) => {
+                warn!("HTTP_WEBHOOK_METHODの形式が不正なため、Webhook 通知は送信しません。");
+                return None;
+            }
+        };
+        let headers = match parse_headers(&webhook.headers) {
+            Some(headers) => headers,
+            None => {
+                warn!("HTTP_WEBHOOK_HEADERSは文字列値のオブジェクトで指定してください。Webhook 通知は送信しません。");
+                return None;
+            }
+        };
+        Some(Self {
+            client: client.clone(),
+            method,
+            url,
+            headers,
+            body_template: Some(webhook.body_template.as_str())
+                .filter(|template| !template.trim().is_empty())
+                .unwrap_or(DEFAULT_BODY_TEMPLATE)
+                .to_string(),
+        })
+    }
+
+    fn content_type(&self) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// ヘッダーの設定値を解析します。値が文字列でないヘッダーがある場合は `None` を返します。
+pub fn parse_headers(headers: &Map<String, Value>) -> Option<Vec<(String, String)>> {
+    headers
+        .iter()
+        .map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+        .collect()
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn send(&self, event: &NotificationEvent) -> DeliveryOutcome {
+        let content_type = self.content_type().unwrap_or("application/json");
+        let body = if content_type.to_ascii_lowercase().contains("json") {
+            render_template(&self.body_template, event, escape_json_string)
+        } else {
+            render_template(&self.body_template, event, str::to_string)
+        };
+
+        let mut request = self.client.request(self.method.clone(), &self.url);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        if self.content_type().is_none() {
+            request = request.header("Content-Type", content_type);
+        }
+        DeliveryOutcome::from_response(request.body(body).send().await).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WebhookNotifier;
+    use crate::notifier::{http_client, DeliveryOutcome, NotificationEvent, Notifier};
+    use crate::test_support::{app_config, sample_app, stand_in_server};
+    use crate::window_utils::AppInfo;
+    use serde_json::json;
+
+    fn event() -> NotificationEvent {
+        let app = AppInfo {
+            process_id: 42,
+            ..sample_app()
+        };
+        NotificationEvent::icon_changed(app, 0.2)
+    }
+
+    #[test]
+    fn from_config_rejects_invalid_headers() {
+        assert!(WebhookNotifier::from_config(
+            &app_config(json!({
+                "HTTP_WEBHOOK_URL": "http://localhost/hook",
+                "HTTP_WEBHOOK_HEADERS": { "X-Count": 1 }
+            })),
+            &http_client()
+        )
+        .is_none());
+    }
+
+    #[tokio::test]
+    async fn send_uses_method_headers_and_rendered_body() {
+        let (base_url, server) = stand_in_server("204 No Content", "");
+        let notifier = WebhookNotifier::from_config(
+            &app_config(json!({
+                "HTTP_WEBHOOK_URL": format!("{}/hook", base_url),
+                "HTTP_WEBHOOK_METHOD": "put",
+                "HTTP_WEBHOOK_HEADERS": { "X-Api-Key": "k", "Content-Type": "text/plain" },
+                "HTTP_WEBHOOK_BODY_TEMPLATE": "{app_name} ({pid}) {trigger}"
+            })),
+            &http_client(),
+        )
+        .unwrap();
+
+        let outcome = notifier.send(&event()).await;
+        let request = server.join().unwrap();
+
+        assert_eq!(outcome, DeliveryOutcome::Sent);
+        assert!(request.starts_with("PUT /hook "));
+        assert!(request.contains("x-api-key: k"));
+        assert!(request.contains("content-type: text/plain"));
+        assert!(request.ends_with("Chat (42) icon_changed"));
+    }
+
+    #[tokio::test]
+    async fn send_defaults_to_json_body() {
+        let (base_url, server) = stand_in_server("200 OK", "");
+        let notifier = WebhookNotifier::from_config(
+            &app_config(json!({
+                "HTTP_WEBHOOK_URL": format!("{}/hook", base_url)
+            })),
+            &http_client(),
+        )
+        .unwrap();
+
+        let outcome = notifier.send(&event()).await;
+        let request = server.join().unwrap();
+        let body = &request[request.find("\r\n\r\n").unwrap() + 4..];
+        let value: serde_json::Value = serde_json::from_str(body).unwrap();
+
+        assert_eq!(outcome, DeliveryOutcome::Sent);
+        assert!(request.starts_with("POST /hook "));
+        assert!(request.contains("content-type: application/json"));
+        assert_eq!(value["app_name"], "Chat");
+        assert_eq!(value["pid"], 42);
+    }
+}
@@ -2,12 +2,16 @@ use base64::{engine::general_purpose, Engine};
 use image::{DynamicImage, ImageBuffer, ImageFormat, Rgba};
 use log::{error, info, warn};
 use std::{ffi::c_void, io::Cursor, mem::MaybeUninit, ptr::null_mut};
+use windows::core::PWSTR;
 use windows::Win32::{
-    Foundation::{BOOL, HANDLE, HWND, LPARAM, WPARAM},
+    Foundation::{CloseHandle, BOOL, HANDLE, HWND, LPARAM, WPARAM},
     Graphics::Gdi::{
         CreateDIBSection, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, HBITMAP, HGDIOBJ,
     },
-    System::Threading::GetCurrentProcessId,
+    System::Threading::{
+        GetCurrentProcessId, OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    },
     UI::WindowsAndMessaging::{
         DrawIconEx, EnumWindows, GetAncestor, GetClassLongPtrW, GetWindowLongPtrW, GetWindowTextW,
         GetWindowThreadProcessId, IsWindowVisible, SendMessageW, DI_NORMAL, GA_ROOTOWNER,
@@ -123,6 +127,7 @@ unsafe extern "system" fn enum_windows_proc(hwnd: HWND, lparam: LPARAM) -> BOOL
         );
 
         let icon_base64 = get_window_icon_base64(hwnd);
+        let executable = get_process_executable(process_id);
 
         let title_clone = title.clone();
         apps.push(AppInfo {
@@ -131,12 +136,64 @@ unsafe extern "system" fn enum_windows_proc(hwnd: HWND, lparam: LPARAM) -> BOOL
             process_id: process_id,
             thread_id: thread_id,
             icon: icon_base64,
+            executable,
         });
         info!("タイトル：{:?}、追加しました。", title_clone);
     }
     BOOL(1)
 }
 
+/// プロセスの実行ファイルのフルパスを取得する。
+///
+/// # 引数
+/// - `process_id`: 対象のプロセス ID。
+///
+/// # 戻り値
+/// - `Option<String>`:
+///   - 成功時: 実行ファイルのフルパス。
+///   - 失敗時: `None`。
+///
+/// # 注意事項
+/// - 権限の高いプロセスなど、プロセスを開けない場合は `None` を返します。
+///
+unsafe fn get_process_executable(process_id: u32) -> Option<String> {
+    let process = match OpenProcess(
+        PROCESS_QUERY_LIMITED_INFORMATION,
+        BOOL::from(false),
+        process_id,
+    ) {
+        Ok(process) => process,
+        Err(e) => {
+            warn!(
+                "プロセスを開けませんでした。プロセスID：{:?}、エラー: {:?}",
+                process_id, e
+            );
+            return None;
+        }
+    };
+
+    let mut buffer = [0u16; 1024];
+    let mut length = buffer.len() as u32;
+    let result = QueryFullProcessImageNameW(
+        process,
+        PROCESS_NAME_WIN32,
+        PWSTR(buffer.as_mut_ptr()),
+        &mut length,
+    );
+    let _ = CloseHandle(process);
+
+    match result {
+        Ok(_) => Some(String::from_utf16_lossy(&buffer[..length as usize])),
+        Err(e) => {
+            warn!(
+                "実行ファイルのパスを取得できませんでした。プロセスID：{:?}、エラー: {:?}",
+                process_id, e
+            );
+            None
+        }
+    }
+}
+
 /// ウィンドウのアイコンを Base64 エンコードして返す。
 ///
 /// # 引数
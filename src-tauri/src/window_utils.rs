@@ -1,10 +1,15 @@
+use chrono::{DateTime, Local};
 use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 use std::{collections::HashMap, path::PathBuf};
 use tauri::async_runtime::{JoinHandle, Mutex};
-use tauri::State;
+use tauri::{Manager, State};
 use windows::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
 use windows::Win32::UI::WindowsAndMessaging::{SendMessageW, SC_RESTORE, WM_SYSCOMMAND};
 use windows::{
@@ -19,7 +24,11 @@ use windows::{
     },
 };
 
-use crate::monitor::monitor_app_icon;
+use crate::app_config::AppConfig;
+use crate::escalation::AcknowledgeSource;
+use crate::monitor::{monitor_app_icon, MonitorSettings};
+use crate::monitor_persistence::save_monitored_targets;
+use crate::notifier::DeliveryResult;
 
 /// アプリケーション情報を格納する構造体。
 ///
@@ -29,6 +38,7 @@ use crate::monitor::monitor_app_icon;
 /// - `process_id`: プロセス ID。
 /// - `thread_id`: スレッド ID。
 /// - `icon`: アプリケーションのアイコンを Base64 形式でエンコードした文字列。
+/// - `executable`: プロセスの実行ファイルのフルパス。取得できない場合は `None`。
 ///
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AppInfo {
@@ -37,6 +47,31 @@ pub struct AppInfo {
     pub process_id: u32,      // プロセスID
     pub thread_id: u32,       // スレッドID
     pub icon: Option<String>, // アイコンのBase64データ
+    #[serde(default)]
+    pub executable: Option<String>, // 実行ファイルのフルパス
+}
+
+impl AppInfo {
+    /// `MonitorState` で監視対象を識別するためのキーを返します。
+    ///
+    /// ウィンドウタイトルは重複や変化がありうるため、ウィンドウハンドルをキーとして使用します。
+    pub fn target_key(&self) -> String {
+        self.hwnd.to_string()
+    }
+
+    /// 再起動をまたいでアプリケーションを識別するためのキーを返します。
+    ///
+    /// ウィンドウハンドルやプロセス ID は再起動で変わるため、実行ファイル名（小文字）とウィンドウタイトルを組み合わせます。
+    /// 設定ファイルの監視対象ごとの設定は、このキーで保存されます。
+    pub fn identity(&self) -> String {
+        let executable = self
+            .executable
+            .as_deref()
+            .and_then(|path| Path::new(path).file_name())
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        format!("{}|{}", executable, self.name)
+    }
 }
 
 /// 設定ファイルのパスを管理する構造体。
@@ -49,87 +84,469 @@ pub struct ConfigState {
     pub path: PathBuf,
 }
 
+/// 監視対象ごとの状態。
+///
+/// - `Starting`: 監視タスクを起動し、初期画像を取得している。
+/// - `Watching`: アイコンの変化を監視している。
+/// - `Paused`: 一時停止中。監視タスクは停止しているが、監視対象としては保持されている。
+/// - `Unacknowledged`: アイコンの変化を検知し、確認を待っている。エスカレーションポリシーが設定されている場合のみ。
+/// - `Triggered`: アイコンの変化を検知し、監視タスクが終了した。
+/// - `Lost`: 監視対象のウィンドウが見つからなくなり、監視タスクが終了した。
+/// - `Error`: 初期画像の取得などに失敗し、監視タスクが終了した。
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetState {
+    Starting,
+    Watching,
+    Paused,
+    Unacknowledged,
+    Triggered,
+    Lost,
+    Error,
+}
+
+/// 監視対象 1 件分の監視状況。
+///
+/// `get_monitoring_status` コマンドの戻り値、および `monitor_status` イベントのペイロードとして使用します。
+///
+/// # フィールド
+/// - `app`: 監視対象アプリケーションの情報。
+/// - `state`: 監視対象の状態。
+/// - `started_at`: 監視タスクを開始した時刻。
+/// - `last_capture_at`: 最後にアイコン画像の取得に成功した時刻。
+/// - `last_diff_score`: 最後に計算した初期画像との正規化差分値（`0.0〜1.0`）。
+/// - `consecutive_capture_failures`: アイコン画像の取得に連続で失敗した回数。
+/// - `error`: `Error` 状態になった原因。
+/// - `last_delivery`: 最後に検知した際の通知先ごとの送信結果。
+/// - `next_escalation_at`: 確認待ちの場合、次にエスカレーションを行う時刻。
+/// - `acknowledged_by`: 検知した変化が確認されたきっかけ。
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct TargetStatus {
+    pub app: AppInfo,
+    pub state: TargetState,
+    pub started_at: Option<DateTime<Local>>,
+    pub last_capture_at: Option<DateTime<Local>>,
+    pub last_diff_score: Option<f32>,
+    pub consecutive_capture_failures: u32,
+    pub error: Option<String>,
+    pub last_delivery: Vec<DeliveryResult>,
+    pub next_escalation_at: Option<DateTime<Local>>,
+    pub acknowledged_by: Option<AcknowledgeSource>,
+}
+
+impl TargetStatus {
+    fn new(app: AppInfo) -> Self {
+        Self {
+            app,
+            state: TargetState::Starting,
+            started_at: None,
+            last_capture_at: None,
+            last_diff_score: None,
+            consecutive_capture_failures: 0,
+            error: None,
+            last_delivery: Vec::new(),
+            next_escalation_at: None,
+            acknowledged_by: None,
+        }
+    }
+}
+
+/// 監視対象 1 件分の情報。
+///
+/// # フィールド
+/// - `status`: 監視対象の監視状況。
+/// - `settings`: 監視設定。再開時に同じ設定で監視を再起動するため、および停止時のウィンドウ復元判定に使用します。
+/// - `handle`: 実行中の監視タスクのハンドル。一時停止中や終了後は `None`。
+/// - `run_id`: 監視タスクの起動ごとに採番される ID。終了済みタスクが新しいタスクの状態を上書きしないために使用します。
+/// - `acknowledged`: 確認ボタンが押されたかどうか。監視タスクの起動ごとに作成し、`StatusReporter` と共有します。
+///
+struct MonitorTarget {
+    status: TargetStatus,
+    settings: MonitorSettings,
+    handle: Option<JoinHandle<()>>,
+    run_id: u64,
+    acknowledged: Arc<AtomicBool>,
+}
+
+impl MonitorTarget {
+    fn abort(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
+    }
+
+    /// 監視開始時に最小化したウィンドウを、監視の停止時に復元します。
+    ///
+    /// 変化を検知して終了した監視タスクはすでにウィンドウを復元しているため、
+    /// 最大化されていたウィンドウを元のサイズへ戻してしまわないよう、再度は復元しません。
+    fn restore_window_on_stop(&self) {
+        if !self.settings.minimize_on_start {
+            info!("停止時のウィンドウ復元は無効です。");
+            return;
+        }
+        if self.handle.is_none() && self.status.state == TargetState::Triggered {
+            return;
+        }
+        restore_window(self.status.app.hwnd);
+    }
+}
+
+/// 監視タスクから自身の監視状況を `MonitorState` へ報告するためのハンドル。
+///
+/// 監視タスクの起動時に `MonitorState` が生成して渡します。
+/// 監視対象が削除されたり、別の監視タスクへ置き換えられた後の報告は無視されます。
+///
+#[derive(Clone)]
+pub struct StatusReporter {
+    app_handle: tauri::AppHandle,
+    key: String,
+    run_id: u64,
+    acknowledged: Arc<AtomicBool>,
+}
+
+impl StatusReporter {
+    /// 監視状況を更新し、`monitor_status` イベントを発行します。
+    pub async fn update<F: FnOnce(&mut TargetStatus)>(&self, f: F) {
+        self.app_handle
+            .state::<MonitorState>()
+            .update_status(&self.key, self.run_id, f)
+            .await;
+    }
+
+    /// 確認待ちの監視対象の確認ボタンが押されたかどうかを返します。
+    pub fn is_acknowledged(&self) -> bool {
+        self.acknowledged.load(Ordering::SeqCst)
+    }
+
+    /// 監視タスクが終了したことを記録します。
+    async fn finish(&self) {
+        self.app_handle
+            .state::<MonitorState>()
+            .mark_finished(&self.key, self.run_id)
+            .await;
+    }
+}
+
 /// アプリケーション監視の状態を管理する構造体。
 ///
 /// # 機能
-/// - 各監視タスクを非同期で管理。
+/// - 監視対象ごとに非同期タスクと監視状況を管理。
+/// - 監視対象単位での追加・削除・一時停止・再開。
 /// - 全監視タスクの停止処理。
+/// - 監視状況が変化した際の `monitor_status` イベントの発行。
 ///
 /// # フィールド
-/// - `tasks`: タスク名をキーとし、対応する非同期タスクのハンドルを格納するマップ。
+/// - `app_handle`: イベントの発行と監視タスクの起動に使用する Tauri の `AppHandle`。
+/// - `targets`: 監視対象キー（`AppInfo::target_key`）をキーとし、監視対象の情報を格納するマップ。
+/// - `next_run_id`: 次に起動する監視タスクへ割り当てる ID。
+/// - `targets_file`: 再起動後に監視を再開するため、監視対象の一覧を保存するファイルのパス。
 ///
 pub struct MonitorState {
-    pub tasks: Mutex<HashMap<String, JoinHandle<()>>>,
-    restore_windows_on_stop: Mutex<bool>,
+    app_handle: tauri::AppHandle,
+    targets: Mutex<HashMap<String, MonitorTarget>>,
+    next_run_id: Mutex<u64>,
+    targets_file: Option<PathBuf>,
 }
 impl MonitorState {
-    pub fn new() -> Self {
+    pub fn new(app_handle: tauri::AppHandle, targets_file: Option<PathBuf>) -> Self {
         Self {
-            tasks: Mutex::new(HashMap::new()),
-            restore_windows_on_stop: Mutex::new(true),
+            app_handle,
+            targets: Mutex::new(HashMap::new()),
+            next_run_id: Mutex::new(0),
+            targets_file,
         }
     }
 
-    pub async fn stop_all(&self, apps: Vec<AppInfo>) {
+    /// 監視対象の一覧を保存するファイルのパスを返します。
+    pub fn targets_file(&self) -> Option<PathBuf> {
+        self.targets_file.clone()
+    }
+
+    /// すべての監視タスクを停止し、監視対象を空にします。
+    ///
+    /// 監視開始時に最小化した監視対象のウィンドウは復元します。
+    /// 次回起動時に監視が再開されないよう、空の監視対象の一覧を保存します。
+    pub async fn stop_all(&self) {
         info!("stop_allを呼び出しました。");
-        let mut tasks = self.tasks.lock().await;
-        for (_, handle) in tasks.drain() {
-            handle.abort();
+        let mut targets = self.targets.lock().await;
+        self.stop_targets(&mut targets);
+        self.persist_targets(&targets);
+    }
+
+    /// アプリケーションの終了時に、すべての監視タスクを停止します。
+    ///
+    /// 次回起動時に監視を再開できるよう、`stop_all` と異なり保存済みの監視対象の一覧は変更しません。
+    pub async fn shutdown(&self) {
+        info!("shutdownを呼び出しました。");
+        let mut targets = self.targets.lock().await;
+        self.stop_targets(&mut targets);
+    }
+
+    /// 監視対象の監視タスクを停止して監視対象を空にし、監視開始時に最小化したウィンドウを復元します。
+    fn stop_targets(&self, targets: &mut HashMap<String, MonitorTarget>) {
+        for (key, mut target) in targets.drain() {
+            target.restore_window_on_stop();
+            target.abort();
+            self.emit_removed(&key);
         }
+    }
 
-        let restore_windows = *self.restore_windows_on_stop.lock().await;
-        if !restore_windows {
-            info!("停止時のウィンドウ復元は無効です。");
-            return;
+    /// 監視対象を追加し、監視タスクを開始します。
+    ///
+    /// 同じ監視対象がすでに存在する場合は、既存の監視タスクを停止してから新しい設定で開始し直します。
+    /// 他の監視対象の監視タスクには影響しません。
+    pub async fn monitor_target<'a>(
+        &self,
+        app_info: AppInfo,
+        settings: MonitorSettings,
+        config_state: State<'a, ConfigState>,
+    ) {
+        info!("monitor_targetを呼び出しました。");
+        let key = app_info.target_key();
+        let mut targets = self.targets.lock().await;
+        if let Some(mut previous) = targets.remove(&key) {
+            info!(
+                "監視対象：{:?}の既存の監視タスクを停止します。",
+                previous.status.app.name
+            );
+            previous.abort();
         }
 
-        for app in apps {
-            unsafe {
-                let result = SendMessageW(
-                    HWND(app.hwnd as *mut _),
-                    WM_SYSCOMMAND,
-                    WPARAM(SC_RESTORE as usize),
-                    LPARAM(0),
-                );
-                if result == LRESULT(0) {
-                    info!("ウィンドウを復元しました。 (返り値: {:?})", result);
-                } else {
-                    error!("ウィンドウの復元に失敗しました。： {:?}", result);
-                    return;
-                }
+        let mut target = MonitorTarget {
+            status: TargetStatus::new(app_info),
+            settings,
+            handle: None,
+            run_id: 0,
+            acknowledged: Arc::new(AtomicBool::new(false)),
+        };
+        self.spawn_target(&key, &mut target, config_state.path.clone())
+            .await;
+        targets.insert(key, target);
+        self.persist_targets(&targets);
+    }
+
+    /// 指定した監視対象の監視を停止し、監視対象から削除します。
+    ///
+    /// # 戻り値
+    /// * `true` - 監視対象が存在し、削除した場合。
+    /// * `false` - 監視対象が存在しなかった場合。
+    pub async fn remove_target(&self, key: &str) -> bool {
+        let mut targets = self.targets.lock().await;
+        match targets.remove(key) {
+            Some(mut target) => {
+                target.restore_window_on_stop();
+                target.abort();
+                info!("監視対象：{:?}を削除しました。", target.status.app.name);
+                self.emit_removed(key);
+                self.persist_targets(&targets);
+                true
             }
+            None => false,
         }
     }
 
-    pub async fn set_restore_windows_on_stop(&self, restore_windows: bool) {
-        let mut restore_windows_on_stop = self.restore_windows_on_stop.lock().await;
-        *restore_windows_on_stop = restore_windows;
+    /// 指定した監視対象の監視を一時停止します。
+    ///
+    /// 監視タスクは停止しますが、監視対象としての情報と設定は保持されます。
+    /// 監視開始時に最小化したウィンドウは復元します。
+    pub async fn pause_target(&self, key: &str) -> Result<(), String> {
+        let mut targets = self.targets.lock().await;
+        let target = targets
+            .get_mut(key)
+            .ok_or_else(|| format!("監視対象が見つかりません: {}", key))?;
+        if target.handle.is_none() {
+            return Err(format!(
+                "監視対象：{}は実行中ではありません。({:?})",
+                target.status.app.name, target.status.state
+            ));
+        }
+
+        target.abort();
+        target.status.state = TargetState::Paused;
+        if target.settings.minimize_on_start {
+            restore_window(target.status.app.hwnd);
+        }
+        info!("監視対象：{:?}を一時停止しました。", target.status.app.name);
+        self.emit_status(&target.status);
+        Ok(())
     }
 
-    pub async fn monitor_target<'a>(
+    /// 確認待ちの監視対象を確認済みにし、保留中のエスカレーションを取り消します。
+    ///
+    /// 監視タスクは次の監視間隔で確認済みであることを検出し、検知後と同じ手順で終了します。
+    pub async fn acknowledge_target(&self, key: &str) -> Result<(), String> {
+        let targets = self.targets.lock().await;
+        let target = targets
+            .get(key)
+            .ok_or_else(|| format!("監視対象が見つかりません: {}", key))?;
+        if target.handle.is_none() || target.status.state != TargetState::Unacknowledged {
+            return Err(format!(
+                "監視対象：{}は確認待ちではありません。({:?})",
+                target.status.app.name, target.status.state
+            ));
+        }
+
+        target.acknowledged.store(true, Ordering::SeqCst);
+        info!(
+            "監視対象：{:?}を確認済みにしました。",
+            target.status.app.name
+        );
+        Ok(())
+    }
+
+    /// 一時停止中、または終了済みの監視対象の監視を再開します。
+    ///
+    /// 保持している監視設定で監視タスクを起動し直すため、
+    /// 比較元のアイコン画像は再開時点で取得し直されます。
+    pub async fn resume_target<'a>(
         &self,
-        app_info: AppInfo,
-        interval: u64,
-        threshold: f32,
-        minimize_on_start: bool,
+        key: &str,
         config_state: State<'a, ConfigState>,
-        app_handle: tauri::AppHandle,
-    ) {
-        info!("monitor_targetを呼び出しました。");
-        let app_name = app_info.name.clone();
-        let config_path = config_state.path.clone();
+    ) -> Result<(), String> {
+        let mut targets = self.targets.lock().await;
+        let target = targets
+            .get_mut(key)
+            .ok_or_else(|| format!("監視対象が見つかりません: {}", key))?;
+        if target.handle.is_some() {
+            return Err(format!(
+                "監視対象：{}はすでに実行中です。",
+                target.status.app.name
+            ));
+        }
+
+        self.spawn_target(key, target, config_state.path.clone())
+            .await;
+        info!(
+            "監視対象：{:?}の監視を再開しました。",
+            target.status.app.name
+        );
+        Ok(())
+    }
+
+    /// すべての監視対象の監視状況を取得します。
+    pub async fn statuses(&self) -> Vec<TargetStatus> {
+        let targets = self.targets.lock().await;
+        let mut statuses: Vec<TargetStatus> = targets
+            .values()
+            .map(|target| target.status.clone())
+            .collect();
+        statuses.sort_by(|a, b| a.app.name.cmp(&b.app.name));
+        statuses
+    }
+
+    /// 監視状況を更新し、`monitor_status` イベントを発行します。
+    ///
+    /// `run_id` が現在の監視タスクと一致しない場合は、すでに別の監視タスクへ置き換えられているため何もしません。
+    async fn update_status<F: FnOnce(&mut TargetStatus)>(&self, key: &str, run_id: u64, f: F) {
+        let mut targets = self.targets.lock().await;
+        if let Some(target) = targets.get_mut(key) {
+            if target.run_id == run_id && target.handle.is_some() {
+                f(&mut target.status);
+                self.emit_status(&target.status);
+            }
+        }
+    }
+
+    /// 監視タスクが終了したことを記録します。
+    async fn mark_finished(&self, key: &str, run_id: u64) {
+        let mut targets = self.targets.lock().await;
+        if let Some(target) = targets.get_mut(key) {
+            if target.run_id == run_id {
+                target.handle = None;
+            }
+        }
+    }
+
+    async fn spawn_target(&self, key: &str, target: &mut MonitorTarget, config_path: PathBuf) {
+        let run_id = {
+            let mut next_run_id = self.next_run_id.lock().await;
+            *next_run_id += 1;
+            *next_run_id
+        };
+        let acknowledged = Arc::new(AtomicBool::new(false));
+        let reporter = StatusReporter {
+            app_handle: self.app_handle.clone(),
+            key: key.to_string(),
+            run_id,
+            acknowledged: acknowledged.clone(),
+        };
+        let app_info = target.status.app.clone();
+        let settings = target.settings.clone();
+        let app_handle = self.app_handle.clone();
         let handle = tauri::async_runtime::spawn(async move {
             monitor_app_icon(
                 app_info,
-                interval,
-                threshold,
-                minimize_on_start,
+                settings,
                 config_path,
                 app_handle,
+                reporter.clone(),
             )
             .await;
+            reporter.finish().await;
         });
-        self.tasks.lock().await.insert(app_name, handle);
+
+        target.run_id = run_id;
+        target.acknowledged = acknowledged;
+        target.handle = Some(handle);
+        target.status = TargetStatus {
+            state: TargetState::Starting,
+            started_at: Some(Local::now()),
+            ..TargetStatus::new(target.status.app.clone())
+        };
+        self.emit_status(&target.status);
+    }
+
+    /// 監視対象の一覧をファイルへ保存します。
+    ///
+    /// アプリケーションが異常終了した場合や PC が再起動した場合でも、次回起動時に監視を再開できるようにするためです。
+    fn persist_targets(&self, targets: &HashMap<String, MonitorTarget>) {
+        if let Some(path) = &self.targets_file {
+            let apps: Vec<AppInfo> = targets
+                .values()
+                .map(|target| target.status.app.clone())
+                .collect();
+            save_monitored_targets(path, &apps);
+        }
+    }
+
+    fn emit_status(&self, status: &TargetStatus) {
+        if let Err(e) = self.app_handle.emit_all("monitor_status", status) {
+            error!("monitor_statusイベントの送信に失敗しました: {:?}", e);
+        }
+    }
+
+    fn emit_removed(&self, key: &str) {
+        if let Err(e) = self.app_handle.emit_all("monitor_target_removed", key) {
+            error!(
+                "monitor_target_removedイベントの送信に失敗しました: {:?}",
+                e
+            );
+        }
+    }
+}
+
+/// 指定したウィンドウを元のサイズへ復元します。
+///
+/// # 戻り値
+/// * `true` - 復元に成功した場合。
+/// * `false` - 復元に失敗した場合。
+pub fn restore_window(hwnd: isize) -> bool {
+    unsafe {
+        let result = SendMessageW(
+            HWND(hwnd as *mut _),
+            WM_SYSCOMMAND,
+            WPARAM(SC_RESTORE as usize),
+            LPARAM(0),
+        );
+        if result == LRESULT(0) {
+            info!("ウィンドウを復元しました。 (返り値: {:?})", result);
+            true
+        } else {
+            error!("ウィンドウの復元に失敗しました。： {:?}", result);
+            false
+        }
     }
 }
 
@@ -431,11 +848,12 @@ pub fn get_or_create_config_file_path(config_dir: &Path, file_name: &str) -> Pat
 
 /// 指定されたパスにデフォルトの設定ファイル ("appsettings.json") を初期化（書き込み）します。
 ///
-/// 書き込み内容は以下のJSON形式で、各項目はユーザーが後で更新する前提です:
+/// 書き込み内容は `AppConfig` の既定値で、各項目はユーザーが後で更新する前提です:
 /// {
-///     "DISCORD_WEBHOOK_URL": "ここにDiscordBotURLを入力してください",
-///     "THRESHOLD": "0.050",
-///     "INTERVAL": "3000"
+///     "DISCORD_WEBHOOK_URL": "",
+///     "THRESHOLD": "0.05",
+///     "INTERVAL": "1000",
+///     ...
 /// }
 ///
 /// # 引数
@@ -449,18 +867,7 @@ pub fn get_or_create_config_file_path(config_dir: &Path, file_name: &str) -> Pat
 /// ```
 ///
 pub fn initilize_config_file(config_file: &Path) {
-    match fs::write(
-        config_file,
-        r#"{
-"DISCORD_WEBHOOK_URL": "",
-"LINE_ENABLED": "false",
-"LINE_CHANNEL_ACCESS_TOKEN": "",
-"LINE_TARGET": "",
-"THRESHOLD": "0.050",
-"INTERVAL": "1000",
-"MINIMIZE_ON_MONITOR_START": "true"
-}"#,
-    ) {
+    match AppConfig::default().save(config_file) {
         Ok(_) => info!("設定ファイルを初期化しました。"),
         Err(e) => warn!("設定ファイルの初期化に失敗しました: {:?}", e),
     }
@@ -1,4 +1,4 @@
-import React, { useEffect, useRef, useState } from "react";
+import React, { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/tauri";
 import { dialog } from "@tauri-apps/api";
 import { listen, UnlistenFn } from "@tauri-apps/api/event";
@@ -7,7 +7,13 @@ import { EyeAnimation } from "./EyeAnimation";
 import { ListSection } from "./ListSection";
 import { MoveButtons } from "./MoveButtons";
 import { PrimaryActionButtons } from "./PrimaryActionButtons";
-import { AppInfo } from "./types";
+import {
+    AppConfig,
+    AppInfo,
+    DeliveryFailureReport,
+    ResumeReport,
+    TargetStatus,
+} from "./types";
 import { logFrontend } from "./logger";
 import "./App.css";
 import "./mystyle.css";
@@ -29,7 +35,8 @@ function App() {
     const [lineChannelAccessToken, setLineChannelAccessToken] =
         useState<string>("");
     const [lineTarget, setLineTarget] = useState<string>("");
-    const [currentWebhookUrl, setCurrentWebhookUrl] = useState<string>("");
+    const [currentWebhookUrlConfigured, setCurrentWebhookUrlConfigured] =
+        useState(false);
     const [currentThreshold, setCurrentThreshold] = useState<string>("");
     const [currentInterval, setCurrentInterval] = useState<string>("");
     const [lineEnabled, setLineEnabled] = useState(false);
@@ -40,12 +47,6 @@ function App() {
     ] = useState(false);
     const [currentLineTarget, setCurrentLineTarget] = useState<string>("");
     const [isMonitoring, setIsMonitoring] = useState(false);
-    const monitoredItemsRef = useRef<AppInfo[]>([]);
-
-    useEffect(() => {
-        monitoredItemsRef.current = monitoredItems;
-    }, [monitoredItems]);
-
     useEffect(() => {
         (async () => {
             logFrontend("info", "fetchWindows called");
@@ -60,6 +61,38 @@ function App() {
     const fetchWindows = async () => {
         await fetchGetTaskbarApps();
         await fetchGetConfig();
+        await fetchGetResumeReport();
+    };
+
+    const applyResumeReport = async (report: ResumeReport) => {
+        const resumedHwnds = new Set(report.resumed.map((app) => app.hwnd));
+        setMonitoredItems(report.resumed);
+        setAvailableItems((items) =>
+            items.filter((item) => !resumedHwnds.has(item.hwnd))
+        );
+        setIsMonitoring(report.resumed.length > 0);
+        const notResumed = [...report.missing, ...report.failed];
+        if (notResumed.length > 0) {
+            await dialog.message(
+                `次の監視対象は監視を再開できませんでした。\n${notResumed
+                    .map((target) => target.name)
+                    .join("\n")}`,
+                { title: "監視の再開" }
+            );
+        }
+    };
+
+    const fetchGetResumeReport = async () => {
+        try {
+            const report: ResumeReport | null = await invoke(
+                "get_resume_report"
+            );
+            if (report) {
+                await applyResumeReport(report);
+            }
+        } catch (e) {
+            logFrontend("error", `get_resume_report failed: ${e}`);
+        }
     };
 
     const fetchGetTaskbarApps = async () => {
@@ -71,27 +104,30 @@ function App() {
         }
     };
 
+    const updateAppConfig = async (changes: Partial<AppConfig>) => {
+        const config: AppConfig = await invoke("get_app_config");
+        await invoke("update_app_config", {
+            config: { ...config, ...changes },
+        });
+    };
+
     const fetchGetConfig = async () => {
         try {
-            const url: string = await invoke("get_webhook_url");
-            const threshold: string = await invoke("get_threshold");
-            const interval: string = await invoke("get_interval");
-            const lineEnabledValue: string = await invoke("get_line_enabled");
-            const lineTokenConfigured: boolean = await invoke(
-                "get_line_channel_access_token_configured"
+            const config: AppConfig = await invoke("get_app_config");
+            setCurrentWebhookUrlConfigured(
+                config.DISCORD_WEBHOOK_URL === null
             );
-            const lineTargetValue: string = await invoke("get_line_target");
-            const minimizeOnStartValue: boolean = await invoke(
-                "get_minimize_on_monitor_start"
+            setCurrentThreshold(config.THRESHOLD);
+            setCurrentInterval(config.INTERVAL);
+            setLineEnabled(config.LINE_ENABLED === "true");
+            setMinimizeOnMonitorStart(
+                config.MINIMIZE_ON_MONITOR_START === "true"
             );
-            setCurrentWebhookUrl(url);
-            setCurrentThreshold(threshold);
-            setCurrentInterval(interval);
-            setLineEnabled(lineEnabledValue === "true");
-            setMinimizeOnMonitorStart(minimizeOnStartValue);
-            setCurrentLineChannelAccessTokenConfigured(lineTokenConfigured);
-            setCurrentLineTarget(lineTargetValue);
-            setLineTarget(lineTargetValue);
+            setCurrentLineChannelAccessTokenConfigured(
+                config.LINE_CHANNEL_ACCESS_TOKEN === null
+            );
+            setCurrentLineTarget(config.LINE_TARGET);
+            setLineTarget(config.LINE_TARGET);
         } catch (e) {
             logFrontend("error", `get_config failed: ${e}`);
         }
@@ -101,13 +137,96 @@ function App() {
         let unlisten: UnlistenFn | undefined;
         (async () => {
             try {
-                unlisten = await listen("monitoring_stopped", async () => {
-                    logFrontend("info", "monitoring_stopped event received");
-                    await invoke("stop_monitoring", {
-                        apps: [...monitoredItemsRef.current],
-                    });
-                    setIsMonitoring(false);
-                });
+                unlisten = await listen<AppInfo>(
+                    "monitor_target_stopped",
+                    async (event) => {
+                        logFrontend(
+                            "info",
+                            "monitor_target_stopped event received"
+                        );
+                        await invoke("remove_monitoring_target", {
+                            app: event.payload,
+                        });
+                        const statuses: TargetStatus[] = await invoke(
+                            "get_monitoring_status"
+                        );
+                        setIsMonitoring(
+                            statuses.some((status) =>
+                                ["starting", "watching", "unacknowledged"].includes(
+                                    status.state
+                                )
+                            )
+                        );
+                    }
+                );
+            } catch (e) {
+                logFrontend("error", `failed to register listener: ${e}`);
+            }
+        })();
+
+        return () => {
+            if (unlisten) {
+                try {
+                    unlisten();
+                } catch (e) {
+                    logFrontend("error", `failed to remove listener: ${e}`);
+                }
+            }
+        };
+    }, []);
+
+    useEffect(() => {
+        let unlisten: UnlistenFn | undefined;
+        (async () => {
+            try {
+                unlisten = await listen<ResumeReport>(
+                    "monitoring_resumed",
+                    async (event) => {
+                        logFrontend("info", "monitoring_resumed event received");
+                        await applyResumeReport(event.payload);
+                    }
+                );
+            } catch (e) {
+                logFrontend("error", `failed to register listener: ${e}`);
+            }
+        })();
+
+        return () => {
+            if (unlisten) {
+                try {
+                    unlisten();
+                } catch (e) {
+                    logFrontend("error", `failed to remove listener: ${e}`);
+                }
+            }
+        };
+    }, []);
+
+    useEffect(() => {
+        let unlisten: UnlistenFn | undefined;
+        (async () => {
+            try {
+                unlisten = await listen<DeliveryFailureReport>(
+                    "notification_delivery_failed",
+                    async (event) => {
+                        logFrontend(
+                            "info",
+                            "notification_delivery_failed event received"
+                        );
+                        const { app, failures } = event.payload;
+                        await dialog.message(
+                            `「${app.name}」の通知を送信できませんでした。通知先の設定を確認してください。\n${failures
+                                .map((failure) =>
+                                    failure.outcome === "failed" &&
+                                    failure.status !== null
+                                        ? `${failure.notifier}: ${failure.status}`
+                                        : failure.notifier
+                                )
+                                .join("\n")}`,
+                            { title: "通知の送信エラー", type: "error" }
+                        );
+                    }
+                );
             } catch (e) {
                 logFrontend("error", `failed to register listener: ${e}`);
             }
@@ -131,7 +250,7 @@ function App() {
                 { title: "確認" }
             );
             if (!confirmed) return;
-            await invoke("stop_monitoring", { apps: [...monitoredItems] });
+            await invoke("stop_monitoring");
             setIsMonitoring(false);
             setMonitoredItems([]);
             initSelectedAvailableItem();
@@ -184,6 +303,15 @@ function App() {
     };
 
     const moveToMonitored = async () => {
+        const item = selectedAvailableItem.item;
+        if (isMonitoring && item) {
+            try {
+                await invoke("add_monitoring_target", { app: item });
+            } catch (e) {
+                logFrontend("error", `add_monitoring_target failed: ${e}`);
+                return;
+            }
+        }
         handleMove(
             availableItems,
             monitoredItems,
@@ -196,6 +324,15 @@ function App() {
     };
 
     const moveToAvailable = async () => {
+        const item = selectedMonitoredItem.item;
+        if (isMonitoring && item) {
+            try {
+                await invoke("remove_monitoring_target", { app: item });
+            } catch (e) {
+                logFrontend("error", `remove_monitoring_target failed: ${e}`);
+                return;
+            }
+        }
         handleMove(
             monitoredItems,
             availableItems,
@@ -236,7 +373,7 @@ function App() {
 
     const handleStopMonitoring = async () => {
         try {
-            await invoke("stop_monitoring", { apps: [...monitoredItems] });
+            await invoke("stop_monitoring");
             setIsMonitoring(false);
         } catch (e) {
             logFrontend("error", `stop_monitoring failed: ${e}`);
@@ -245,8 +382,6 @@ function App() {
 
     const handleClose = async () => {
         try {
-            await invoke("stop_monitoring", { apps: [...monitoredItems] });
-            setIsMonitoring(false);
             window.close();
         } catch (e) {
             logFrontend("error", `close failed: ${e}`);
@@ -288,12 +423,10 @@ function App() {
     ) => {
         const enabled = event.target.checked;
         try {
-            await invoke("update_line_enabled", {
-                enabled: enabled ? "true" : "false",
-            });
+            await updateAppConfig({ LINE_ENABLED: enabled ? "true" : "false" });
             setLineEnabled(enabled);
         } catch (e) {
-            logFrontend("error", `update_line_enabled failed: ${e}`);
+            logFrontend("error", `update_app_config failed: ${e}`);
         }
     };
 
@@ -302,15 +435,12 @@ function App() {
     ) => {
         const enabled = event.target.checked;
         try {
-            await invoke("update_minimize_on_monitor_start", {
-                enabled,
+            await updateAppConfig({
+                MINIMIZE_ON_MONITOR_START: enabled ? "true" : "false",
             });
             setMinimizeOnMonitorStart(enabled);
         } catch (e) {
-            logFrontend(
-                "error",
-                `update_minimize_on_monitor_start failed: ${e}`
-            );
+            logFrontend("error", `update_app_config failed: ${e}`);
         }
     };
 
@@ -328,28 +458,28 @@ function App() {
 
     const handleSetWebhookUrl = async () => {
         try {
-            await invoke("update_webhook_url", { url: webhookUrl });
-            setCurrentWebhookUrl(webhookUrl);
+            await updateAppConfig({ DISCORD_WEBHOOK_URL: webhookUrl.trim() });
+            setCurrentWebhookUrlConfigured(webhookUrl.trim() !== "");
         } catch (e) {
-            logFrontend("error", `update_webhook_url failed: ${e}`);
+            logFrontend("error", `update_app_config failed: ${e}`);
         }
     };
 
     const handleSetThreshold = async () => {
         try {
-            await invoke("update_threshold", { threshold });
+            await updateAppConfig({ THRESHOLD: threshold.trim() });
             setCurrentThreshold(threshold);
         } catch (e) {
-            logFrontend("error", `update_threshold failed: ${e}`);
+            logFrontend("error", `update_app_config failed: ${e}`);
         }
     };
 
     const handleSetInterval = async () => {
         try {
-            await invoke("update_interval", { interval });
+            await updateAppConfig({ INTERVAL: interval.trim() });
             setCurrentInterval(interval);
         } catch (e) {
-            logFrontend("error", `update_interval failed: ${e}`);
+            logFrontend("error", `update_app_config failed: ${e}`);
         }
     };
 
@@ -359,14 +489,18 @@ function App() {
                 lineChannelAccessToken.trim();
             const trimmedLineTarget = lineTarget.trim();
 
+            const changes: Partial<AppConfig> = {
+                LINE_TARGET: trimmedLineTarget,
+            };
+            if (trimmedLineChannelAccessToken !== "") {
+                changes.LINE_CHANNEL_ACCESS_TOKEN =
+                    trimmedLineChannelAccessToken;
+            }
+            await updateAppConfig(changes);
             if (trimmedLineChannelAccessToken !== "") {
-                await invoke("update_line_channel_access_token", {
-                    token: trimmedLineChannelAccessToken,
-                });
                 setCurrentLineChannelAccessTokenConfigured(true);
                 setLineChannelAccessToken("");
             }
-            await invoke("update_line_target", { target: trimmedLineTarget });
             setLineTarget(trimmedLineTarget);
             setCurrentLineTarget(trimmedLineTarget);
         } catch (e) {
@@ -401,7 +535,7 @@ function App() {
                     webhookUrl={webhookUrl}
                     handleWebhookUrlChange={handleWebhookUrlChange}
                     handleSetWebhookUrl={handleSetWebhookUrl}
-                    currentWebhookUrl={currentWebhookUrl}
+                    currentWebhookUrlConfigured={currentWebhookUrlConfigured}
                     lineEnabled={lineEnabled}
                     handleLineEnabledChange={handleLineEnabledChange}
                     lineChannelAccessToken={lineChannelAccessToken}
@@ -5,7 +5,7 @@ interface ConfigContainerProps {
     webhookUrl: string;
     handleWebhookUrlChange: (e: React.ChangeEvent<HTMLInputElement>) => void;
     handleSetWebhookUrl: () => void;
-    currentWebhookUrl: string;
+    currentWebhookUrlConfigured: boolean;
     lineEnabled: boolean;
     handleLineEnabledChange: (e: React.ChangeEvent<HTMLInputElement>) => void;
     lineChannelAccessToken: string;
@@ -42,7 +42,7 @@ export const ConfigContainer: React.FC<ConfigContainerProps> = ({
     webhookUrl,
     handleWebhookUrlChange,
     handleSetWebhookUrl,
-    currentWebhookUrl,
+    currentWebhookUrlConfigured,
     lineEnabled,
     handleLineEnabledChange,
     lineChannelAccessToken,
@@ -95,7 +95,10 @@ export const ConfigContainer: React.FC<ConfigContainerProps> = ({
                             <button onClick={handleSetWebhookUrl}>Set</button>
                         </div>
                         <div className="current-value">
-                            Current URL: {currentWebhookUrl || "Not configured"}
+                            URL:{" "}
+                            {currentWebhookUrlConfigured
+                                ? "Configured"
+                                : "Not configured"}
                         </div>
                     </div>
 
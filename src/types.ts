@@ -4,4 +4,190 @@ export interface AppInfo {
     process_id: number;
     thread_id: number;
     icon: string | null;
+    executable: string | null;
+}
+
+export interface PersistedTarget {
+    identity: string;
+    name: string;
+    executable: string | null;
+}
+
+export interface ResumeReport {
+    resumed: AppInfo[];
+    missing: PersistedTarget[];
+    failed: PersistedTarget[];
+}
+
+export type DeliveryOutcome =
+    | { outcome: "sent" }
+    | {
+          outcome: "failed";
+          status: number | null;
+          message: string;
+          permanent: boolean;
+      }
+    | {
+          outcome: "rate_limited";
+          retry_after_ms: number | null;
+          message: string;
+      };
+
+export type DeliveryResult = DeliveryOutcome & {
+    notifier: string;
+    delivered_at: string;
+    attempts: number;
+};
+
+export interface DeliveryFailureReport {
+    app: AppInfo;
+    failures: DeliveryResult[];
+}
+
+export interface NotificationEvent {
+    app: AppInfo;
+    trigger: "icon_changed";
+    detected_at: string;
+    diff_score: number;
+    delayed: boolean;
+}
+
+export interface QueuedNotification {
+    id: number;
+    notifier: string;
+    event: NotificationEvent;
+    captured_icon_base64: string | null;
+    queued_at: string;
+    attempts: number;
+    last_error: string;
+}
+
+export type NotificationLanguage = "ja" | "en";
+
+export interface NotifierMessageTemplate {
+    notifier: string;
+    template: string;
+    default_template: string;
+}
+
+export interface MessageTemplateSettings {
+    language: NotificationLanguage;
+    templates: NotifierMessageTemplate[];
+}
+
+export interface RoutingRule {
+    name: string;
+    title_pattern: string;
+    executable: string;
+    notifiers: string[];
+}
+
+export type NotifierKind =
+    | "discord"
+    | "line"
+    | "slack"
+    | "teams"
+    | "telegram"
+    | "ntfy"
+    | "gotify"
+    | "pushover"
+    | "email"
+    | "webhook"
+    | "command";
+
+export interface NotifierInstance {
+    id: string;
+    kind: NotifierKind;
+    label: string;
+    enabled: boolean;
+    settings: Record<string, unknown>;
+}
+
+export interface NotifierInstanceSummary extends NotifierInstance {
+    configured_secrets: string[];
+}
+
+export interface AppConfig {
+    THRESHOLD: string;
+    INTERVAL: string;
+    MINIMIZE_ON_MONITOR_START: string;
+    RESUME_MONITORING_ON_STARTUP: string;
+    DISCORD_WEBHOOK_URL: string | null;
+    LINE_ENABLED: string;
+    LINE_CHANNEL_ACCESS_TOKEN: string | null;
+    LINE_TARGET: string;
+    TARGET_SETTINGS: Record<string, unknown>;
+    NOTIFY_RETRY_MAX_ATTEMPTS: string;
+    NOTIFY_RETRY_INITIAL_DELAY_MS: string;
+    NOTIFY_LANGUAGE: NotificationLanguage;
+    MESSAGE_TEMPLATES: Record<string, string>;
+    ROUTING_RULES: RoutingRule[];
+    NOTIFIER_INSTANCES: NotifierInstance[];
+    [key: string]: unknown;
+}
+
+export interface TestNotificationResult {
+    notifier: string;
+    success: boolean;
+    status: number | null;
+    message: string;
+}
+
+export type EscalationAction =
+    | { action: "resend" }
+    | { action: "notify"; notifiers: string[] }
+    | { action: "raise_priority" };
+
+export type EscalationStep = EscalationAction & {
+    after_minutes: number;
+};
+
+export interface EscalationPolicy {
+    steps: EscalationStep[];
+}
+
+export type AcknowledgeSource = "button" | "window_activated" | "icon_restored";
+
+export type TargetState =
+    | "starting"
+    | "watching"
+    | "paused"
+    | "unacknowledged"
+    | "triggered"
+    | "lost"
+    | "error";
+
+export interface TargetStatus {
+    app: AppInfo;
+    state: TargetState;
+    started_at: string | null;
+    last_capture_at: string | null;
+    last_diff_score: number | null;
+    consecutive_capture_failures: number;
+    error: string | null;
+    last_delivery: DeliveryResult[];
+    next_escalation_at: string | null;
+    acknowledged_by: AcknowledgeSource | null;
+}
+
+export type HistoryKind = "detection" | "escalation" | "queued";
+
+export interface HistoryEntry {
+    kind: HistoryKind;
+    recorded_at: string;
+    app_name: string;
+    executable: string | null;
+    trigger: string;
+    detected_at: string;
+    diff_score: number;
+    threshold: number | null;
+    escalated_after_minutes: number | null;
+    deliveries: DeliveryResult[];
+}
+
+export interface HistoryFilter {
+    from?: string;
+    to?: string;
+    target?: string;
+    limit?: number;
 }